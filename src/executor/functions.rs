@@ -0,0 +1,819 @@
+//! Built-in scalar functions
+//!
+//! Every function returns NULL when any argument is NULL. String functions
+//! count and slice Unicode characters, not bytes.
+
+use super::ExecutionError;
+use crate::types::{
+    DataType, Decimal, RoundingMode, TypeMode, Uuid, Value, datetime, decode_hex, encode_hex, json,
+};
+use chrono::format::{Item, StrftimeItems};
+use chrono::{DateTime, Datelike, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc};
+use std::fmt::{self, Display, Write};
+
+/// Signature of a built-in function
+struct Signature {
+    /// Minimum number of arguments
+    min_args: usize,
+    /// Maximum number of arguments (None for variadic functions)
+    max_args: Option<usize>,
+    /// Type of the returned value
+    return_type: DataType,
+}
+
+impl Signature {
+    fn new(min_args: usize, max_args: Option<usize>, return_type: DataType) -> Self {
+        Self {
+            min_args,
+            max_args,
+            return_type,
+        }
+    }
+}
+
+/// Look up the signature of a built-in function by (case-insensitive) name
+fn signature(name: &str) -> Result<Signature, ExecutionError> {
+    let signature = match name.to_uppercase().as_str() {
+        "LENGTH" | "CHAR_LENGTH" => Signature::new(1, Some(1), DataType::Integer),
+        "UPPER" | "LOWER" => Signature::new(1, Some(1), DataType::Text),
+        "TRIM" | "LTRIM" | "RTRIM" => Signature::new(1, Some(2), DataType::Text),
+        "SUBSTR" | "SUBSTRING" => Signature::new(2, Some(3), DataType::Text),
+        "REPLACE" => Signature::new(3, Some(3), DataType::Text),
+        "INSTR" => Signature::new(2, Some(2), DataType::Integer),
+        "CONCAT" => Signature::new(1, None, DataType::Text),
+        "LPAD" | "RPAD" => Signature::new(2, Some(3), DataType::Text),
+        "SPLIT_PART" => Signature::new(3, Some(3), DataType::Text),
+        "HEX" => Signature::new(1, Some(1), DataType::Text),
+        "UNHEX" => Signature::new(1, Some(1), DataType::Blob),
+        "ROUND" => Signature::new(1, Some(3), DataType::Decimal(None)),
+        "NOW" => Signature::new(0, Some(0), DataType::TimestampTz),
+        "DATE_TRUNC" => Signature::new(2, Some(2), DataType::Timestamp),
+        "EXTRACT" | "DATE_PART" => Signature::new(2, Some(2), DataType::Integer),
+        "STRFTIME" => Signature::new(2, Some(2), DataType::Text),
+        "JSON_EXTRACT" => Signature::new(2, Some(2), DataType::Json),
+        "JSON_ARRAY_LENGTH" => Signature::new(1, Some(2), DataType::Integer),
+        "JSON_TYPE" => Signature::new(1, Some(2), DataType::Text),
+        "GEN_RANDOM_UUID" | "UUIDV4" | "UUIDV7" => Signature::new(0, Some(0), DataType::Uuid),
+        "ARRAY_LENGTH" => Signature::new(1, Some(2), DataType::Integer),
+        "ARRAY_AGG" => Signature::new(1, Some(1), DataType::Array(Box::new(DataType::Text))),
+        "COUNT" => Signature::new(0, Some(1), DataType::Integer),
+        "NEXTVAL" | "CURRVAL" => Signature::new(1, Some(1), DataType::Integer),
+        "SETVAL" => Signature::new(2, Some(3), DataType::Integer),
+        _ => return Err(ExecutionError::FunctionNotFound(name.to_string())),
+    };
+    Ok(signature)
+}
+
+/// Type an argument of a built-in function must have in strict type mode
+#[derive(Debug, Clone, Copy)]
+enum Param {
+    Any,
+    Text,
+    Integer,
+    Numeric,
+    Boolean,
+    /// TEXT, BLOB or UUID
+    Bytes,
+    /// A date, time, timestamp or interval
+    Temporal,
+    /// JSON, or TEXT holding a JSON document
+    Json,
+    Array,
+}
+
+impl Param {
+    fn accepts(self, data_type: &DataType) -> bool {
+        match self {
+            Param::Any => true,
+            Param::Text => *data_type == DataType::Text,
+            Param::Integer => *data_type == DataType::Integer,
+            Param::Numeric => data_type.is_numeric(),
+            Param::Boolean => *data_type == DataType::Boolean,
+            Param::Bytes => matches!(data_type, DataType::Text | DataType::Blob | DataType::Uuid),
+            Param::Temporal => data_type.is_temporal(),
+            Param::Json => matches!(data_type, DataType::Json | DataType::Text),
+            Param::Array => matches!(data_type, DataType::Array(_)),
+        }
+    }
+}
+
+impl Display for Param {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Param::Any => "any value",
+            Param::Text => "TEXT",
+            Param::Integer => "INTEGER",
+            Param::Numeric => "a number",
+            Param::Boolean => "BOOLEAN",
+            Param::Bytes => "TEXT, BLOB or UUID",
+            Param::Temporal => "a date, time or interval",
+            Param::Json => "JSON or TEXT",
+            Param::Array => "an array",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Parameter types of a built-in function; the last one repeats for
+/// variadic functions
+fn params(name: &str) -> &'static [Param] {
+    use Param::*;
+    match name.to_uppercase().as_str() {
+        "LENGTH" | "CHAR_LENGTH" | "HEX" => &[Bytes],
+        "UPPER" | "LOWER" | "TRIM" | "LTRIM" | "RTRIM" | "REPLACE" | "INSTR" | "UNHEX" => &[Text],
+        "SUBSTR" | "SUBSTRING" => &[Bytes, Integer],
+        "LPAD" | "RPAD" => &[Text, Integer, Text],
+        "SPLIT_PART" => &[Text, Text, Integer],
+        "ROUND" => &[Numeric, Integer, Text],
+        "DATE_TRUNC" | "EXTRACT" | "DATE_PART" | "STRFTIME" => &[Text, Temporal],
+        "JSON_EXTRACT" | "JSON_ARRAY_LENGTH" | "JSON_TYPE" => &[Json, Text],
+        "ARRAY_LENGTH" => &[Array, Integer],
+        "NEXTVAL" | "CURRVAL" => &[Text],
+        "SETVAL" => &[Text, Integer, Boolean],
+        _ => &[Any],
+    }
+}
+
+/// In strict type mode, check the types of the arguments of a built-in
+/// function call; `arg_types` is None for NULL literals, which are accepted
+/// anywhere
+pub fn check_arg_types(name: &str, arg_types: &[Option<DataType>]) -> Result<(), ExecutionError> {
+    let params = params(name);
+    for (i, arg_type) in arg_types.iter().enumerate() {
+        let param = params[i.min(params.len() - 1)];
+        if let Some(arg_type) = arg_type
+            && !param.accepts(arg_type)
+        {
+            return Err(ExecutionError::TypeMismatch(format!(
+                "Argument {} to {} must be {} without a CAST, not {}",
+                i + 1,
+                name.to_uppercase(),
+                param,
+                arg_type
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Result type of a built-in function called with arguments of the given types
+pub fn return_type(name: &str, arg_types: &[DataType]) -> Result<DataType, ExecutionError> {
+    let signature = signature(name)?;
+    // DATE_TRUNC keeps the time zone of its argument
+    if name.eq_ignore_ascii_case("DATE_TRUNC") && arg_types.get(1) == Some(&DataType::TimestampTz) {
+        return Ok(DataType::TimestampTz);
+    }
+    // SUBSTR of a BLOB is a BLOB
+    if matches!(name.to_uppercase().as_str(), "SUBSTR" | "SUBSTRING")
+        && arg_types.first() == Some(&DataType::Blob)
+    {
+        return Ok(DataType::Blob);
+    }
+    // ARRAY_AGG collects values of its argument's type
+    if name.eq_ignore_ascii_case("ARRAY_AGG")
+        && let Some(data_type) = arg_types.first()
+    {
+        return Ok(DataType::Array(Box::new(data_type.clone())));
+    }
+    // ROUND keeps the numeric type of its argument
+    if name.eq_ignore_ascii_case("ROUND")
+        && let Some(data_type @ (DataType::Integer | DataType::Float)) = arg_types.first()
+    {
+        return Ok(data_type.clone());
+    }
+    Ok(signature.return_type)
+}
+
+/// Check that a built-in function is called with an acceptable number of
+/// arguments
+pub fn check_arg_count(name: &str, count: usize) -> Result<(), ExecutionError> {
+    let signature = signature(name)?;
+    if count < signature.min_args || signature.max_args.is_some_and(|max| count > max) {
+        return Err(ExecutionError::InvalidValue(format!(
+            "Wrong number of arguments to {}: {}",
+            name.to_uppercase(),
+            count
+        )));
+    }
+    Ok(())
+}
+
+/// Call a built-in function with already evaluated arguments
+pub fn call(name: &str, args: Vec<Value>) -> Result<Value, ExecutionError> {
+    check_arg_count(name, args.len())?;
+
+    if is_aggregate(name) {
+        return Err(ExecutionError::InvalidValue(format!(
+            "Aggregate function {} can only be used as a select list item",
+            name.to_uppercase()
+        )));
+    }
+
+    if args.iter().any(Value::is_null) {
+        return Ok(Value::Null);
+    }
+    let args = Args { name, values: args };
+
+    let result = match name.to_uppercase().as_str() {
+        "LENGTH" | "CHAR_LENGTH" => match &args.values[0] {
+            Value::Blob(bytes) => Value::Integer(bytes.len() as i64),
+            _ => Value::Integer(args.text(0)?.chars().count() as i64),
+        },
+        "UPPER" => Value::Text(args.text(0)?.to_uppercase()),
+        "LOWER" => Value::Text(args.text(0)?.to_lowercase()),
+        "TRIM" | "LTRIM" | "RTRIM" => {
+            let text = args.text(0)?;
+            let characters: Vec<char> = match args.values.get(1) {
+                Some(_) => args.text(1)?.chars().collect(),
+                None => vec![' '],
+            };
+            let trimmed = match name.to_uppercase().as_str() {
+                "LTRIM" => text.trim_start_matches(characters.as_slice()),
+                "RTRIM" => text.trim_end_matches(characters.as_slice()),
+                _ => text.trim_matches(characters.as_slice()),
+            };
+            Value::Text(trimmed.to_string())
+        }
+        "SUBSTR" | "SUBSTRING" => {
+            let length = match args.values.get(2) {
+                Some(_) => Some(args.integer(2)?),
+                None => None,
+            };
+            let range = substr_range(args.integer(1)?, length)?;
+            match &args.values[0] {
+                Value::Blob(bytes) => {
+                    Value::Blob(bytes.iter().copied().skip(range.0).take(range.1).collect())
+                }
+                _ => Value::Text(args.text(0)?.chars().skip(range.0).take(range.1).collect()),
+            }
+        }
+        "REPLACE" => {
+            let (text, from, to) = (args.text(0)?, args.text(1)?, args.text(2)?);
+            if from.is_empty() {
+                Value::Text(text)
+            } else {
+                Value::Text(text.replace(&from, &to))
+            }
+        }
+        "INSTR" => {
+            let (text, needle) = (args.text(0)?, args.text(1)?);
+            let position = text
+                .find(&needle)
+                .map(|byte_index| text[..byte_index].chars().count() as i64 + 1)
+                .unwrap_or(0);
+            Value::Integer(position)
+        }
+        "CONCAT" => {
+            let mut result = String::new();
+            for i in 0..args.values.len() {
+                result.push_str(&args.text(i)?);
+            }
+            Value::Text(result)
+        }
+        "LPAD" | "RPAD" => {
+            let fill = match args.values.get(2) {
+                Some(_) => args.text(2)?,
+                None => " ".to_string(),
+            };
+            let left = name.eq_ignore_ascii_case("LPAD");
+            Value::Text(pad(&args.text(0)?, args.integer(1)?, &fill, left)?)
+        }
+        "SPLIT_PART" => Value::Text(split_part(
+            &args.text(0)?,
+            &args.text(1)?,
+            args.integer(2)?,
+        )?),
+        "HEX" => {
+            let bytes = match &args.values[0] {
+                Value::Blob(bytes) => bytes.clone(),
+                Value::Uuid(uuid) => uuid.as_bytes().to_vec(),
+                _ => args.text(0)?.into_bytes(),
+            };
+            Value::Text(encode_hex(&bytes))
+        }
+        "UNHEX" => Value::Blob(decode_hex(&args.text(0)?).ok_or_else(|| {
+            ExecutionError::InvalidValue(format!("Invalid hexadecimal string: {}", args.values[0]))
+        })?),
+        "ROUND" => {
+            let digits = match args.values.get(1) {
+                Some(_) => args.integer(1)?,
+                None => 0,
+            };
+            let mode = match args.values.get(2) {
+                Some(_) => args.text(2)?.parse::<RoundingMode>()?,
+                None => RoundingMode::HalfUp,
+            };
+            round(&args.values[0], digits, mode)?
+        }
+        "NOW" => Value::TimestampTz(datetime::now()),
+        "DATE_TRUNC" => {
+            let unit = args.text(0)?;
+            let truncate = |timestamp| {
+                date_trunc(&unit, timestamp).ok_or_else(|| {
+                    ExecutionError::InvalidValue(format!("Unknown DATE_TRUNC unit: {}", unit))
+                })
+            };
+            match &args.values[1] {
+                Value::TimestampTz(timestamp) => {
+                    Value::TimestampTz(truncate(timestamp.naive_utc())?.and_utc())
+                }
+                _ => Value::Timestamp(truncate(args.timestamp(1)?)?),
+            }
+        }
+        "EXTRACT" | "DATE_PART" => {
+            let field = args.text(0)?;
+            let part = match &args.values[1] {
+                Value::Time(time) => extract_time(&field, time),
+                Value::Interval(interval) => extract_interval(&field, interval),
+                _ => extract_timestamp(&field, &args.timestamp(1)?),
+            };
+            Value::Integer(part.ok_or_else(|| {
+                ExecutionError::InvalidValue(format!(
+                    "Cannot extract {} from {}",
+                    field, args.values[1]
+                ))
+            })?)
+        }
+        "STRFTIME" => Value::Text(strftime(&args.text(0)?, &args.datetime(1)?)?),
+        "JSON_EXTRACT" | "JSON_ARRAY_LENGTH" | "JSON_TYPE" => {
+            let document = args.json(0)?;
+            let target = match args.values.get(1) {
+                Some(_) => json::lookup(&document, &json::parse_path(&args.text(1)?)?),
+                None => Some(&document),
+            };
+            match (name.to_uppercase().as_str(), target) {
+                (_, None) => Value::Null,
+                ("JSON_EXTRACT", Some(target)) => Value::Json(target.clone()),
+                ("JSON_ARRAY_LENGTH", Some(target)) => Value::Integer(match target {
+                    serde_json::Value::Array(items) => items.len() as i64,
+                    _ => 0,
+                }),
+                (_, Some(target)) => Value::Text(json::type_name(target).to_string()),
+            }
+        }
+        "ARRAY_LENGTH" => {
+            let dimension = match args.values.get(1) {
+                Some(_) => args.integer(1)?,
+                None => 1,
+            };
+            match &args.values[0] {
+                Value::Array(items) if dimension == 1 => Value::Integer(items.len() as i64),
+                Value::Array(_) => Value::Null,
+                other => return Err(args.invalid(0, other)),
+            }
+        }
+        "GEN_RANDOM_UUID" | "UUIDV4" => Value::Uuid(Uuid::new_v4()),
+        "UUIDV7" => Value::Uuid(Uuid::new_v7()),
+        _ => return Err(ExecutionError::FunctionNotFound(name.to_string())),
+    };
+
+    Ok(result)
+}
+
+/// Check whether a function reads or advances a sequence, which needs the
+/// database and so is called by the executor instead of `call`
+pub fn is_sequence_function(name: &str) -> bool {
+    matches!(
+        name.to_uppercase().as_str(),
+        "NEXTVAL" | "CURRVAL" | "SETVAL"
+    )
+}
+
+/// Check whether a function is an aggregate, computed over all rows
+pub fn is_aggregate(name: &str) -> bool {
+    matches!(name.to_uppercase().as_str(), "ARRAY_AGG" | "COUNT")
+}
+
+/// Compute an aggregate function from the arguments it was called with for
+/// each row
+pub fn aggregate(
+    name: &str,
+    arg_count: usize,
+    rows: Vec<Vec<Value>>,
+) -> Result<Value, ExecutionError> {
+    let signature = signature(name)?;
+    if arg_count < signature.min_args || signature.max_args.is_some_and(|max| arg_count > max) {
+        return Err(ExecutionError::InvalidValue(format!(
+            "Wrong number of arguments to {}: {}",
+            name.to_uppercase(),
+            arg_count
+        )));
+    }
+
+    match name.to_uppercase().as_str() {
+        // ARRAY_AGG keeps NULLs, and is NULL when there are no rows
+        "ARRAY_AGG" if rows.is_empty() => Ok(Value::Null),
+        "ARRAY_AGG" => Ok(Value::Array(
+            rows.into_iter()
+                .filter_map(|args| args.into_iter().next())
+                .collect(),
+        )),
+        // COUNT(*) counts rows, COUNT(expr) the rows where expr is not NULL
+        "COUNT" => Ok(Value::Integer(
+            rows.iter()
+                .filter(|args| args.first().is_none_or(|value| !value.is_null()))
+                .count() as i64,
+        )),
+        _ => Err(ExecutionError::FunctionNotFound(name.to_string())),
+    }
+}
+
+/// Concatenate two values as text (the `||` operator); two BLOBs
+/// concatenate to a BLOB, and arrays concatenate with arrays or elements
+///
+/// A NULL array next to a non-NULL operand counts as an empty array, so
+/// `xs || 9` is `ARRAY[9]` when `xs` is NULL; `types` gives the static types
+/// of the operands to tell NULL arrays from NULL elements, and is only
+/// called when there is a NULL operand. Elements added to an array are
+/// converted to its element type, and rejected if they do not convert
+/// implicitly.
+pub fn concat(
+    left: &Value,
+    right: &Value,
+    types: impl FnOnce() -> Result<(DataType, DataType), ExecutionError>,
+) -> Result<Value, ExecutionError> {
+    let (left, right) = match (left, right) {
+        (Value::Null, Value::Null) => return Ok(Value::Null),
+        (Value::Null, _) | (_, Value::Null) => {
+            let (left_type, right_type) = types()?;
+            let operand = |value: &Value, data_type: &DataType| match (value, data_type) {
+                (Value::Null, DataType::Array(_)) => Value::Array(vec![]),
+                _ => value.clone(),
+            };
+            (operand(left, &left_type), operand(right, &right_type))
+        }
+        _ => (left.clone(), right.clone()),
+    };
+
+    match (left, right) {
+        (Value::Array(left), Value::Array(right)) => {
+            let items = [left, right].concat();
+            items
+                .iter()
+                .map(|item| array_element(&items, item.clone()))
+                .collect::<Result<_, _>>()
+                .map(Value::Array)
+        }
+        (Value::Array(mut items), item) => {
+            let item = array_element(&items, item)?;
+            items.push(item);
+            Ok(Value::Array(items))
+        }
+        (item, Value::Array(mut items)) => {
+            let item = array_element(&items, item)?;
+            items.insert(0, item);
+            Ok(Value::Array(items))
+        }
+        (Value::Blob(left), Value::Blob(right)) => Ok(Value::Blob([left, right].concat())),
+        (left, right) => call("CONCAT", vec![left, right]),
+    }
+}
+
+/// Convert a value added to an array to the array's element type (that of
+/// its first non-NULL item)
+fn array_element(items: &[Value], item: Value) -> Result<Value, ExecutionError> {
+    let (Some(element), Some(data_type)) =
+        (items.iter().find_map(Value::data_type), item.data_type())
+    else {
+        return Ok(item);
+    };
+    if data_type != element && !data_type.coerces_to(&element, TypeMode::Lenient) {
+        return Err(ExecutionError::TypeMismatch(format!(
+            "Cannot add {} value {} to an array of {}",
+            data_type, item, element
+        )));
+    }
+    Ok(item.as_type(&element)?)
+}
+
+/// Look up a member or element of a JSON document (the `->` and `->>`
+/// operators)
+///
+/// The selector is an object key, an array index (negative from the end) or a
+/// path starting with `$`. `->` returns JSON and `->>` text, with JSON strings
+/// unquoted; a missing member or a NULL operand gives NULL.
+pub fn json_get(
+    document: &Value,
+    selector: &Value,
+    as_text: bool,
+) -> Result<Value, ExecutionError> {
+    if document.is_null() || selector.is_null() {
+        return Ok(Value::Null);
+    }
+    let args = Args {
+        name: if as_text { "->>" } else { "->" },
+        values: vec![document.clone(), selector.clone()],
+    };
+    let document = args.json(0)?;
+    let path = match &args.values[1] {
+        Value::Integer(index) => vec![json::PathStep::Index(*index)],
+        Value::Text(path) if path.starts_with('$') => json::parse_path(path)?,
+        _ => vec![json::PathStep::Key(args.text(1)?)],
+    };
+    Ok(match json::lookup(&document, &path) {
+        None => Value::Null,
+        Some(target) if as_text => json::to_text(target),
+        Some(target) => Value::Json(target.clone()),
+    })
+}
+
+/// Non-NULL arguments of a function call, with typed accessors
+struct Args<'a> {
+    name: &'a str,
+    values: Vec<Value>,
+}
+
+impl Args<'_> {
+    /// Get argument `i` as text, converting integers
+    fn text(&self, i: usize) -> Result<String, ExecutionError> {
+        match self.values[i].as_type(&DataType::Text)? {
+            Value::Text(s) => Ok(s),
+            other => Err(self.invalid(i, &other)),
+        }
+    }
+
+    /// Get argument `i` as an integer, converting numeric text
+    fn integer(&self, i: usize) -> Result<i64, ExecutionError> {
+        match self.values[i].as_type(&DataType::Integer)? {
+            Value::Integer(n) => Ok(n),
+            other => Err(self.invalid(i, &other)),
+        }
+    }
+
+    /// Get argument `i` as a timestamp, converting dates and parsing text;
+    /// timestamps with time zone are taken in UTC
+    fn timestamp(&self, i: usize) -> Result<NaiveDateTime, ExecutionError> {
+        match self.values[i].as_type(&DataType::Timestamp)? {
+            Value::Timestamp(timestamp) => Ok(timestamp),
+            other => Err(self.invalid(i, &other)),
+        }
+    }
+
+    /// Get argument `i` as a UTC date and time; a TIME is taken to be on
+    /// 1970-01-01
+    fn datetime(&self, i: usize) -> Result<DateTime<Utc>, ExecutionError> {
+        match &self.values[i] {
+            Value::TimestampTz(timestamp) => Ok(*timestamp),
+            Value::Time(time) => Ok(NaiveDate::default().and_time(*time).and_utc()),
+            _ => Ok(self.timestamp(i)?.and_utc()),
+        }
+    }
+
+    /// Get argument `i` as a JSON document, parsing text
+    fn json(&self, i: usize) -> Result<serde_json::Value, ExecutionError> {
+        match &self.values[i] {
+            Value::Json(json) => Ok(json.clone()),
+            Value::Text(text) => Ok(json::parse(text)?),
+            other => Err(self.invalid(i, other)),
+        }
+    }
+
+    fn invalid(&self, i: usize, value: &Value) -> ExecutionError {
+        ExecutionError::InvalidValue(format!(
+            "Invalid argument {} to {}: {}",
+            i + 1,
+            self.name.to_uppercase(),
+            value
+        ))
+    }
+}
+
+/// `SUBSTR(text, start [, length])` with 1-based character (or, for a BLOB,
+/// byte) positions, as the number of items to skip and to take
+///
+/// Positions before the start of the string still count towards `length`,
+/// so `SUBSTR('hello', 0, 3)` is `'he'`.
+fn substr_range(start: i64, length: Option<i64>) -> Result<(usize, usize), ExecutionError> {
+    let end = match length {
+        Some(length) if length < 0 => {
+            return Err(ExecutionError::InvalidValue(
+                "Negative substring length not allowed".to_string(),
+            ));
+        }
+        Some(length) => start.saturating_add(length),
+        None => i64::MAX,
+    };
+
+    // Clamp the 1-based half-open range [start, end) to the string
+    let skip = usize::try_from(start.max(1) - 1).unwrap_or(usize::MAX);
+    let take = usize::try_from(end.saturating_sub(start.max(1)).max(0)).unwrap_or(usize::MAX);
+    Ok((skip, take))
+}
+
+/// Longest result of `LPAD`/`RPAD`, in characters
+const MAX_PAD_LENGTH: i64 = 10 * 1024 * 1024;
+
+/// `LPAD`/`RPAD`: pad `text` to `length` characters with repetitions of
+/// `fill`, truncating it if it is already longer
+fn pad(text: &str, length: i64, fill: &str, left: bool) -> Result<String, ExecutionError> {
+    if length > MAX_PAD_LENGTH {
+        return Err(ExecutionError::InvalidValue(format!(
+            "Padded length {} exceeds the maximum of {} characters",
+            length, MAX_PAD_LENGTH
+        )));
+    }
+    let length = length.max(0) as usize;
+    let chars: Vec<char> = text.chars().collect();
+    if chars.len() >= length || fill.is_empty() {
+        return Ok(chars.into_iter().take(length).collect());
+    }
+
+    let padding: String = fill.chars().cycle().take(length - chars.len()).collect();
+    Ok(if left {
+        padding + text
+    } else {
+        text.to_string() + &padding
+    })
+}
+
+/// `SPLIT_PART(text, delimiter, n)`: the n-th field (1-based), counting from
+/// the end when `n` is negative; out of range fields are empty
+fn split_part(text: &str, delimiter: &str, n: i64) -> Result<String, ExecutionError> {
+    if n == 0 {
+        return Err(ExecutionError::InvalidValue(
+            "SPLIT_PART field position must not be zero".to_string(),
+        ));
+    }
+
+    let fields: Vec<&str> = if delimiter.is_empty() {
+        vec![text]
+    } else {
+        text.split(delimiter).collect()
+    };
+
+    let index = if n > 0 {
+        usize::try_from(n - 1).ok()
+    } else {
+        usize::try_from(-n)
+            .ok()
+            .and_then(|from_end| fields.len().checked_sub(from_end))
+    };
+
+    Ok(index
+        .and_then(|i| fields.get(i))
+        .map(|field| field.to_string())
+        .unwrap_or_default())
+}
+
+/// `ROUND(number [, digits [, mode]])`: round to `digits` places after the
+/// point (before it if negative), keeping the type of `number`
+///
+/// Rounding is exact for every type; FLOAT values are rounded through their
+/// shortest decimal representation, so `ROUND(2.675, 2)` is 2.68.
+fn round(value: &Value, digits: i64, mode: RoundingMode) -> Result<Value, ExecutionError> {
+    let digits = digits.clamp(-64, 64) as i32;
+    let decimal = match value {
+        Value::Float(f) if !f.is_finite() => return Ok(value.clone()),
+        // FLOATs too large for a DECIMAL are rounded in floating point
+        Value::Float(f) => match Decimal::from_f64(*f).and_then(|d| d.round(digits, mode)) {
+            Ok(rounded) => return Ok(Value::Decimal(rounded).as_type(&DataType::Float)?),
+            Err(_) => return Ok(Value::Float(round_float(*f, digits, mode))),
+        },
+        Value::Decimal(d) => *d,
+        Value::Integer(i) => Decimal::from_i64(*i),
+        Value::Text(s) => s.parse()?,
+        other => {
+            return Err(ExecutionError::InvalidValue(format!(
+                "Invalid argument 1 to ROUND: {}",
+                other
+            )));
+        }
+    };
+
+    let rounded = Value::Decimal(decimal.round(digits, mode)?);
+    match value {
+        Value::Integer(_) => Ok(rounded.as_type(&DataType::Integer)?),
+        _ => Ok(rounded),
+    }
+}
+
+/// Round a FLOAT to the given number of fractional digits in floating point
+fn round_float(value: f64, digits: i32, mode: RoundingMode) -> f64 {
+    let factor = 10f64.powi(digits);
+    let scaled = value * factor;
+    if !scaled.is_finite() || scaled.fract() == 0.0 {
+        return value;
+    }
+    let rounded = match mode {
+        RoundingMode::HalfUp => scaled.round(),
+        RoundingMode::HalfDown if scaled.fract().abs() == 0.5 => scaled.trunc(),
+        RoundingMode::HalfDown => scaled.round(),
+        RoundingMode::HalfEven => scaled.round_ties_even(),
+        RoundingMode::Up => scaled.trunc() + scaled.signum(),
+        RoundingMode::Down => scaled.trunc(),
+        RoundingMode::Ceiling => scaled.ceil(),
+        RoundingMode::Floor => scaled.floor(),
+    };
+    rounded / factor
+}
+
+/// `DATE_TRUNC(unit, timestamp)`: truncate to the start of the given unit
+/// (`microseconds` up to `millennium`); weeks start on Monday, and centuries
+/// and millennia start at years ending in 1
+fn date_trunc(unit: &str, timestamp: NaiveDateTime) -> Option<NaiveDateTime> {
+    let date = timestamp.date();
+    // First year of the period of `years` years containing the date, where
+    // periods start at years equal to `first` modulo `years`
+    let year_start = |years: i32, first: i32| {
+        let year = date.year() - (date.year() - first).rem_euclid(years);
+        NaiveDate::from_ymd_opt(year, 1, 1)
+    };
+    let (date, time) = match unit.to_lowercase().as_str() {
+        "microsecond" | "microseconds" => return Some(timestamp),
+        "millisecond" | "milliseconds" => {
+            let nanos = timestamp.nanosecond() / 1_000_000 * 1_000_000;
+            (date, timestamp.time().with_nanosecond(nanos)?)
+        }
+        "second" => (date, timestamp.time().with_nanosecond(0)?),
+        "minute" => (
+            date,
+            NaiveTime::from_hms_opt(timestamp.hour(), timestamp.minute(), 0)?,
+        ),
+        "hour" => (date, NaiveTime::from_hms_opt(timestamp.hour(), 0, 0)?),
+        "day" => (date, NaiveTime::MIN),
+        "week" => (
+            date - chrono::Days::new(date.weekday().num_days_from_monday() as u64),
+            NaiveTime::MIN,
+        ),
+        "month" => (date.with_day(1)?, NaiveTime::MIN),
+        "quarter" => (
+            NaiveDate::from_ymd_opt(date.year(), date.month0() / 3 * 3 + 1, 1)?,
+            NaiveTime::MIN,
+        ),
+        "year" => (year_start(1, 0)?, NaiveTime::MIN),
+        "decade" => (year_start(10, 0)?, NaiveTime::MIN),
+        "century" => (year_start(100, 1)?, NaiveTime::MIN),
+        "millennium" => (year_start(1000, 1)?, NaiveTime::MIN),
+        _ => return None,
+    };
+    Some(date.and_time(time))
+}
+
+/// `EXTRACT(field FROM timestamp)`; `dow` counts from Sunday = 0 and `week`
+/// is the ISO week number
+fn extract_timestamp(field: &str, timestamp: &NaiveDateTime) -> Option<i64> {
+    let year = timestamp.year() as i64;
+    let value = match field.to_lowercase().as_str() {
+        "millennium" => (year - 1).div_euclid(1000) + 1,
+        "century" => (year - 1).div_euclid(100) + 1,
+        "decade" => year.div_euclid(10),
+        "year" => year,
+        "quarter" => timestamp.month0() as i64 / 3 + 1,
+        "month" => timestamp.month() as i64,
+        "week" => timestamp.iso_week().week() as i64,
+        "day" => timestamp.day() as i64,
+        "dow" => timestamp.weekday().num_days_from_sunday() as i64,
+        "isodow" => timestamp.weekday().number_from_monday() as i64,
+        "doy" => timestamp.ordinal() as i64,
+        "epoch" => timestamp.and_utc().timestamp(),
+        _ => return extract_time(field, &timestamp.time()),
+    };
+    Some(value)
+}
+
+/// `EXTRACT(field FROM time)` for the time-of-day fields
+fn extract_time(field: &str, time: &NaiveTime) -> Option<i64> {
+    let micros = time.second() as i64 * 1_000_000 + time.nanosecond() as i64 / 1_000;
+    let value = match field.to_lowercase().as_str() {
+        "hour" => time.hour() as i64,
+        "minute" => time.minute() as i64,
+        "second" => time.second() as i64,
+        "milliseconds" => micros / 1_000,
+        "microseconds" => micros,
+        "epoch" => time.num_seconds_from_midnight() as i64,
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// `EXTRACT(field FROM interval)`; each field is that part of the interval,
+/// except `epoch`, the total number of seconds (a month counts as 30 days)
+fn extract_interval(field: &str, interval: &datetime::Interval) -> Option<i64> {
+    let micros = interval.micros;
+    let value = match field.to_lowercase().as_str() {
+        "year" => interval.months as i64 / 12,
+        "month" => interval.months as i64 % 12,
+        "day" => interval.days as i64,
+        "hour" => micros / 3_600_000_000,
+        "minute" => micros / 60_000_000 % 60,
+        "second" => micros / 1_000_000 % 60,
+        "milliseconds" => micros % 60_000_000 / 1_000,
+        "microseconds" => micros % 60_000_000,
+        "epoch" => interval.seconds().trunc() as i64,
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// `STRFTIME(format, value)` using strftime-style `%` specifiers
+fn strftime(format: &str, timestamp: &DateTime<Utc>) -> Result<String, ExecutionError> {
+    let invalid = || ExecutionError::InvalidValue(format!("Invalid STRFTIME format: '{}'", format));
+    let items: Vec<Item> = StrftimeItems::new(format).collect();
+    if items.contains(&Item::Error) {
+        return Err(invalid());
+    }
+
+    let mut result = String::new();
+    write!(result, "{}", timestamp.format_with_items(items.into_iter())).map_err(|_| invalid())?;
+    Ok(result)
+}
@@ -1,10 +1,29 @@
+mod functions;
+mod result;
+mod table_functions;
+#[cfg(test)]
+mod tests;
+
+pub use result::{ExecutionResult, StatementKind};
+
 use crate::{
     parser::{
-        CreateTableStatement, InsertStatement, Operator, SelectStatement, Statement, WhereClause,
+        BinaryOperator, ColumnConstraint, ConflictAction, CreateSequenceStatement,
+        CreateTableAsStatement, CreateTableStatement, CreateViewStatement, DropViewStatement,
+        Expression, InsertSource, InsertStatement, InsertValue, OnConflict, Operator, OrderByItem,
+        References, SelectItem, SelectStatement, SequenceOptions, Statement, TableConstraint,
+        TableReference, WhereClause, parse_expression_sql, parse_select_sql,
+    },
+    storage::{Database, RowChange, StorageError, ViewMetadata},
+    types::{
+        ArithmeticOperator, CheckConstraint, Column, DataType, ForeignKey, Generated, Identity,
+        KeyConstraint, Operator as TypeOperator, ReferentialAction, ResultSet, Row, Schema,
+        Sequence, Truth, TypeError, TypeMode, Value,
     },
-    storage::{Database, StorageError},
-    types::{Column, ResultSet, Row, Schema, Value},
 };
+use std::cmp::Ordering;
+use std::collections::{HashMap, hash_map::Entry};
+use std::time::Instant;
 use thiserror::Error;
 
 /// Error types for query execution
@@ -14,6 +33,10 @@ pub enum ExecutionError {
     #[error("Storage error: {0}")]
     StorageError(#[from] StorageError),
 
+    /// Error from value comparison or conversion
+    #[error("Type error: {0}")]
+    TypeError(#[from] TypeError),
+
     /// Query execution failed
     #[error("Execution error: {0}")]
     ExecutionFailed(String),
@@ -22,15 +45,25 @@ pub enum ExecutionError {
     #[error("Column not found: {0}")]
     ColumnNotFound(String),
 
+    /// Unknown function name
+    #[error("Function not found: {0}")]
+    FunctionNotFound(String),
+
     /// Invalid value for operation
     #[error("Invalid value: {0}")]
-    #[allow(dead_code)]
     InvalidValue(String),
 
     /// Unsupported operation
     #[error("Unsupported operation: {0}")]
-    #[allow(dead_code)]
     UnsupportedOperation(String),
+
+    /// Row for which a CHECK constraint's condition is FALSE
+    #[error("CHECK constraint failed: {0}")]
+    CheckViolation(String),
+
+    /// Values of different types mixed without a CAST in strict type mode
+    #[error("Type mismatch: {0}")]
+    TypeMismatch(String),
 }
 
 /// Query executor handles executing SQL statements
@@ -46,228 +79,2661 @@ impl QueryExecutor {
         Self { storage }
     }
 
-    /// Execute an SQL statement and return results
-    pub fn execute(&self, statement: Statement) -> Result<ResultSet, ExecutionError> {
-        match statement {
-            Statement::CreateTable(create) => self.execute_create_table(create),
-            Statement::Insert(insert) => self.execute_insert(insert),
-            Statement::Select(select) => self.execute_select(select),
-        }
+    /// Execute an SQL statement and report what it did
+    ///
+    /// Nothing is printed; the result holds the rows returned, if any, the
+    /// number of rows affected and the time taken, for the caller to show.
+    pub fn execute(&self, statement: Statement) -> Result<ExecutionResult, ExecutionError> {
+        let start = Instant::now();
+        let result = match statement {
+            Statement::CreateTable(create) => self.execute_create_table(create)?,
+            Statement::CreateTableAs(create) => self.execute_create_table_as(create)?,
+            Statement::CreateSequence(create) => self.execute_create_sequence(create)?,
+            Statement::CreateView(create) => self.execute_create_view(create)?,
+            Statement::DropView(drop) => self.execute_drop_view(drop)?,
+            Statement::Insert(insert) => self.execute_insert(insert, vec![])?,
+            Statement::Select(select) => {
+                ExecutionResult::with_rows(StatementKind::Select, self.execute_select(select)?)
+            }
+        };
+        Ok(result.with_elapsed(start.elapsed()))
     }
 
     /// Execute a CREATE TABLE statement
+    ///
+    /// Columns are nullable unless declared NOT NULL or part of the primary
+    /// key. Column and table constraints are checked for consistency here and
+    /// enforced by the storage layer on insert; a foreign key must refer to a
+    /// PRIMARY KEY or UNIQUE key of an existing table (or of this one).
+    /// CHECK constraints are named here and evaluated by INSERT, as they need
+    /// the expression engine.
     fn execute_create_table(
         &self,
         stmt: CreateTableStatement,
-    ) -> Result<ResultSet, ExecutionError> {
-        // Convert column definitions to our schema format
-        let columns: Vec<Column> = stmt
-            .columns
-            .into_iter()
-            .map(|col_def| Column::new(col_def.name, col_def.data_type, col_def.nullable))
-            .collect();
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+        if stmt.columns.is_empty() {
+            return failed(format!("Table {} has no columns", stmt.table_name));
+        }
+        let mode = self.storage.type_mode()?;
 
-        // Create schema from columns
-        let schema = Schema::new(columns);
+        // Convert column definitions to our schema format, collecting keys
+        let mut columns: Vec<Column> = Vec::new();
+        let mut keys = Vec::new();
+        let mut references = Vec::new();
+        let mut checks = Vec::new();
+        let mut sequences = Vec::new();
+        for col_def in stmt.columns {
+            if columns.iter().any(|column| column.name == col_def.name) {
+                return failed(format!("Duplicate column name {}", col_def.name));
+            }
 
-        // Create the table
-        self.storage.create_table(stmt.table_name, schema)?;
+            let has = |constraint: ColumnConstraint| col_def.constraints.contains(&constraint);
+            let identity = col_def
+                .constraints
+                .iter()
+                .find_map(|constraint| match constraint {
+                    ColumnConstraint::Identity { always, options } => Some((*always, options)),
+                    _ => None,
+                });
+            let not_null = has(ColumnConstraint::NotNull)
+                || has(ColumnConstraint::PrimaryKey)
+                || identity.is_some();
+            if not_null && has(ColumnConstraint::Null) {
+                return failed(format!(
+                    "Column {} cannot be both NULL and NOT NULL",
+                    col_def.name
+                ));
+            }
+            for (constraint, primary) in [
+                (ColumnConstraint::PrimaryKey, true),
+                (ColumnConstraint::Unique, false),
+            ] {
+                if has(constraint) {
+                    keys.push(KeyConstraint {
+                        columns: vec![col_def.name.clone()],
+                        primary,
+                    });
+                }
+            }
+            let mut default = None;
+            let mut generated = None;
+            for constraint in &col_def.constraints {
+                match constraint {
+                    ColumnConstraint::References(target) => {
+                        references.push((vec![col_def.name.clone()], target.clone()));
+                    }
+                    ColumnConstraint::Default(sql) => default = Some(sql.clone()),
+                    ColumnConstraint::Check { name, expr } => {
+                        checks.push((name.clone(), Some(col_def.name.clone()), expr.clone()));
+                    }
+                    ColumnConstraint::Generated { expr, stored } => {
+                        generated = Some(Generated {
+                            expr: expr.clone(),
+                            stored: *stored,
+                        });
+                    }
+                    _ => {}
+                }
+            }
+            if default.is_some() && generated.is_some() {
+                return failed(format!(
+                    "Generated column {} cannot have a DEFAULT",
+                    col_def.name
+                ));
+            }
+            // Defaults are evaluated without a row, so cannot refer to columns
+            if let Some(sql) = &default {
+                let expr = parse_stored_expression(sql)?;
+                let empty = Schema::new(vec![]);
+                if let Some(name) = unknown_column(&expr, &empty) {
+                    return failed(format!(
+                        "DEFAULT of column {} cannot refer to column {}",
+                        col_def.name, name
+                    ));
+                }
+                infer_type(&expr, &empty)?;
+                if mode == TypeMode::Strict {
+                    check_stored_type(&expr, &empty, &col_def.name, &col_def.data_type, mode)?;
+                }
+            }
 
-        // Return empty result set
-        Ok(ResultSet::empty(Schema::new(vec![])))
-    }
+            // Identity columns take their values from a sequence of their own
+            let identity = match identity {
+                Some(_) if col_def.data_type != DataType::Integer => {
+                    return failed(format!("Identity column {} must be INTEGER", col_def.name));
+                }
+                Some(_) if default.is_some() || generated.is_some() => {
+                    return failed(format!(
+                        "Identity column {} cannot have a DEFAULT or be generated",
+                        col_def.name
+                    ));
+                }
+                Some((always, options)) => {
+                    let sequence = format!("{}_{}_seq", stmt.table_name, col_def.name);
+                    sequences.push((sequence.clone(), build_sequence(options)?));
+                    Some(Identity { sequence, always })
+                }
+                None => None,
+            };
 
-    /// Execute an INSERT statement
-    fn execute_insert(&self, stmt: InsertStatement) -> Result<ResultSet, ExecutionError> {
-        // Get table metadata to validate the insert
-        let metadata = self.storage.get_table_metadata(&stmt.table_name)?;
-        let schema = metadata.schema;
+            columns.push(
+                Column::new(col_def.name, col_def.data_type, !not_null)
+                    .with_default(default)
+                    .with_generated(generated)
+                    .with_identity(identity),
+            );
+        }
 
-        // If columns are specified, we need to map values to the right columns
-        if let Some(column_names) = stmt.columns {
-            // Validate that specified columns exist in the table
-            for name in &column_names {
-                if schema.get_column(name).is_none() {
-                    return Err(ExecutionError::ColumnNotFound(name.clone()));
+        // Generated columns are computed from the other, non-generated columns
+        let base = Schema::new(
+            columns
+                .iter()
+                .filter(|column| column.generated.is_none())
+                .cloned()
+                .collect(),
+        );
+        for column in &columns {
+            if let Some(generated) = &column.generated {
+                let expr = parse_stored_expression(&generated.expr)?;
+                if let Some(name) = unknown_column(&expr, &base) {
+                    return failed(format!(
+                        "Generated column {} cannot refer to column {}",
+                        column.name, name
+                    ));
+                }
+                infer_type(&expr, &base)?;
+                if mode == TypeMode::Strict {
+                    check_stored_type(&expr, &base, &column.name, &column.data_type, mode)?;
                 }
             }
+        }
 
-            // Process each row to insert
-            for values in stmt.values {
-                // Make sure we have the right number of values
-                if values.len() != column_names.len() {
-                    return Err(ExecutionError::ExecutionFailed(format!(
-                        "Column count ({}) does not match value count ({})",
-                        column_names.len(),
-                        values.len()
-                    )));
+        for constraint in stmt.constraints {
+            let (key_columns, primary) = match constraint {
+                TableConstraint::PrimaryKey(key_columns) => (key_columns, true),
+                TableConstraint::Unique(key_columns) => (key_columns, false),
+                TableConstraint::ForeignKey {
+                    columns,
+                    references: target,
+                } => {
+                    references.push((columns, target));
+                    continue;
+                }
+                TableConstraint::Check { name, expr } => {
+                    checks.push((name, None, expr));
+                    continue;
                 }
+            };
+            for (i, name) in key_columns.iter().enumerate() {
+                if key_columns[..i].contains(name) {
+                    return failed(format!("Column {} appears twice in a key", name));
+                }
+                let column = columns
+                    .iter_mut()
+                    .find(|column| &column.name == name)
+                    .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))?;
+                // Primary key columns are NOT NULL
+                if primary {
+                    column.nullable = false;
+                }
+            }
+            keys.push(KeyConstraint {
+                columns: key_columns,
+                primary,
+            });
+        }
 
-                // Create a full row with NULL values for unspecified columns
-                let mut row_values = vec![Value::Null; schema.columns.len()];
+        if keys.iter().filter(|key| key.primary).count() > 1 {
+            return failed(format!(
+                "Table {} has more than one primary key",
+                stmt.table_name
+            ));
+        }
 
-                // Fill in the specified values
-                for (i, col_name) in column_names.iter().enumerate() {
-                    let col_idx = schema
-                        .get_column_index(col_name)
-                        .ok_or_else(|| ExecutionError::ColumnNotFound(col_name.clone()))?;
+        // Create schema from columns and keys, then resolve foreign keys
+        // against it or the referenced tables
+        let mut schema = Schema::new(columns).with_keys(keys);
+        let mut foreign_keys = Vec::new();
+        for (columns, target) in references {
+            foreign_keys.push(self.resolve_foreign_key(
+                &stmt.table_name,
+                &schema,
+                columns,
+                target,
+            )?);
+        }
+        schema = schema.with_foreign_keys(foreign_keys);
 
-                    row_values[col_idx] = values[i].clone();
+        // Name the CHECK constraints, PostgreSQL style, and make sure their
+        // conditions only refer to the table's columns
+        let mut check_constraints: Vec<CheckConstraint> = Vec::new();
+        for (name, column, expr) in checks {
+            let taken = |name: &str| check_constraints.iter().any(|check| check.name == name);
+            let name = match name {
+                Some(name) if taken(&name) => {
+                    return failed(format!("Constraint name {} is used more than once", name));
+                }
+                Some(name) => name,
+                None => {
+                    let base = match column {
+                        Some(column) => format!("{}_{}_check", stmt.table_name, column),
+                        None => format!("{}_check", stmt.table_name),
+                    };
+                    let mut name = base.clone();
+                    let mut suffix = 1;
+                    while taken(&name) {
+                        name = format!("{}{}", base, suffix);
+                        suffix += 1;
+                    }
+                    name
                 }
+            };
+            let condition = parse_stored_expression(&expr)?;
+            if let Some(column) = unknown_column(&condition, &schema) {
+                return failed(format!(
+                    "CHECK constraint {} refers to unknown column {}",
+                    name, column
+                ));
+            }
+            check_types(&condition, &schema, mode)?;
+            check_constraints.push(CheckConstraint { name, expr });
+        }
+        schema = schema.with_checks(check_constraints);
+
+        // Create the table, then the sequences of its identity columns
+        for (name, _) in &sequences {
+            if self.storage.sequence_exists(name)? {
+                return Err(StorageError::SequenceAlreadyExists(name.clone()).into());
+            }
+        }
+        self.storage.create_table(stmt.table_name, schema)?;
+        for (name, sequence) in sequences {
+            self.storage.create_sequence(name, sequence)?;
+        }
+
+        Ok(ExecutionResult::new(StatementKind::CreateTable))
+    }
+
+    /// Execute a CREATE TABLE ... AS SELECT statement
+    ///
+    /// The table's columns have the names and types of the query's result
+    /// columns, unless names are listed after the table name, and are
+    /// nullable without constraints. Every row of the query is validated
+    /// against the new schema, and the table is not kept if one fails.
+    fn execute_create_table_as(
+        &self,
+        stmt: CreateTableAsStatement,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+        if self.storage.table_exists(&stmt.table_name)? {
+            return Err(StorageError::TableAlreadyExists(stmt.table_name).into());
+        }
+        let result = self.execute_select(stmt.query)?;
+
+        let names = match stmt.columns {
+            Some(names) if names.len() != result.schema.columns.len() => {
+                return failed(format!(
+                    "Table {} has {} column names but the query has {} columns",
+                    stmt.table_name,
+                    names.len(),
+                    result.schema.columns.len()
+                ));
+            }
+            Some(names) => names,
+            None => result
+                .schema
+                .columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect(),
+        };
+        let mut columns: Vec<Column> = Vec::new();
+        for (name, column) in names.into_iter().zip(&result.schema.columns) {
+            if columns.iter().any(|column| column.name == name) {
+                return failed(format!("Duplicate column name {}", name));
+            }
+            columns.push(Column::new(name, column.data_type.clone(), true));
+        }
+
+        // Create the table and copy the rows into it
+        self.storage
+            .create_table(stmt.table_name.clone(), Schema::new(columns))?;
+        let copied = result.rows.len();
+        if let Err(error) = self.storage.insert_many(&stmt.table_name, result.rows) {
+            self.storage.drop_table(&stmt.table_name)?;
+            return Err(error.into());
+        }
+
+        Ok(ExecutionResult::new(StatementKind::CreateTableAs).with_rows_affected(copied))
+    }
+
+    /// Execute a CREATE SEQUENCE statement
+    fn execute_create_sequence(
+        &self,
+        stmt: CreateSequenceStatement,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let sequence = build_sequence(&stmt.options)?;
+        self.storage.create_sequence(stmt.name, sequence)?;
+
+        Ok(ExecutionResult::new(StatementKind::CreateSequence))
+    }
+
+    /// Execute a CREATE [OR REPLACE] VIEW statement
+    ///
+    /// The query is run once to check it and find the view's columns. The
+    /// view keeps the query's SQL text and runs it again whenever it is read,
+    /// so it cannot refer to itself, directly or through other views.
+    fn execute_create_view(
+        &self,
+        stmt: CreateViewStatement,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+        if self.storage.table_exists(&stmt.name)? {
+            return Err(StorageError::TableAlreadyExists(stmt.name).into());
+        }
+        if !stmt.or_replace && self.storage.view_exists(&stmt.name)? {
+            return Err(StorageError::ViewAlreadyExists(stmt.name).into());
+        }
+        if self.refers_to(&stmt.query, &stmt.name)? {
+            return failed(format!("View {} cannot refer to itself", stmt.name));
+        }
+        // The query is checked but not run, so it has no side effects
+        let schema = self.describe_select(&stmt.query)?;
 
-                // Insert the row
-                let row = Row::new(row_values);
-                self.storage.insert(&stmt.table_name, row)?;
+        let names = match &stmt.columns {
+            Some(names) if names.len() != schema.columns.len() => {
+                return failed(format!(
+                    "View {} has {} column names but the query has {} columns",
+                    stmt.name,
+                    names.len(),
+                    schema.columns.len()
+                ));
+            }
+            Some(names) => names.clone(),
+            None => schema
+                .columns
+                .iter()
+                .map(|column| column.name.clone())
+                .collect(),
+        };
+        for (i, name) in names.iter().enumerate() {
+            if names[..i].contains(name) {
+                return failed(format!("Duplicate column name {}", name));
             }
+        }
+
+        let view = ViewMetadata {
+            name: stmt.name,
+            columns: stmt.columns,
+            query: stmt.query_sql,
+        };
+        if stmt.or_replace && self.storage.view_exists(&view.name)? {
+            self.replace_view(view)?;
         } else {
-            // No columns specified, insert values as-is
-            for values in stmt.values {
-                // Make sure we have the right number of values
-                if values.len() != schema.columns.len() {
-                    return Err(ExecutionError::ExecutionFailed(format!(
-                        "Column count ({}) does not match value count ({})",
-                        schema.columns.len(),
-                        values.len()
+            self.storage.create_view(view, stmt.or_replace)?;
+        }
+
+        Ok(ExecutionResult::new(StatementKind::CreateView))
+    }
+
+    /// Replace the definition of a view that other views may read
+    ///
+    /// While other views depend on it, the view's columns must stay, in
+    /// order, with the same names and types (new columns may follow them),
+    /// and every dependent view must keep its columns. Otherwise the old
+    /// definition is restored.
+    fn replace_view(&self, view: ViewMetadata) -> Result<(), ExecutionError> {
+        let name = view.name.clone();
+        let mut dependents = Vec::new();
+        for other in self.storage.get_views()? {
+            if other.name != name && self.refers_to(&parse_view_query(&other)?, &name)? {
+                let columns = self.describe_view(&other.name)?.columns;
+                dependents.push((other.name, columns));
+            }
+        }
+        let Some((first_dependent, _)) = dependents.first() else {
+            self.storage.create_view(view, true)?;
+            return Ok(());
+        };
+
+        let old = self.storage.get_view(&name)?;
+        let old_columns = self.describe_view(&name)?.columns;
+        self.storage.create_view(view, true)?;
+        let failed = |message: String| {
+            ExecutionError::ExecutionFailed(format!("Cannot replace view {}: {}", name, message))
+        };
+        let checked = (|| {
+            let columns = self.describe_view(&name)?.columns;
+            for (i, old_column) in old_columns.iter().enumerate() {
+                match columns.get(i) {
+                    Some(column)
+                        if column.name == old_column.name
+                            && column.data_type == old_column.data_type => {}
+                    _ => {
+                        return Err(failed(format!(
+                            "column {} ({}) would be dropped, renamed or changed, \
+                             and view {} depends on it",
+                            old_column.name, old_column.data_type, first_dependent
+                        )));
+                    }
+                }
+            }
+            for (dependent, dependent_columns) in &dependents {
+                let columns = self
+                    .describe_view(dependent)
+                    .map_err(|e| failed(format!("view {} depends on it ({})", dependent, e)))?;
+                let unchanged = columns.columns.len() == dependent_columns.len()
+                    && columns
+                        .columns
+                        .iter()
+                        .zip(dependent_columns)
+                        .all(|(a, b)| a.name == b.name && a.data_type == b.data_type);
+                if !unchanged {
+                    return Err(failed(format!(
+                        "the columns of view {} would change",
+                        dependent
                     )));
                 }
+            }
+            Ok(())
+        })();
+        if checked.is_err() {
+            self.storage.create_view(old, true)?;
+        }
+        checked
+    }
 
-                // Insert the row
-                let row = Row::new(values);
-                self.storage.insert(&stmt.table_name, row)?;
+    /// Execute a DROP VIEW statement
+    ///
+    /// A view that another view reads cannot be dropped.
+    fn execute_drop_view(
+        &self,
+        stmt: DropViewStatement,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        if !self.storage.view_exists(&stmt.name)? {
+            if stmt.if_exists {
+                return Ok(ExecutionResult::new(StatementKind::DropView));
             }
+            return Err(StorageError::ViewNotFound(stmt.name).into());
         }
+        for view in self.storage.get_views()? {
+            if view.name != stmt.name && self.refers_to(&parse_view_query(&view)?, &stmt.name)? {
+                return Err(ExecutionError::ExecutionFailed(format!(
+                    "Cannot drop view {}: view {} depends on it",
+                    stmt.name, view.name
+                )));
+            }
+        }
+        self.storage.drop_view(&stmt.name)?;
 
-        // Return empty result set with count of rows affected
-        let count = self.storage.get_row_count(&stmt.table_name)?;
-        let result = ResultSet::empty(Schema::new(vec![]));
+        Ok(ExecutionResult::new(StatementKind::DropView))
+    }
 
-        // Create a simple message about the operation
-        println!("Inserted rows. Total rows: {}", count);
+    /// Whether a query reads the table or view of the given name, directly
+    /// or through the views it reads
+    fn refers_to(&self, query: &SelectStatement, name: &str) -> Result<bool, ExecutionError> {
+        for source in &query.from {
+            if let TableReference::Table(table) = source {
+                if table == name {
+                    return Ok(true);
+                }
+                if self.storage.view_exists(table)? {
+                    let view = self.storage.get_view(table)?;
+                    if self.refers_to(&parse_view_query(&view)?, name)? {
+                        return Ok(true);
+                    }
+                }
+            }
+        }
+        Ok(false)
+    }
 
-        Ok(result)
+    /// Check a foreign key of a table being created and fill in the
+    /// referenced columns, which default to the referenced table's primary key
+    fn resolve_foreign_key(
+        &self,
+        table_name: &str,
+        schema: &Schema,
+        columns: Vec<String>,
+        target: References,
+    ) -> Result<ForeignKey, ExecutionError> {
+        let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+        let parent = if target.table == table_name {
+            schema.clone()
+        } else {
+            self.storage.get_table_metadata(&target.table)?.schema
+        };
+
+        let referenced = if target.columns.is_empty() {
+            match parent.primary_key() {
+                Some(key) => key.columns.clone(),
+                None => return failed(format!("Table {} has no primary key", target.table)),
+            }
+        } else {
+            target.columns
+        };
+        if referenced.len() != columns.len() {
+            return failed(format!(
+                "Foreign key ({}) does not match referenced columns {}({})",
+                columns.join(", "),
+                target.table,
+                referenced.join(", ")
+            ));
+        }
+        if !parent.is_key(&referenced) {
+            return failed(format!(
+                "Columns {}({}) are not a PRIMARY KEY or UNIQUE key",
+                target.table,
+                referenced.join(", ")
+            ));
+        }
+
+        for (name, referenced_name) in columns.iter().zip(&referenced) {
+            let column = schema
+                .get_column(name)
+                .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))?;
+            let referenced_column = parent
+                .get_column(referenced_name)
+                .ok_or_else(|| ExecutionError::ColumnNotFound(referenced_name.clone()))?;
+            if !column
+                .data_type
+                .comparable_with(&referenced_column.data_type, TypeMode::Strict)
+            {
+                return failed(format!(
+                    "Foreign key column {} ({}) cannot reference {}.{} ({})",
+                    name,
+                    column.data_type,
+                    target.table,
+                    referenced_name,
+                    referenced_column.data_type
+                ));
+            }
+        }
+
+        Ok(ForeignKey {
+            columns,
+            table: target.table,
+            referenced,
+            on_delete: target.on_delete,
+            on_update: target.on_update,
+        })
     }
 
-    /// Execute a SELECT statement
-    fn execute_select(&self, stmt: SelectStatement) -> Result<ResultSet, ExecutionError> {
-        // Get table metadata and verify table exists
+    /// Execute an INSERT statement
+    ///
+    /// Columns left out of the column list or given as DEFAULT take the value
+    /// of their DEFAULT expression, or NULL without one. Generated columns
+    /// are then computed from the rest of the row, and the row must satisfy
+    /// the table's CHECK constraints and the WHERE clauses of `view_checks`,
+    /// the views the INSERT goes through.
+    ///
+    /// Rows inserted, updated or replaced count as affected; rows skipped by
+    /// ON CONFLICT do not. The last insert id is taken from the rows inserted
+    /// or replaced, not from updated ones.
+    fn execute_insert(
+        &self,
+        stmt: InsertStatement,
+        view_checks: Vec<ViewCheck>,
+    ) -> Result<ExecutionResult, ExecutionError> {
+        // A simple view is inserted into through the table it reads
+        if self.storage.view_exists(&stmt.table_name)? {
+            let (stmt, view_checks) = self.insert_through_view(stmt, view_checks)?;
+            return self.execute_insert(stmt, view_checks);
+        }
+
+        // Get table metadata to validate the insert
         let metadata = self.storage.get_table_metadata(&stmt.table_name)?;
-        let table_schema = metadata.schema;
+        let schema = metadata.schema;
 
-        // Get all rows from the table initially
-        let mut rows = self.storage.scan(&stmt.table_name)?;
+        // Find the column each value is for
+        let targets: Vec<usize> = match &stmt.columns {
+            Some(column_names) => {
+                let mut targets = Vec::new();
+                for name in column_names {
+                    let index = schema
+                        .get_column_index(name)
+                        .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))?;
+                    if targets.contains(&index) {
+                        return Err(ExecutionError::ExecutionFailed(format!(
+                            "Column {} is given more than once",
+                            name
+                        )));
+                    }
+                    targets.push(index);
+                }
+                targets
+            }
+            None => (0..schema.columns.len()).collect(),
+        };
 
-        // Apply WHERE clause filter if present
-        if let Some(where_clause) = stmt.where_clause {
-            rows = self.filter_rows(rows, &where_clause, &table_schema)?;
-        }
+        // The rows of a query are inserted as VALUES rows of their values
+        let rows = match stmt.source {
+            InsertSource::Values(rows) => rows,
+            InsertSource::Select(query) => {
+                let result = self.execute_select(*query)?;
+                if result.schema.columns.len() != targets.len() {
+                    return Err(ExecutionError::ExecutionFailed(format!(
+                        "Column count ({}) does not match query column count ({})",
+                        targets.len(),
+                        result.schema.columns.len()
+                    )));
+                }
+                result
+                    .rows
+                    .into_iter()
+                    .map(|row| {
+                        row.values
+                            .into_iter()
+                            .map(|value| InsertValue::Expression(Expression::Literal(value)))
+                            .collect()
+                    })
+                    .collect()
+            }
+        };
+        self.check_insert_types(&stmt.columns, &rows, &schema)?;
 
-        // Handle column projection
-        let result_schema = if stmt.columns.contains(&"*".to_string()) {
-            // Select all columns
-            table_schema.clone()
-        } else {
-            // Project only requested columns
-            let mut columns = Vec::new();
+        // Identity columns default to the next value of their sequence
+        let mut defaults = Vec::new();
+        for column in &schema.columns {
+            defaults.push(match (&column.default, &column.identity) {
+                (_, Some(identity)) => Some(Expression::Function {
+                    name: "NEXTVAL".to_string(),
+                    args: vec![Expression::Literal(Value::Text(identity.sequence.clone()))],
+                }),
+                (Some(sql), None) => Some(parse_stored_expression(sql)?),
+                (None, None) => None,
+            });
+        }
+        let generated = generated_columns(&schema, false)?;
+        let mut view_conditions = Vec::new();
+        for check in view_checks {
+            let (mut columns, mut indexes) = (Vec::new(), Vec::new());
+            for (name, column) in &check.columns {
+                let index = schema
+                    .get_column_index(column)
+                    .ok_or_else(|| ExecutionError::ColumnNotFound(column.clone()))?;
+                let mut column = schema.columns[index].clone();
+                column.name = name.clone();
+                columns.push(column);
+                indexes.push(index);
+            }
+            view_conditions.push((check, Schema::new(columns), indexes));
+        }
+        let mut checks = Vec::new();
+        for check in &schema.checks {
+            checks.push((check, parse_stored_expression(&check.expr)?));
+        }
+        let mode = self.storage.type_mode()?;
+        let (assignments, combined) = match &stmt.on_conflict {
+            Some(on_conflict) => self.check_on_conflict(on_conflict, &stmt.table_name, &schema)?,
+            None => (vec![], Schema::new(vec![])),
+        };
 
-            for col_name in &stmt.columns {
-                let col = table_schema
-                    .get_column(col_name)
-                    .ok_or_else(|| ExecutionError::ColumnNotFound(col_name.clone()))?;
-                columns.push(col.clone());
+        // RETURNING is evaluated for each row stored, inserted or updated
+        for item in &stmt.returning {
+            if let SelectItem::Expression { expr, .. } = item {
+                check_types(expr, &schema, mode)?;
             }
+        }
+        let (returning, returning_schema) = select_outputs(&stmt.returning, &schema)?;
+        if returning.iter().any(|(_, expr)| is_aggregate(expr)) {
+            return Err(ExecutionError::ExecutionFailed(
+                "Aggregate functions cannot be used in RETURNING".to_string(),
+            ));
+        }
+        let mut returned = Vec::new();
 
-            // Create a new schema with only the selected columns
-            Schema::new(columns)
+        let id_column = insert_id_column(&schema);
+        let row_id = |row: &Row| match id_column.map(|index| &row.values[index]) {
+            Some(Value::Integer(id)) => Some(*id),
+            _ => None,
         };
+        let mut last_insert_id = None;
 
-        // Project rows to include only requested columns
-        let result_rows = if stmt.columns.contains(&"*".to_string()) {
-            // Keep all columns
-            rows
-        } else {
-            // Project only requested columns
-            let mut projected_rows = Vec::new();
+        // Every row is worked out and checked before any is written, so that
+        // an error leaves the table unchanged
+        let stored_rows = self.storage.get_row_count(&stmt.table_name)?;
+        let (mut pending, mut replaced) = (Vec::new(), Vec::new());
+        let mut identity_values = Vec::new();
+        let (empty_row, empty_schema) = (Row::new(vec![]), Schema::new(vec![]));
+        for values in &rows {
+            // Make sure we have the right number of values
+            if values.len() != targets.len() {
+                return Err(ExecutionError::ExecutionFailed(format!(
+                    "Column count ({}) does not match value count ({})",
+                    targets.len(),
+                    values.len()
+                )));
+            }
 
-            for row in rows {
-                let mut values = Vec::new();
+            // Evaluate the given values, which cannot refer to columns
+            let mut given = vec![None; schema.columns.len()];
+            for (value, &index) in values.iter().zip(&targets) {
+                let column = &schema.columns[index];
+                match value {
+                    InsertValue::Expression(_) if column.generated.is_some() => {
+                        return Err(ExecutionError::ExecutionFailed(format!(
+                            "Cannot insert a value into generated column {}",
+                            column.name
+                        )));
+                    }
+                    InsertValue::Expression(_)
+                        if column
+                            .identity
+                            .as_ref()
+                            .is_some_and(|identity| identity.always) =>
+                    {
+                        return Err(ExecutionError::ExecutionFailed(format!(
+                            "Cannot insert a value into identity column {} (GENERATED ALWAYS)",
+                            column.name
+                        )));
+                    }
+                    InsertValue::Expression(expr) => {
+                        given[index] = Some(self.evaluate(expr, &empty_row, &empty_schema)?);
+                    }
+                    InsertValue::Default => {}
+                }
+            }
+
+            // Fill in defaults for the rest
+            let mut row_values = Vec::new();
+            for (value, default) in given.into_iter().zip(&defaults) {
+                row_values.push(match (value, default) {
+                    (Some(value), _) => value,
+                    (None, Some(default)) => self.evaluate(default, &empty_row, &empty_schema)?,
+                    (None, None) => Value::Null,
+                });
+            }
 
-                for col_name in &stmt.columns {
-                    let col_idx = table_schema
-                        .get_column_index(col_name)
-                        .ok_or_else(|| ExecutionError::ColumnNotFound(col_name.clone()))?;
+            // Compute generated columns and check constraints from the
+            // values converted to the column types, as they will be stored
+            let mut row = schema.coerce_row(Row::new(row_values), mode);
+            self.compute_generated(&mut row, &schema, &generated)?;
+            let row = schema.coerce_row(row, mode);
+            self.check_row(&row, &schema, &checks)?;
+            for (check, check_schema, indexes) in &view_conditions {
+                let values = indexes.iter().map(|&i| row.values[i].clone()).collect();
+                if !self
+                    .evaluate_condition(&check.condition, &Row::new(values), check_schema)?
+                    .is_true()
+                {
+                    return Err(ExecutionError::ExecutionFailed(format!(
+                        "Row does not satisfy the WHERE clause of view {} ({})",
+                        check.view, check.condition
+                    )));
+                }
+            }
 
-                    let value = row.get_value(col_idx).ok_or_else(|| {
-                        ExecutionError::ExecutionFailed(format!(
-                            "Missing value for column {}",
-                            col_name
-                        ))
-                    })?;
+            // Insert the row or handle a duplicate key. A row that this
+            // statement already wrote cannot be changed again.
+            let conflicts = match &stmt.on_conflict {
+                Some(on_conflict) => {
+                    let target = Some(on_conflict.target.as_slice()).filter(|t| !t.is_empty());
+                    self.storage
+                        .find_conflicts(&stmt.table_name, &row, target, &pending)?
+                }
+                None => vec![],
+            };
+            let action = stmt
+                .on_conflict
+                .as_ref()
+                .map(|on_conflict| &on_conflict.action);
+            let written_again = conflicts.iter().any(|(position, _)| {
+                *position >= stored_rows || replaced.iter().flatten().any(|p| p == position)
+            });
+            let (positions, stored) = match (action, conflicts.first()) {
+                (Some(ConflictAction::Nothing), Some(_)) => continue,
+                (Some(_), Some(_)) if written_again => {
+                    return Err(ExecutionError::ExecutionFailed(format!(
+                        "ON CONFLICT cannot change a row of {} that the same INSERT wrote",
+                        stmt.table_name
+                    )));
+                }
+                (Some(ConflictAction::Update { condition, .. }), Some((position, existing))) => {
+                    // Evaluate against the existing row, the new one, then the
+                    // existing row again for `table.col`
+                    let mut both = existing.values.clone();
+                    both.extend(row.values);
+                    both.extend(existing.values.iter().cloned());
+                    let both = Row::new(both);
+                    if let Some(condition) = condition
+                        && !self
+                            .evaluate_condition(condition, &both, &combined)?
+                            .is_true()
+                    {
+                        continue;
+                    }
 
-                    values.push(value.clone());
+                    let mut values = existing.values.clone();
+                    for (index, expr) in &assignments {
+                        values[*index] = self.evaluate(expr, &both, &combined)?;
+                    }
+                    let mut updated = schema.coerce_row(Row::new(values), mode);
+                    self.compute_generated(&mut updated, &schema, &generated)?;
+                    let updated = schema.coerce_row(updated, mode);
+                    self.check_row(&updated, &schema, &checks)?;
+                    (vec![*position], updated)
                 }
+                (Some(ConflictAction::Replace), Some(_)) => {
+                    let positions = conflicts.iter().map(|(position, _)| *position).collect();
+                    last_insert_id = row_id(&row).or(last_insert_id);
+                    (positions, row)
+                }
+                _ => {
+                    last_insert_id = row_id(&row).or(last_insert_id);
+                    (vec![], row)
+                }
+            };
 
-                projected_rows.push(Row::new(values));
+            // Identity values given explicitly must not be handed out again
+            // by the sequence
+            for (column, value) in schema.columns.iter().zip(&stored.values) {
+                if let (Some(identity), Value::Integer(n)) = (&column.identity, value) {
+                    identity_values.push((identity.sequence.as_str(), *n));
+                }
             }
 
-            projected_rows
-        };
+            if !returning.is_empty() {
+                let mut values = Vec::new();
+                for (_, expr) in &returning {
+                    values.push(self.evaluate(expr, &stored, &schema)?);
+                }
+                returned.push(Row::new(values));
+            }
+            pending.push(stored);
+            replaced.push(positions);
+        }
 
-        // Return the result set
-        Ok(ResultSet::new(result_schema, result_rows))
+        let rows_affected = pending.len();
+        let mut changes = Vec::new();
+        for (positions, row) in replaced.into_iter().zip(pending) {
+            match positions.split_first() {
+                Some((&first, rest)) => {
+                    changes.push(RowChange::Update(first, row));
+                    changes.extend(rest.iter().map(|&position| RowChange::Delete(position)));
+                }
+                None => changes.push(RowChange::Insert(row)),
+            }
+        }
+        let replace = matches!(
+            stmt.on_conflict
+                .as_ref()
+                .map(|on_conflict| &on_conflict.action),
+            Some(ConflictAction::Replace)
+        );
+        let changes = self.referential_actions(&stmt.table_name, changes, replace)?;
+        self.storage.write_rows(changes)?;
+        for (sequence, value) in identity_values {
+            self.storage.skip_sequence_past(sequence, value)?;
+        }
+
+        let result = if stmt.returning.is_empty() {
+            ExecutionResult::new(StatementKind::Insert)
+        } else {
+            ExecutionResult::with_rows(
+                StatementKind::Insert,
+                ResultSet::new(returning_schema, returned),
+            )
+        };
+        Ok(result
+            .with_rows_affected(rows_affected)
+            .with_last_insert_id(last_insert_id))
     }
 
-    /// Filter rows based on WHERE clause conditions
-    fn filter_rows(
+    /// Add the changes that the ON DELETE and ON UPDATE actions of foreign
+    /// keys make to the changes an INSERT makes to its table
+    ///
+    /// The stored rows that the INSERT replaces are deleted with `replace`
+    /// (INSERT OR REPLACE) and updated otherwise (ON CONFLICT DO UPDATE).
+    /// Rows referring to a key that is gone are deleted or given the new key
+    /// (CASCADE), or have the referring columns set to NULL or their
+    /// defaults, and the rows referring to those follow in turn; their
+    /// generated columns and CHECK constraints are evaluated again. Rows
+    /// under NO ACTION and RESTRICT are left for `Database::write_rows` to
+    /// reject. Returns the changes of every table, this one first.
+    fn referential_actions(
         &self,
-        rows: Vec<Row>,
-        where_clause: &WhereClause,
-        schema: &Schema,
-    ) -> Result<Vec<Row>, ExecutionError> {
-        // Convert parser Operator to types Operator
-        let convert_operator = |op: &Operator| -> crate::types::Operator {
-            match op {
-                Operator::Equals => crate::types::Operator::Eq,
-                Operator::NotEquals => crate::types::Operator::NotEq,
-                Operator::GreaterThan => crate::types::Operator::Gt,
-                Operator::LessThan => crate::types::Operator::Lt,
-                Operator::GreaterThanOrEqual => crate::types::Operator::GtEq,
-                Operator::LessThanOrEqual => crate::types::Operator::LtEq,
-            }
+        table_name: &str,
+        changes: Vec<RowChange>,
+        replace: bool,
+    ) -> Result<Vec<(String, Vec<RowChange>)>, ExecutionError> {
+        let mut names = self.storage.get_table_names()?;
+        names.sort();
+        let mut schemas = Vec::new();
+        for name in names {
+            let schema = self.storage.get_table_metadata(&name)?.schema;
+            schemas.push((name, schema));
+        }
+        let referred = |name: &str| {
+            schemas.iter().any(|(_, schema)| {
+                schema
+                    .foreign_keys
+                    .iter()
+                    .any(|foreign_key| foreign_key.table == name)
+            })
         };
+        if !referred(table_name) {
+            return Ok(vec![(table_name.to_string(), changes)]);
+        }
 
-        // For each condition in the WHERE clause, filter the rows
-        let mut filtered_rows = rows;
+        /// Rows of a table as they will be after the INSERT
+        struct PendingRows {
+            rows: Vec<Option<Row>>,
+            /// Rows that the INSERT itself writes, which actions leave alone
+            fixed: Vec<bool>,
+            changed: Vec<bool>,
+        }
+        impl PendingRows {
+            fn new(rows: Vec<Row>) -> Self {
+                let count = rows.len();
+                Self {
+                    rows: rows.into_iter().map(Some).collect(),
+                    fixed: vec![false; count],
+                    changed: vec![false; count],
+                }
+            }
+        }
 
-        for condition in &where_clause.conditions {
-            // Get column index
-            let col_idx = schema
-                .get_column_index(&condition.column)
-                .ok_or_else(|| ExecutionError::ColumnNotFound(condition.column.clone()))?;
+        // Each stored row the INSERT replaces, with the row updating it
+        let stored = self.storage.scan(table_name)?;
+        let mut target = PendingRows::new(stored.clone());
+        let mut queue = Vec::new();
+        for change in &changes {
+            let (position, row) = match change {
+                RowChange::Insert(row) => {
+                    target.rows.push(Some(row.clone()));
+                    target.fixed.push(true);
+                    target.changed.push(false);
+                    continue;
+                }
+                RowChange::Update(position, row) => (*position, Some(row.clone())),
+                RowChange::Delete(position) => (*position, None),
+            };
+            let old = stored.get(position).ok_or_else(|| {
+                StorageError::ConcurrencyError(format!(
+                    "Rows of {} changed while they were being written",
+                    table_name
+                ))
+            })?;
+            queue.push((
+                table_name.to_string(),
+                old.clone(),
+                row.clone().filter(|_| !replace),
+            ));
+            target.rows[position] = row;
+            target.fixed[position] = true;
+        }
+        let mut tables = HashMap::new();
+        tables.insert(table_name.to_string(), target);
 
-            // Convert operator
-            let op = convert_operator(&condition.operator);
+        let mode = self.storage.type_mode()?;
+        let (empty_row, empty_schema) = (Row::new(vec![]), Schema::new(vec![]));
+        while let Some((parent, old, new)) = queue.pop() {
+            let Some((_, parent_schema)) = schemas.iter().find(|(name, _)| *name == parent) else {
+                continue;
+            };
+            for (child, schema) in &schemas {
+                for foreign_key in schema.foreign_keys.iter().filter(|fk| fk.table == parent) {
+                    let key = match parent_schema.values_of(&old, &foreign_key.referenced) {
+                        Some(key) if key.iter().all(|value| !value.is_null()) => key,
+                        _ => continue,
+                    };
+                    let has_key = |row: &Row| {
+                        parent_schema
+                            .values_of(row, &foreign_key.referenced)
+                            .is_some_and(|values| same_values(&values, &key))
+                    };
+                    // Nothing happens while the key is still in the table
+                    if tables[&parent].rows.iter().flatten().any(has_key) {
+                        continue;
+                    }
+                    let action = match &new {
+                        Some(_) => foreign_key.on_update,
+                        None => foreign_key.on_delete,
+                    };
+                    if matches!(
+                        action,
+                        ReferentialAction::NoAction | ReferentialAction::Restrict
+                    ) {
+                        continue;
+                    }
+                    let new_key: Option<Vec<Value>> = new.as_ref().and_then(|new| {
+                        let values = parent_schema.values_of(new, &foreign_key.referenced)?;
+                        Some(values.into_iter().cloned().collect())
+                    });
 
-            // Filter rows
-            filtered_rows = filtered_rows
-                .into_iter()
-                .filter(|row| {
-                    if let Some(value) = row.get_value(col_idx) {
-                        match value.compare(&op, &condition.value) {
-                            Ok(true) => true,
+                    let generated = generated_columns(schema, false)?;
+                    let mut checks = Vec::new();
+                    for check in &schema.checks {
+                        checks.push((check, parse_stored_expression(&check.expr)?));
+                    }
+                    let pending = match tables.entry(child.clone()) {
+                        Entry::Occupied(entry) => entry.into_mut(),
+                        Entry::Vacant(entry) => {
+                            entry.insert(PendingRows::new(self.storage.scan(child)?))
+                        }
+                    };
+                    for position in 0..pending.rows.len() {
+                        let refers = match &pending.rows[position] {
+                            Some(row) if !pending.fixed[position] => schema
+                                .values_of(row, &foreign_key.columns)
+                                .is_some_and(|values| same_values(&values, &key)),
                             _ => false,
+                        };
+                        let Some(row) = pending.rows[position].clone().filter(|_| refers) else {
+                            continue;
+                        };
+
+                        let mut values = row.values.clone();
+                        for (i, name) in foreign_key.columns.iter().enumerate() {
+                            let Some(index) = schema.get_column_index(name) else {
+                                continue;
+                            };
+                            values[index] = match (action, &new_key) {
+                                (ReferentialAction::Cascade, Some(new_key)) => new_key[i].clone(),
+                                (ReferentialAction::SetDefault, _) => {
+                                    match &schema.columns[index].default {
+                                        Some(sql) => self.evaluate(
+                                            &parse_stored_expression(sql)?,
+                                            &empty_row,
+                                            &empty_schema,
+                                        )?,
+                                        None => Value::Null,
+                                    }
+                                }
+                                _ => Value::Null,
+                            };
                         }
-                    } else {
-                        false
+                        let updated = if action == ReferentialAction::Cascade && new.is_none() {
+                            None
+                        } else {
+                            let mut updated = schema.coerce_row(Row::new(values), mode);
+                            self.compute_generated(&mut updated, schema, &generated)?;
+                            let updated = schema.coerce_row(updated, mode);
+                            self.check_row(&updated, schema, &checks)?;
+                            Some(updated)
+                        };
+                        pending.rows[position] = updated.clone();
+                        pending.changed[position] = true;
+                        queue.push((child.clone(), row, updated));
                     }
+                }
+            }
+        }
+
+        // The INSERT's own changes come first, then those of the actions
+        let mut result = vec![(table_name.to_string(), changes)];
+        for (name, _) in &schemas {
+            let Some(pending) = tables.remove(name) else {
+                continue;
+            };
+            let changes: Vec<RowChange> = pending
+                .rows
+                .into_iter()
+                .zip(pending.changed)
+                .enumerate()
+                .filter(|(_, (_, changed))| *changed)
+                .map(|(position, (row, _))| match row {
+                    Some(row) => RowChange::Update(position, row),
+                    None => RowChange::Delete(position),
                 })
                 .collect();
+            if name == table_name {
+                result[0].1.extend(changes);
+            } else if !changes.is_empty() {
+                result.push((name.clone(), changes));
+            }
         }
+        Ok(result)
+    }
 
-        Ok(filtered_rows)
+    /// Rewrite an INSERT into a view as an INSERT into the table (or view)
+    /// that the view reads
+    ///
+    /// Only simple views can be inserted into: a single source in FROM and a
+    /// SELECT list of its plain columns, each used once. Columns left out of
+    /// the view take their defaults. The view's WHERE clause is added to
+    /// `view_checks`, the clauses of the views the INSERT went through
+    /// before, whose columns are renamed to those of the view's source, so
+    /// that a row is only inserted if it shows in the view.
+    fn insert_through_view(
+        &self,
+        stmt: InsertStatement,
+        view_checks: Vec<ViewCheck>,
+    ) -> Result<(InsertStatement, Vec<ViewCheck>), ExecutionError> {
+        let view = self.storage.get_view(&stmt.table_name)?;
+        let query = parse_view_query(&view)?;
+        let not_updatable = |reason: String| {
+            Err(ExecutionError::UnsupportedOperation(format!(
+                "Inserting into view {}: {}",
+                view.name, reason
+            )))
+        };
+        if stmt.on_conflict.is_some() || !stmt.returning.is_empty() {
+            return not_updatable("ON CONFLICT and RETURNING are not supported on views".into());
+        }
+        let base = match query.from.as_slice() {
+            [TableReference::Table(base)] => base.clone(),
+            _ => return not_updatable("it does not read a single table".into()),
+        };
+        let base_schema = if self.storage.view_exists(&base)? {
+            self.describe_view(&base)?
+        } else {
+            self.storage.get_table_metadata(&base)?.schema
+        };
+
+        // The column of the base each column of the view shows
+        let (outputs, _) = select_outputs(&query.columns, &base_schema)?;
+        let mut base_columns: Vec<String> = Vec::new();
+        for (_, expr) in &outputs {
+            match expr {
+                Expression::Column(column) if base_columns.contains(column) => {
+                    return not_updatable(format!("it shows column {} more than once", column));
+                }
+                Expression::Column(column) => base_columns.push(column.clone()),
+                _ => return not_updatable(format!("{} is not a column of {}", expr, base)),
+            }
+        }
+        let names = match view.columns {
+            Some(names) => names,
+            None => outputs.into_iter().map(|(name, _)| name).collect(),
+        };
+
+        let base_column = |column: &str| {
+            names
+                .iter()
+                .position(|name| name == column)
+                .map(|index| base_columns[index].clone())
+                .ok_or_else(|| ExecutionError::ColumnNotFound(column.to_string()))
+        };
+
+        let mut checks = Vec::new();
+        for mut check in view_checks {
+            for (_, column) in &mut check.columns {
+                *column = base_column(column)?;
+            }
+            checks.push(check);
+        }
+        if let Some(where_clause) = query.where_clause {
+            checks.push(ViewCheck {
+                view: view.name.clone(),
+                condition: where_clause.condition,
+                columns: base_schema
+                    .columns
+                    .iter()
+                    .map(|column| (column.name.clone(), column.name.clone()))
+                    .collect(),
+            });
+        }
+
+        let columns = match stmt.columns {
+            Some(columns) => columns
+                .iter()
+                .map(|column| base_column(column))
+                .collect::<Result<_, _>>()?,
+            None => base_columns.clone(),
+        };
+        let stmt = InsertStatement {
+            table_name: base,
+            columns: Some(columns),
+            ..stmt
+        };
+        Ok((stmt, checks))
     }
 
-    /// Helper method to get the database instance
-    pub fn get_storage(&self) -> Database {
-        self.storage.clone()
+    /// Set the generated columns of a row from its other columns
+    fn compute_generated(
+        &self,
+        row: &mut Row,
+        schema: &Schema,
+        generated: &[(usize, Expression)],
+    ) -> Result<(), ExecutionError> {
+        for (index, expr) in generated {
+            row.values[*index] = self.evaluate(expr, row, schema)?;
+        }
+        Ok(())
+    }
+
+    /// Check a row against the table's CHECK constraints, given with their
+    /// parsed conditions
+    fn check_row(
+        &self,
+        row: &Row,
+        schema: &Schema,
+        checks: &[(&CheckConstraint, Expression)],
+    ) -> Result<(), ExecutionError> {
+        for (check, condition) in checks {
+            if self.evaluate_condition(condition, row, schema)? == Truth::False {
+                return Err(ExecutionError::CheckViolation(format!(
+                    "{} ({})",
+                    check.name, check.expr
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the ON CONFLICT clause of an INSERT
+    ///
+    /// Returns the DO UPDATE assignments by column position, and the schema
+    /// they are evaluated with: the table's columns, holding the existing
+    /// row, followed by the same columns named `excluded.col`, holding the
+    /// row that was to be inserted, and again named `table.col`, holding the
+    /// existing row.
+    fn check_on_conflict<'a>(
+        &self,
+        on_conflict: &'a OnConflict,
+        table_name: &str,
+        schema: &Schema,
+    ) -> Result<(Assignments<'a>, Schema), ExecutionError> {
+        let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+        if !on_conflict.target.is_empty() && !schema.is_key(&on_conflict.target) {
+            return failed(format!(
+                "ON CONFLICT columns ({}) are not a PRIMARY KEY or UNIQUE key of {}",
+                on_conflict.target.join(", "),
+                table_name
+            ));
+        }
+        let ConflictAction::Update {
+            assignments,
+            condition,
+        } = &on_conflict.action
+        else {
+            return Ok((vec![], Schema::new(vec![])));
+        };
+        if on_conflict.target.is_empty() {
+            return failed("ON CONFLICT DO UPDATE needs the columns of a key".to_string());
+        }
+
+        let mut columns = schema.columns.clone();
+        for qualifier in ["excluded", table_name] {
+            for column in &schema.columns {
+                let mut qualified = column.clone();
+                qualified.name = format!("{}.{}", qualifier, column.name);
+                columns.push(qualified);
+            }
+        }
+        let combined = Schema::new(columns);
+
+        let mode = self.storage.type_mode()?;
+        let mut resolved = Vec::new();
+        for (name, expr) in assignments {
+            let index = schema
+                .get_column_index(name)
+                .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))?;
+            let column = &schema.columns[index];
+            if column.generated.is_some() {
+                return failed(format!("Cannot update generated column {}", name));
+            }
+            if resolved.iter().any(|(other, _)| *other == index) {
+                return failed(format!("Column {} is given more than once", name));
+            }
+            check_stored_type(expr, &combined, &column.name, &column.data_type, mode)?;
+            resolved.push((index, expr));
+        }
+        if let Some(condition) = condition {
+            check_types(condition, &combined, mode)?;
+        }
+        Ok((resolved, combined))
+    }
+
+    /// In strict type mode, check that every inserted expression has a type
+    /// that converts to its column's type without a CAST
+    fn check_insert_types(
+        &self,
+        columns: &Option<Vec<String>>,
+        rows: &[Vec<InsertValue>],
+        schema: &Schema,
+    ) -> Result<(), ExecutionError> {
+        let mode = self.storage.type_mode()?;
+        if mode == TypeMode::Lenient {
+            return Ok(());
+        }
+
+        let columns: Vec<&Column> = match columns {
+            Some(names) => names
+                .iter()
+                .map(|name| {
+                    schema
+                        .get_column(name)
+                        .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))
+                })
+                .collect::<Result<_, _>>()?,
+            None => schema.columns.iter().collect(),
+        };
+        let empty = Schema::new(vec![]);
+        for values in rows {
+            for (value, column) in values.iter().zip(&columns) {
+                let expr = match value {
+                    InsertValue::Expression(Expression::Literal(Value::Null))
+                    | InsertValue::Default => continue,
+                    InsertValue::Expression(expr) => expr,
+                };
+                check_stored_type(expr, &empty, &column.name, &column.data_type, mode)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Execute a SELECT statement
+    fn execute_select(&self, stmt: SelectStatement) -> Result<ResultSet, ExecutionError> {
+        // Produce the rows of the FROM list
+        let (table_schema, mut rows) = self.scan_from(&stmt.from)?;
+        check_ambiguous(&stmt, &table_schema)?;
+
+        // Reject implicit conversions in strict type mode before producing rows
+        let mode = self.storage.type_mode()?;
+        if let Some(where_clause) = &stmt.where_clause {
+            check_types(&where_clause.condition, &table_schema, mode)?;
+        }
+        for item in &stmt.columns {
+            if let SelectItem::Expression { expr, .. } = item {
+                check_types(expr, &table_schema, mode)?;
+            }
+        }
+
+        // Apply WHERE clause filter if present
+        if let Some(where_clause) = &stmt.where_clause {
+            rows = self.filter_rows(rows, where_clause, &table_schema)?;
+        }
+
+        let (outputs, result_schema) = select_outputs(&stmt.columns, &table_schema)?;
+
+        // Aggregate functions combine all the rows into a single row
+        if outputs.iter().any(|(_, expr)| is_aggregate(expr)) {
+            let row = self.aggregate_rows(&outputs, &rows, &table_schema)?;
+            return Ok(ResultSet::new(result_schema, vec![row]));
+        }
+
+        // Project rows, keeping each source row for ORDER BY
+        let mut projected_rows = Vec::new();
+        for row in rows {
+            let mut values = Vec::new();
+            for (_, expr) in &outputs {
+                values.push(self.evaluate(expr, &row, &table_schema)?);
+            }
+            projected_rows.push((row, Row::new(values)));
+        }
+
+        let result_rows = if stmt.order_by.is_empty() {
+            projected_rows.into_iter().map(|(_, row)| row).collect()
+        } else {
+            self.sort_rows(projected_rows, &stmt.order_by, &table_schema, &outputs)?
+        };
+
+        // Return the result set
+        Ok(ResultSet::new(result_schema, result_rows))
+    }
+
+    /// Compute the single output row of a select list with aggregates
+    ///
+    /// Each item must be an aggregate function call or an expression that
+    /// does not refer to columns, as there is no GROUP BY.
+    fn aggregate_rows(
+        &self,
+        outputs: &[(String, Expression)],
+        rows: &[Row],
+        schema: &Schema,
+    ) -> Result<Row, ExecutionError> {
+        let mut values = Vec::new();
+        for (_, expr) in outputs {
+            let value = match expr {
+                Expression::Function { name, args } if is_aggregate(expr) => {
+                    let mut inputs = Vec::new();
+                    for row in rows {
+                        let mut arg_values = Vec::new();
+                        for arg in args {
+                            arg_values.push(self.evaluate(arg, row, schema)?);
+                        }
+                        inputs.push(arg_values);
+                    }
+                    functions::aggregate(name, args.len(), inputs)?
+                }
+                _ => self
+                    .evaluate(expr, &Row::new(vec![]), &Schema::new(vec![]))
+                    .map_err(|e| match e {
+                        ExecutionError::ColumnNotFound(name)
+                            if schema.get_column(&name).is_some() =>
+                        {
+                            ExecutionError::ExecutionFailed(format!(
+                                "Column {} must be used in an aggregate function",
+                                name
+                            ))
+                        }
+                        e => e,
+                    })?,
+            };
+            values.push(value);
+        }
+        Ok(Row::new(values))
+    }
+
+    /// Produce the schema and rows of a FROM list
+    ///
+    /// The list may start with a table; each table function after it is
+    /// called once per row so far, with its arguments evaluated against that
+    /// row, and its output columns appended. Joining tables is not supported.
+    /// When the list has table functions every column is named after its
+    /// source (see `from_schema`).
+    fn scan_from(&self, from: &[TableReference]) -> Result<(Schema, Vec<Row>), ExecutionError> {
+        let mut schema = Schema::new(vec![]);
+        let mut rows = vec![Row::new(vec![])];
+
+        for (i, source) in from.iter().enumerate() {
+            match source {
+                TableReference::Table(name) if i == 0 && self.storage.view_exists(name)? => {
+                    (schema, rows) = self.scan_view(name)?;
+                }
+                TableReference::Table(name) if i == 0 => {
+                    schema = self.storage.get_table_metadata(name)?.schema;
+                    rows = self.storage.scan(name)?;
+
+                    // VIRTUAL generated columns are computed as rows are read,
+                    // and converted to their column types as on insert
+                    let generated = generated_columns(&schema, true)?;
+                    if !generated.is_empty() {
+                        let mode = self.storage.type_mode()?;
+                        rows = rows
+                            .into_iter()
+                            .map(|mut row| {
+                                self.compute_generated(&mut row, &schema, &generated)?;
+                                Ok(schema.coerce_row(row, mode))
+                            })
+                            .collect::<Result<_, ExecutionError>>()?;
+                    }
+                }
+                TableReference::Table(name) => {
+                    return Err(ExecutionError::UnsupportedOperation(format!(
+                        "Joining table {} with other sources",
+                        name
+                    )));
+                }
+                TableReference::Function { name, args, .. } => {
+                    let columns = function_columns(source, &schema)?;
+                    let mut joined = Vec::new();
+                    for row in rows {
+                        let mut values = Vec::new();
+                        for arg in args {
+                            values.push(self.evaluate(arg, &row, &schema)?);
+                        }
+                        for output in table_functions::call(name, values)? {
+                            let mut values = row.values.clone();
+                            values.extend(output.values);
+                            joined.push(Row::new(values));
+                        }
+                    }
+                    rows = joined;
+                    schema.columns.extend(columns);
+                }
+            }
+            schema = from_schema(schema, from, i);
+        }
+
+        Ok((schema, rows))
+    }
+
+    /// Check a query and work out its result columns without running it
+    ///
+    /// Nothing is read and no function is called, so functions with side
+    /// effects such as NEXTVAL do not run.
+    fn describe_select(&self, stmt: &SelectStatement) -> Result<Schema, ExecutionError> {
+        let mut schema = Schema::new(vec![]);
+        for (i, source) in stmt.from.iter().enumerate() {
+            match source {
+                TableReference::Table(name) if i == 0 && self.storage.view_exists(name)? => {
+                    schema = self.describe_view(name)?;
+                }
+                TableReference::Table(name) if i == 0 => {
+                    schema = self.storage.get_table_metadata(name)?.schema;
+                }
+                TableReference::Table(name) => {
+                    return Err(ExecutionError::UnsupportedOperation(format!(
+                        "Joining table {} with other sources",
+                        name
+                    )));
+                }
+                TableReference::Function { .. } => {
+                    schema.columns.extend(function_columns(source, &schema)?);
+                }
+            }
+            schema = from_schema(schema, &stmt.from, i);
+        }
+
+        check_ambiguous(stmt, &schema)?;
+        let mode = self.storage.type_mode()?;
+        if let Some(where_clause) = &stmt.where_clause {
+            if let Some(name) = unknown_column(&where_clause.condition, &schema) {
+                return Err(ExecutionError::ColumnNotFound(name));
+            }
+            check_types(&where_clause.condition, &schema, mode)?;
+        }
+        for item in &stmt.columns {
+            if let SelectItem::Expression { expr, .. } = item {
+                check_types(expr, &schema, mode)?;
+            }
+        }
+        let (outputs, result_schema) = select_outputs(&stmt.columns, &schema)?;
+        if outputs.iter().any(|(_, expr)| is_aggregate(expr)) {
+            for (_, expr) in &outputs {
+                if !is_aggregate(expr)
+                    && let Some(name) = unknown_column(expr, &Schema::new(vec![]))
+                {
+                    return Err(ExecutionError::ExecutionFailed(format!(
+                        "Column {} must be used in an aggregate function",
+                        name
+                    )));
+                }
+            }
+        }
+        self.sort_rows(vec![], &stmt.order_by, &schema, &outputs)?;
+        Ok(result_schema)
+    }
+
+    /// The result columns of a view, with the view's names
+    fn describe_view(&self, name: &str) -> Result<Schema, ExecutionError> {
+        let view = self.storage.get_view(name)?;
+        let mut schema = self.describe_select(&parse_view_query(&view)?)?;
+        if let Some(names) = view.columns {
+            for (column, name) in schema.columns.iter_mut().zip(names) {
+                column.name = name;
+            }
+        }
+        Ok(schema)
+    }
+
+    /// Run the query of a view, giving its result columns the view's names
+    fn scan_view(&self, name: &str) -> Result<(Schema, Vec<Row>), ExecutionError> {
+        let view = self.storage.get_view(name)?;
+        let result = self.execute_select(parse_view_query(&view)?)?;
+        let mut schema = result.schema;
+        if let Some(names) = view.columns {
+            for (column, name) in schema.columns.iter_mut().zip(names) {
+                column.name = name;
+            }
+        }
+        Ok((schema, result.rows))
+    }
+
+    /// Sort projected rows by the ORDER BY items
+    ///
+    /// A bare name that matches an output column (such as an alias) and an
+    /// integer literal N both refer to output columns; any other expression
+    /// is evaluated against the source row. A name matching several output
+    /// columns with different expressions is ambiguous.
+    fn sort_rows(
+        &self,
+        rows: Vec<(Row, Row)>,
+        order_by: &[OrderByItem],
+        table_schema: &Schema,
+        outputs: &[(String, Expression)],
+    ) -> Result<Vec<Row>, ExecutionError> {
+        let mut output_indexes = Vec::new();
+        for item in order_by {
+            output_indexes.push(match &item.expr {
+                Expression::Column(name) => {
+                    let mut matching = outputs
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (output, _))| output == name);
+                    let first = matching.next();
+                    if let Some((_, (_, expr))) = first
+                        && matching.any(|(_, (_, other))| other.to_string() != expr.to_string())
+                    {
+                        return Err(ExecutionError::ExecutionFailed(format!(
+                            "ORDER BY {} is ambiguous",
+                            name
+                        )));
+                    }
+                    if first.is_none() && table_schema.is_ambiguous(name) {
+                        return Err(ambiguous_column_error(name));
+                    }
+                    if first.is_none() && table_schema.get_column(name).is_none() {
+                        return Err(ExecutionError::ColumnNotFound(name.clone()));
+                    }
+                    first.map(|(index, _)| index)
+                }
+                Expression::Literal(Value::Integer(position)) => match usize::try_from(*position) {
+                    Ok(position) if (1..=outputs.len()).contains(&position) => Some(position - 1),
+                    _ => {
+                        return Err(ExecutionError::ExecutionFailed(format!(
+                            "ORDER BY position {} is not in select list",
+                            position
+                        )));
+                    }
+                },
+                // Other expressions are evaluated against the source rows
+                expr => {
+                    if let Some(name) = ambiguous_column(expr, table_schema) {
+                        return Err(ambiguous_column_error(&name));
+                    }
+                    match unknown_column(expr, table_schema) {
+                        Some(name) => return Err(ExecutionError::ColumnNotFound(name)),
+                        None => None,
+                    }
+                }
+            });
+        }
+
+        let mut keyed_rows = Vec::new();
+        for (source, output) in rows {
+            let mut keys = Vec::new();
+            for (item, output_index) in order_by.iter().zip(&output_indexes) {
+                let key = match *output_index {
+                    Some(index) => output.values[index].clone(),
+                    None => self.evaluate(&item.expr, &source, table_schema)?,
+                };
+                keys.push(key);
+            }
+            keyed_rows.push((keys, output));
+        }
+
+        // Stable sort, so rows with equal keys keep their scan order
+        keyed_rows.sort_by(|(a, _), (b, _)| {
+            for ((a, b), item) in a.iter().zip(b).zip(order_by) {
+                let ordering = a.sort_cmp(b);
+                let ordering = if item.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                };
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+            Ordering::Equal
+        });
+
+        Ok(keyed_rows.into_iter().map(|(_, row)| row).collect())
+    }
+
+    /// Filter rows based on WHERE clause conditions
+    ///
+    /// Only rows for which the condition is TRUE are kept; FALSE and UNKNOWN
+    /// both reject the row.
+    fn filter_rows(
+        &self,
+        rows: Vec<Row>,
+        where_clause: &WhereClause,
+        schema: &Schema,
+    ) -> Result<Vec<Row>, ExecutionError> {
+        let mut filtered_rows = Vec::new();
+
+        for row in rows {
+            if self
+                .evaluate_condition(&where_clause.condition, &row, schema)?
+                .is_true()
+            {
+                filtered_rows.push(row);
+            }
+        }
+
+        Ok(filtered_rows)
+    }
+
+    /// Evaluate an operand expression to a value for the given row
+    fn evaluate(
+        &self,
+        expr: &Expression,
+        row: &Row,
+        schema: &Schema,
+    ) -> Result<Value, ExecutionError> {
+        match expr {
+            Expression::Column(name) => {
+                let col_idx = schema
+                    .get_column_index(name)
+                    .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))?;
+
+                row.get_value(col_idx).cloned().ok_or_else(|| {
+                    ExecutionError::ExecutionFailed(format!("Missing value for column {}", name))
+                })
+            }
+            Expression::Literal(value) => Ok(value.clone()),
+
+            // CASE, COALESCE and NULLIF evaluate lazily: only the branches and
+            // arguments needed to produce the result are evaluated
+            Expression::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => {
+                let operand = match operand {
+                    Some(operand) => Some(self.evaluate(operand, row, schema)?),
+                    None => None,
+                };
+
+                for (when, then) in when_clauses {
+                    let selected = match &operand {
+                        Some(operand) => {
+                            let when = self.evaluate(when, row, schema)?;
+                            operand.compare(&TypeOperator::Eq, &when)?
+                        }
+                        None => self.evaluate_condition(when, row, schema)?,
+                    };
+                    if selected.is_true() {
+                        return self.evaluate(then, row, schema);
+                    }
+                }
+
+                match else_result {
+                    Some(else_result) => self.evaluate(else_result, row, schema),
+                    None => Ok(Value::Null),
+                }
+            }
+
+            Expression::Coalesce(args) => {
+                for arg in args {
+                    let value = self.evaluate(arg, row, schema)?;
+                    if !value.is_null() {
+                        return Ok(value);
+                    }
+                }
+                Ok(Value::Null)
+            }
+
+            Expression::NullIf(left, right) => {
+                let left = self.evaluate(left, row, schema)?;
+                let right = self.evaluate(right, row, schema)?;
+                if left.compare(&TypeOperator::Eq, &right)?.is_true() {
+                    Ok(Value::Null)
+                } else {
+                    Ok(left)
+                }
+            }
+
+            Expression::BinaryOp {
+                left: left_expr,
+                op,
+                right: right_expr,
+            } => {
+                let left = self.evaluate(left_expr, row, schema)?;
+                let right = self.evaluate(right_expr, row, schema)?;
+                match (op, convert_arithmetic_operator(op)) {
+                    (_, Some(op)) => Ok(left.arithmetic(&op, &right)?),
+                    (BinaryOperator::JsonGet, None) => functions::json_get(&left, &right, false),
+                    (BinaryOperator::JsonGetText, None) => functions::json_get(&left, &right, true),
+                    (_, None) => functions::concat(&left, &right, || {
+                        // A NULL literal takes the type of the other operand,
+                        // so `ARRAY[1] || NULL` is `ARRAY[1]`
+                        let operand_type = |expr: &Expression, other: &Expression| match expr {
+                            Expression::Literal(Value::Null) => infer_type(other, schema),
+                            expr => infer_type(expr, schema),
+                        };
+                        Ok((
+                            operand_type(left_expr, right_expr)?.0,
+                            operand_type(right_expr, left_expr)?.0,
+                        ))
+                    }),
+                }
+            }
+
+            Expression::Negate(expr) => Ok(self.evaluate(expr, row, schema)?.negate()?),
+
+            Expression::Cast {
+                expr,
+                data_type,
+                try_cast,
+            } => match self.evaluate(expr, row, schema)?.as_type(data_type) {
+                Ok(value) => Ok(value),
+                Err(_) if *try_cast => Ok(Value::Null),
+                Err(e) => Err(e.into()),
+            },
+
+            Expression::Array(items) => {
+                let mut values = Vec::new();
+                for item in items {
+                    values.push(self.evaluate(item, row, schema)?);
+                }
+                Ok(Value::Array(values))
+            }
+
+            // Subscripts count from 1; out of range subscripts give NULL
+            Expression::Subscript { expr, index } => {
+                let value = self.evaluate(expr, row, schema)?;
+                let index = self.evaluate(index, row, schema)?;
+                match (value, index.as_type(&DataType::Integer)?) {
+                    (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+                    (Value::Array(items), Value::Integer(index)) => Ok(index
+                        .checked_sub(1)
+                        .and_then(|i| usize::try_from(i).ok())
+                        .and_then(|i| items.into_iter().nth(i))
+                        .unwrap_or(Value::Null)),
+                    (value, _) => Err(ExecutionError::InvalidValue(format!(
+                        "Cannot subscript {}",
+                        value
+                    ))),
+                }
+            }
+
+            Expression::Function { name, args } => {
+                let mut values = Vec::new();
+                for arg in args {
+                    values.push(self.evaluate(arg, row, schema)?);
+                }
+                if functions::is_sequence_function(name) {
+                    functions::check_arg_count(name, values.len())?;
+                    return self.call_sequence_function(name, values);
+                }
+                functions::call(name, values)
+            }
+
+            // Conditions produce their truth value
+            _ => Ok(self.evaluate_condition(expr, row, schema)?.into()),
+        }
+    }
+
+    /// Evaluate a condition for the given row under three-valued logic
+    fn evaluate_condition(
+        &self,
+        expr: &Expression,
+        row: &Row,
+        schema: &Schema,
+    ) -> Result<Truth, ExecutionError> {
+        match expr {
+            Expression::Comparison {
+                left,
+                operator,
+                right,
+            } => {
+                let left = self.evaluate(left, row, schema)?;
+                let right = self.evaluate(right, row, schema)?;
+                Ok(left.compare(&convert_operator(operator), &right)?)
+            }
+
+            Expression::Quantified {
+                left,
+                operator,
+                all,
+                right,
+            } => {
+                // `x = ANY (a)` is `x = a[1] OR x = a[2] ...`, and ALL uses AND
+                let value = self.evaluate(left, row, schema)?;
+                let items = match self.evaluate(right, row, schema)? {
+                    Value::Array(items) => items,
+                    Value::Null => return Ok(Truth::Unknown),
+                    other => {
+                        return Err(ExecutionError::InvalidValue(format!(
+                            "{} requires an array, got {}",
+                            if *all { "ALL" } else { "ANY" },
+                            other
+                        )));
+                    }
+                };
+                let operator = convert_operator(operator);
+                let mut result = if *all { Truth::True } else { Truth::False };
+                for item in &items {
+                    let truth = value.compare(&operator, item)?;
+                    result = if *all {
+                        result.and(truth)
+                    } else {
+                        result.or(truth)
+                    };
+                }
+                Ok(result)
+            }
+
+            Expression::IsNull { expr, negated } => {
+                let value = self.evaluate(expr, row, schema)?;
+                Ok((value.is_null() != *negated).into())
+            }
+
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => {
+                // `x IN (a, b)` is `x = a OR x = b`
+                let value = self.evaluate(expr, row, schema)?;
+                let mut result = Truth::False;
+                for item in list {
+                    let item = self.evaluate(item, row, schema)?;
+                    result = result.or(value.compare(&TypeOperator::Eq, &item)?);
+                    if result.is_true() {
+                        break;
+                    }
+                }
+                Ok(if *negated { !result } else { result })
+            }
+
+            Expression::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => {
+                // `x BETWEEN a AND b` is `x >= a AND x <= b`
+                let value = self.evaluate(expr, row, schema)?;
+                let low = self.evaluate(low, row, schema)?;
+                let high = self.evaluate(high, row, schema)?;
+                let result = value
+                    .compare(&TypeOperator::GtEq, &low)?
+                    .and(value.compare(&TypeOperator::LtEq, &high)?);
+                Ok(if *negated { !result } else { result })
+            }
+
+            Expression::Like {
+                expr,
+                pattern,
+                escape,
+                case_insensitive,
+                negated,
+            } => {
+                let value = self.evaluate(expr, row, schema)?;
+                let pattern = self.evaluate(pattern, row, schema)?;
+                let escape = match escape {
+                    Some(escape) => match self.evaluate(escape, row, schema)? {
+                        Value::Null => return Ok(Truth::Unknown),
+                        Value::Text(s) if s.chars().count() == 1 => s.chars().next(),
+                        other => {
+                            return Err(ExecutionError::InvalidValue(format!(
+                                "ESCAPE must be a single character, got {}",
+                                other
+                            )));
+                        }
+                    },
+                    None => None,
+                };
+                if value.is_null() || pattern.is_null() {
+                    return Ok(Truth::Unknown);
+                }
+
+                let matched = value.like(&pattern, escape, *case_insensitive)?;
+                Ok((matched != *negated).into())
+            }
+
+            // Short-circuit so the right side is only evaluated when it can
+            // change the result
+            Expression::And(left, right) => {
+                let left = self.evaluate_condition(left, row, schema)?;
+                if left == Truth::False {
+                    return Ok(Truth::False);
+                }
+                Ok(left.and(self.evaluate_condition(right, row, schema)?))
+            }
+
+            Expression::Or(left, right) => {
+                let left = self.evaluate_condition(left, row, schema)?;
+                if left == Truth::True {
+                    return Ok(Truth::True);
+                }
+                Ok(left.or(self.evaluate_condition(right, row, schema)?))
+            }
+
+            Expression::Not(expr) => Ok(!self.evaluate_condition(expr, row, schema)?),
+
+            Expression::Column(_)
+            | Expression::Literal(_)
+            | Expression::Case { .. }
+            | Expression::Coalesce(_)
+            | Expression::NullIf(..)
+            | Expression::BinaryOp { .. }
+            | Expression::Negate(_)
+            | Expression::Cast { .. }
+            | Expression::Array(_)
+            | Expression::Subscript { .. }
+            | Expression::Function { .. } => Ok(self.evaluate(expr, row, schema)?.to_truth()?),
+        }
+    }
+
+    /// Call NEXTVAL, CURRVAL or SETVAL, whose first argument is the name of
+    /// a sequence
+    fn call_sequence_function(
+        &self,
+        name: &str,
+        args: Vec<Value>,
+    ) -> Result<Value, ExecutionError> {
+        if args.iter().any(Value::is_null) {
+            return Ok(Value::Null);
+        }
+        let sequence = match &args[0] {
+            Value::Text(sequence) => sequence,
+            value => {
+                return Err(ExecutionError::InvalidValue(format!(
+                    "Sequence name must be text, not {}",
+                    value
+                )));
+            }
+        };
+        let value = match name.to_uppercase().as_str() {
+            "NEXTVAL" => self.storage.next_value(sequence)?,
+            "CURRVAL" => self.storage.current_value(sequence)?,
+            _ => {
+                let Value::Integer(value) = args[1].as_type(&DataType::Integer)? else {
+                    unreachable!("conversion to INTEGER yields an INTEGER");
+                };
+                let is_called = match args.get(2) {
+                    Some(is_called) => {
+                        is_called.as_type(&DataType::Boolean)? == Value::Boolean(true)
+                    }
+                    None => true,
+                };
+                self.storage.set_value(sequence, value, is_called)?
+            }
+        };
+        Ok(Value::Integer(value))
+    }
+
+    /// Helper method to get the database instance
+    pub fn get_storage(&self) -> Database {
+        self.storage.clone()
+    }
+}
+
+/// Expand a select list into one named expression per output column, and
+/// build the result schema, inferring each column's type from its expression
+fn select_outputs(
+    items: &[SelectItem],
+    schema: &Schema,
+) -> Result<(Vec<(String, Expression)>, Schema), ExecutionError> {
+    let mut outputs = Vec::new();
+    for item in items {
+        match item {
+            SelectItem::Wildcard => {
+                for col in &schema.columns {
+                    let name = match col.name.split_once('.') {
+                        Some((_, name)) => name.to_string(),
+                        None => col.name.clone(),
+                    };
+                    outputs.push((name, Expression::Column(col.name.clone())));
+                }
+            }
+            SelectItem::Expression { expr, .. } => {
+                outputs.push((item.output_name(), expr.clone()));
+            }
+        }
+    }
+
+    let mut columns = Vec::new();
+    for (name, expr) in &outputs {
+        let (data_type, nullable) = infer_type(expr, schema)?;
+        columns.push(Column::new(name.clone(), data_type, nullable));
+    }
+    Ok((outputs, Schema::new(columns)))
+}
+
+/// Expressions assigned to columns, by column position
+type Assignments<'a> = Vec<(usize, &'a Expression)>;
+
+/// The WHERE clause of a view that rows inserted through it must satisfy
+struct ViewCheck {
+    view: String,
+    condition: Expression,
+    /// Each column name the condition may use, with the column of the table
+    /// (or view) inserted into that it stands for
+    columns: Vec<(String, String)>,
+}
+
+/// The column whose value is reported as the id of an inserted row: the
+/// first identity column, or else an INTEGER primary key of one column
+fn insert_id_column(schema: &Schema) -> Option<usize> {
+    if let Some(index) = schema.columns.iter().position(|c| c.identity.is_some()) {
+        return Some(index);
+    }
+    let key = schema
+        .keys
+        .iter()
+        .find(|key| key.primary && key.columns.len() == 1)?;
+    let index = schema.get_column_index(&key.columns[0])?;
+    (schema.columns[index].data_type == DataType::Integer).then_some(index)
+}
+
+/// Build a sequence from CREATE SEQUENCE or identity column options
+///
+/// Ascending sequences default to 1..=i64::MAX and descending ones to
+/// i64::MIN..=-1, starting from the bound they move away from.
+fn build_sequence(options: &SequenceOptions) -> Result<Sequence, ExecutionError> {
+    let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+    let increment = options.increment.unwrap_or(1);
+    if increment == 0 {
+        return failed("Sequence INCREMENT cannot be zero".to_string());
+    }
+    let (min_value, max_value) = if increment > 0 {
+        (
+            options.min_value.unwrap_or(1),
+            options.max_value.unwrap_or(i64::MAX),
+        )
+    } else {
+        (
+            options.min_value.unwrap_or(i64::MIN),
+            options.max_value.unwrap_or(-1),
+        )
+    };
+    if min_value >= max_value {
+        return failed(format!(
+            "Sequence MINVALUE ({}) must be less than MAXVALUE ({})",
+            min_value, max_value
+        ));
+    }
+    let start = options
+        .start
+        .unwrap_or(if increment > 0 { min_value } else { max_value });
+    if !(min_value..=max_value).contains(&start) {
+        return failed(format!(
+            "Sequence START value ({}) must be between MINVALUE ({}) and MAXVALUE ({})",
+            start, min_value, max_value
+        ));
+    }
+    Ok(Sequence::new(
+        start,
+        increment,
+        min_value,
+        max_value,
+        options.cycle,
+    ))
+}
+
+/// Whether two keys have equal values
+fn same_values(a: &[&Value], b: &[&Value]) -> bool {
+    a.len() == b.len()
+        && a.iter().zip(b).all(|(a, b)| {
+            a.compare(&TypeOperator::Eq, b)
+                .is_ok_and(|truth| truth.is_true())
+        })
+}
+
+/// The first column an expression refers to that is not in the schema
+fn unknown_column(expr: &Expression, schema: &Schema) -> Option<String> {
+    match expr {
+        Expression::Column(name) if schema.get_column(name).is_none() => Some(name.clone()),
+        _ => expr
+            .children()
+            .into_iter()
+            .find_map(|child| unknown_column(child, schema)),
+    }
+}
+
+/// The first column an expression refers to by a name that columns of
+/// several sources of a FROM list have
+fn ambiguous_column(expr: &Expression, schema: &Schema) -> Option<String> {
+    match expr {
+        Expression::Column(name) if schema.is_ambiguous(name) => Some(name.clone()),
+        _ => expr
+            .children()
+            .into_iter()
+            .find_map(|child| ambiguous_column(child, schema)),
+    }
+}
+
+/// Error for a column name that columns of several sources have
+fn ambiguous_column_error(name: &str) -> ExecutionError {
+    ExecutionError::ExecutionFailed(format!(
+        "Column {} is ambiguous; qualify it with the name of its source",
+        name
+    ))
+}
+
+/// Reject a column name in the WHERE clause or select list of a query that
+/// columns of several sources of its FROM list have
+fn check_ambiguous(stmt: &SelectStatement, schema: &Schema) -> Result<(), ExecutionError> {
+    let mut exprs: Vec<&Expression> = stmt
+        .columns
+        .iter()
+        .filter_map(|item| match item {
+            SelectItem::Expression { expr, .. } => Some(expr),
+            SelectItem::Wildcard => None,
+        })
+        .collect();
+    exprs.extend(stmt.where_clause.as_ref().map(|clause| &clause.condition));
+    match exprs
+        .into_iter()
+        .find_map(|expr| ambiguous_column(expr, schema))
+    {
+        Some(name) => Err(ambiguous_column_error(&name)),
+        None => Ok(()),
+    }
+}
+
+/// The output columns of a table function in a FROM list, called with
+/// arguments evaluated against the columns of the sources before it
+///
+/// The columns are named `source.col`, where the source is the function's
+/// alias, or its name in lower case, and `col` its column alias, if any. A
+/// function with a single column, such as UNNEST, names it after the alias
+/// when there are no column aliases, as PostgreSQL does.
+fn function_columns(
+    function: &TableReference,
+    schema: &Schema,
+) -> Result<Vec<Column>, ExecutionError> {
+    let TableReference::Function {
+        name,
+        args,
+        alias,
+        column_aliases,
+    } = function
+    else {
+        return Ok(vec![]);
+    };
+    let failed = |message: String| Err(ExecutionError::ExecutionFailed(message));
+
+    let mut arg_types = Vec::new();
+    for arg in args {
+        if let Some(column) = ambiguous_column(arg, schema) {
+            return Err(ambiguous_column_error(&column));
+        }
+        arg_types.push(infer_type(arg, schema)?.0);
+    }
+    let mut columns = table_functions::columns(name, &arg_types)?;
+    if column_aliases.len() > columns.len() {
+        return failed(format!(
+            "{} has {} column(s) but {} column aliases were given",
+            name,
+            columns.len(),
+            column_aliases.len()
+        ));
+    }
+    if let (Some(alias), [column], []) = (alias, columns.as_mut_slice(), column_aliases.as_slice())
+    {
+        column.name = alias.clone();
+    }
+    for (column, alias) in columns.iter_mut().zip(column_aliases) {
+        column.name = alias.clone();
+    }
+
+    let source = alias.clone().unwrap_or_else(|| name.to_lowercase());
+    if schema.columns.iter().any(|col| {
+        col.name
+            .split_once('.')
+            .is_some_and(|(other, _)| other == source)
+    }) {
+        return failed(format!(
+            "{} is used more than once in FROM; give the function an alias",
+            source
+        ));
+    }
+    for (i, column) in columns.iter().enumerate() {
+        if columns[..i].iter().any(|other| other.name == column.name) {
+            return failed(format!(
+                "Column {} of {} is named more than once",
+                column.name, source
+            ));
+        }
+    }
+    for column in &mut columns {
+        column.name = format!("{}.{}", source, column.name);
+    }
+    Ok(columns)
+}
+
+/// The schema of a FROM list after its source at `position`
+///
+/// When the list has table functions, the columns of a table or view at
+/// the start are named `table.col`, as those of the functions are, so a
+/// name that several sources have must be qualified.
+fn from_schema(schema: Schema, from: &[TableReference], position: usize) -> Schema {
+    let has_functions = from
+        .iter()
+        .any(|source| matches!(source, TableReference::Function { .. }));
+    match &from[position] {
+        TableReference::Table(name) if has_functions => Schema::new(
+            schema
+                .columns
+                .into_iter()
+                .map(|mut col| {
+                    col.name = format!("{}.{}", name, col.name);
+                    col
+                })
+                .collect(),
+        ),
+        _ => schema,
+    }
+}
+
+/// Infer the data type and nullability of an expression's result
+fn infer_type(expr: &Expression, schema: &Schema) -> Result<(DataType, bool), ExecutionError> {
+    match expr {
+        Expression::Column(name) => {
+            let col = schema
+                .get_column(name)
+                .ok_or_else(|| ExecutionError::ColumnNotFound(name.clone()))?;
+            Ok((col.data_type.clone(), col.nullable))
+        }
+        Expression::Literal(value) => {
+            Ok((value.data_type().unwrap_or(DataType::Text), value.is_null()))
+        }
+        Expression::Case {
+            when_clauses,
+            else_result,
+            ..
+        } => {
+            let mut branches: Vec<&Expression> =
+                when_clauses.iter().map(|(_, then)| then).collect();
+            branches.extend(else_result.as_deref());
+            let (data_type, nullable) = infer_common_type(&branches, schema)?;
+            Ok((data_type, nullable || else_result.is_none()))
+        }
+        Expression::Coalesce(args) => {
+            let args: Vec<&Expression> = args.iter().collect();
+            let (data_type, _) = infer_common_type(&args, schema)?;
+            let mut nullable = true;
+            for arg in args {
+                nullable &= infer_type(arg, schema)?.1;
+            }
+            Ok((data_type, nullable))
+        }
+        Expression::NullIf(left, _) => Ok((infer_type(left, schema)?.0, true)),
+        Expression::BinaryOp { left, op, right } => {
+            let (left_type, left_nullable) = infer_type(left, schema)?;
+            let (right_type, right_nullable) = infer_type(right, schema)?;
+            let data_type = match (op, convert_arithmetic_operator(op)) {
+                (_, Some(op)) => op.result_type(&left_type, &right_type),
+                (BinaryOperator::JsonGet, None) => DataType::Json,
+                (BinaryOperator::Concat, None)
+                    if left_type == DataType::Blob && right_type == DataType::Blob =>
+                {
+                    DataType::Blob
+                }
+                (BinaryOperator::Concat, None) if matches!(left_type, DataType::Array(_)) => {
+                    left_type
+                }
+                (BinaryOperator::Concat, None) if matches!(right_type, DataType::Array(_)) => {
+                    right_type
+                }
+                (_, None) => DataType::Text,
+            };
+            // A JSON lookup is NULL when the member is missing
+            let nullable = matches!(op, BinaryOperator::JsonGet | BinaryOperator::JsonGetText);
+            Ok((data_type, nullable || left_nullable || right_nullable))
+        }
+        Expression::Negate(expr) => infer_type(expr, schema),
+        Expression::Cast {
+            expr,
+            data_type,
+            try_cast,
+        } => {
+            let (_, nullable) = infer_type(expr, schema)?;
+            Ok((data_type.clone(), nullable || *try_cast))
+        }
+        Expression::Array(items) => {
+            let items: Vec<&Expression> = items.iter().collect();
+            let (element, _) = infer_common_type(&items, schema)?;
+            Ok((DataType::Array(Box::new(element)), false))
+        }
+        Expression::Subscript { expr, .. } => match infer_type(expr, schema)?.0 {
+            DataType::Array(element) => Ok((*element, true)),
+            data_type => Err(ExecutionError::InvalidValue(format!(
+                "Cannot subscript {} value {}",
+                data_type, expr
+            ))),
+        },
+        Expression::Function { name, args } => {
+            let mut arg_types = Vec::new();
+            let mut nullable = false;
+            for arg in args {
+                let (data_type, arg_nullable) = infer_type(arg, schema)?;
+                arg_types.push(data_type);
+                nullable |= arg_nullable;
+            }
+            // Aggregates over no rows are NULL
+            nullable |= is_aggregate(expr);
+            Ok((functions::return_type(name, &arg_types)?, nullable))
+        }
+        // Conditions produce BOOLEAN truth values
+        Expression::IsNull { .. } => Ok((DataType::Boolean, false)),
+        _ => Ok((DataType::Boolean, true)),
+    }
+}
+
+/// Parse the SQL text of an expression stored in a schema
+fn parse_stored_expression(sql: &str) -> Result<Expression, ExecutionError> {
+    parse_expression_sql(sql).map_err(|e| {
+        ExecutionError::ExecutionFailed(format!("Invalid stored expression '{}': {}", sql, e))
+    })
+}
+
+/// Parse the stored query of a view
+fn parse_view_query(view: &ViewMetadata) -> Result<SelectStatement, ExecutionError> {
+    parse_select_sql(&view.query).map_err(|e| {
+        ExecutionError::ExecutionFailed(format!("Invalid query of view {}: {}", view.name, e))
+    })
+}
+
+/// The index and parsed expression of each generated column, or of each
+/// VIRTUAL one only
+fn generated_columns(
+    schema: &Schema,
+    only_virtual: bool,
+) -> Result<Vec<(usize, Expression)>, ExecutionError> {
+    let mut generated = Vec::new();
+    for (index, column) in schema.columns.iter().enumerate() {
+        if let Some(column_generated) = &column.generated
+            && !(only_virtual && column_generated.stored)
+        {
+            generated.push((index, parse_stored_expression(&column_generated.expr)?));
+        }
+    }
+    Ok(generated)
+}
+
+/// Check an expression for implicit conversions that the type mode rejects
+///
+/// In strict mode the operands of comparisons must be comparable types
+/// (see `DataType::comparable_with`), as must the alternative results of
+/// CASE and COALESCE; arithmetic may not be applied to TEXT, function
+/// arguments must have the types the function takes, and values
+/// concatenated with an array must convert to its type or element type,
+/// while other operands of `||` must be TEXT, or BLOB on both sides.
+/// NULL literals are accepted anywhere.
+fn check_types(expr: &Expression, schema: &Schema, mode: TypeMode) -> Result<(), ExecutionError> {
+    if mode == TypeMode::Lenient {
+        return Ok(());
+    }
+    for child in expr.children() {
+        check_types(child, schema, mode)?;
+    }
+
+    let operand_type = |expr: &Expression| match expr {
+        Expression::Literal(Value::Null) => Ok(None),
+        expr => infer_type(expr, schema).map(|(data_type, _)| Some(data_type)),
+    };
+    let check_comparison = |left: &Expression, right: &Expression| {
+        if let (Some(a), Some(b)) = (operand_type(left)?, operand_type(right)?)
+            && !a.comparable_with(&b, mode)
+        {
+            return Err(ExecutionError::TypeMismatch(format!(
+                "Cannot compare {} with {} without a CAST: {} and {}",
+                a, b, left, right
+            )));
+        }
+        Ok(())
+    };
+    let check_alternatives = |exprs: Vec<&Expression>| {
+        let mut first: Option<(DataType, &Expression)> = None;
+        for expr in exprs {
+            let Some(data_type) = operand_type(expr)? else {
+                continue;
+            };
+            match &first {
+                Some((common, other)) if !data_type.comparable_with(common, mode) => {
+                    return Err(ExecutionError::TypeMismatch(format!(
+                        "Cannot mix {} and {} results without a CAST: {} and {}",
+                        common, data_type, other, expr
+                    )));
+                }
+                Some(_) => {}
+                None => first = Some((data_type, expr)),
+            }
+        }
+        Ok(())
+    };
+
+    match expr {
+        Expression::Comparison { left, right, .. } | Expression::NullIf(left, right) => {
+            check_comparison(left, right)
+        }
+        Expression::Case {
+            operand,
+            when_clauses,
+            else_result,
+        } => {
+            if let Some(operand) = operand {
+                for (when, _) in when_clauses {
+                    check_comparison(operand, when)?;
+                }
+            }
+            check_alternatives(
+                when_clauses
+                    .iter()
+                    .map(|(_, then)| then)
+                    .chain(else_result.as_deref())
+                    .collect(),
+            )
+        }
+        Expression::Coalesce(args) => check_alternatives(args.iter().collect()),
+        Expression::Function { name, args } => {
+            let arg_types = args
+                .iter()
+                .map(operand_type)
+                .collect::<Result<Vec<_>, _>>()?;
+            functions::check_arg_types(name, &arg_types)
+        }
+        Expression::InList { expr, list, .. } => list
+            .iter()
+            .try_for_each(|item| check_comparison(expr, item)),
+        Expression::Between {
+            expr, low, high, ..
+        } => {
+            check_comparison(expr, low)?;
+            check_comparison(expr, high)
+        }
+        Expression::Quantified { left, right, .. } => {
+            if let (Some(a), Some(DataType::Array(b))) = (operand_type(left)?, operand_type(right)?)
+                && !a.comparable_with(&b, mode)
+            {
+                return Err(ExecutionError::TypeMismatch(format!(
+                    "Cannot compare {} with elements of {} without a CAST: {} and {}",
+                    a,
+                    DataType::Array(b),
+                    left,
+                    right
+                )));
+            }
+            Ok(())
+        }
+        Expression::BinaryOp { left, op, right } if convert_arithmetic_operator(op).is_some() => {
+            for operand in [left, right] {
+                if operand_type(operand)? == Some(DataType::Text) {
+                    return Err(ExecutionError::TypeMismatch(format!(
+                        "Cannot apply {} to TEXT value {} without a CAST",
+                        op, operand
+                    )));
+                }
+            }
+            Ok(())
+        }
+        Expression::BinaryOp {
+            left,
+            op: BinaryOperator::Concat,
+            right,
+        } => {
+            let (other, target) = match (operand_type(left)?, operand_type(right)?) {
+                (Some(a @ DataType::Array(_)), Some(b @ DataType::Array(_))) => (b, a),
+                (Some(DataType::Array(element)), Some(other))
+                | (Some(other), Some(DataType::Array(element))) => (other, *element),
+                (Some(DataType::Array(_)), None) | (None, Some(DataType::Array(_))) => {
+                    return Ok(());
+                }
+                (a, b) => {
+                    let target = if a == Some(DataType::Blob) || b == Some(DataType::Blob) {
+                        DataType::Blob
+                    } else {
+                        DataType::Text
+                    };
+                    for (operand, data_type) in [(left, a), (right, b)] {
+                        if let Some(data_type) = data_type
+                            && data_type != target
+                        {
+                            return Err(ExecutionError::TypeMismatch(format!(
+                                "Cannot concatenate {} value {} as {} without a CAST",
+                                data_type, operand, target
+                            )));
+                        }
+                    }
+                    return Ok(());
+                }
+            };
+            if !other.coerces_to(&target, mode) {
+                return Err(ExecutionError::TypeMismatch(format!(
+                    "Cannot concatenate {} with {} without a CAST: {} and {}",
+                    other, target, left, right
+                )));
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Check that the value of an expression can be stored in a column: its
+/// type must convert to the column's type without a CAST (see
+/// `DataType::coerces_to`), and the expression must pass `check_types`
+fn check_stored_type(
+    expr: &Expression,
+    schema: &Schema,
+    column_name: &str,
+    column_type: &DataType,
+    mode: TypeMode,
+) -> Result<(), ExecutionError> {
+    check_types(expr, schema, mode)?;
+    let (data_type, _) = infer_type(expr, schema)?;
+    if !matches!(expr, Expression::Literal(Value::Null)) && !data_type.coerces_to(column_type, mode)
+    {
+        return Err(ExecutionError::TypeMismatch(format!(
+            "Cannot store {} value {} in {} column {} without a CAST",
+            data_type, expr, column_type, column_name
+        )));
+    }
+    Ok(())
+}
+
+/// Check whether an expression is a call of an aggregate function
+fn is_aggregate(expr: &Expression) -> bool {
+    matches!(expr, Expression::Function { name, .. } if functions::is_aggregate(name))
+}
+
+/// Infer the type shared by alternative results (CASE branches, COALESCE args)
+///
+/// The type comes from the first alternative that is not a NULL literal; the
+/// result is nullable if any alternative is.
+fn infer_common_type(
+    exprs: &[&Expression],
+    schema: &Schema,
+) -> Result<(DataType, bool), ExecutionError> {
+    let mut data_type = None;
+    let mut nullable = false;
+
+    for expr in exprs {
+        let (expr_type, expr_nullable) = infer_type(expr, schema)?;
+        nullable |= expr_nullable;
+        if data_type.is_none() && !matches!(expr, Expression::Literal(Value::Null)) {
+            data_type = Some(expr_type);
+        }
+    }
+
+    Ok((data_type.unwrap_or(DataType::Text), nullable))
+}
+
+/// Convert a parser binary operator to the type-level arithmetic operator
+///
+/// Returns None for operators that are not arithmetic (`||`, `->`, `->>`).
+fn convert_arithmetic_operator(op: &BinaryOperator) -> Option<ArithmeticOperator> {
+    match op {
+        BinaryOperator::Plus => Some(ArithmeticOperator::Add),
+        BinaryOperator::Minus => Some(ArithmeticOperator::Subtract),
+        BinaryOperator::Multiply => Some(ArithmeticOperator::Multiply),
+        BinaryOperator::Divide => Some(ArithmeticOperator::Divide),
+        BinaryOperator::Modulo => Some(ArithmeticOperator::Modulo),
+        BinaryOperator::Concat | BinaryOperator::JsonGet | BinaryOperator::JsonGetText => None,
+    }
+}
+
+/// Convert a parser comparison operator to the type-level operator
+fn convert_operator(op: &Operator) -> TypeOperator {
+    match op {
+        Operator::Equals => TypeOperator::Eq,
+        Operator::NotEquals => TypeOperator::NotEq,
+        Operator::GreaterThan => TypeOperator::Gt,
+        Operator::LessThan => TypeOperator::Lt,
+        Operator::GreaterThanOrEqual => TypeOperator::GtEq,
+        Operator::LessThanOrEqual => TypeOperator::LtEq,
     }
 }
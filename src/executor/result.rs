@@ -0,0 +1,91 @@
+//! Results of executing a statement
+//!
+//! The executor never prints: it reports what a statement did as an
+//! [`ExecutionResult`], which the caller renders as it sees fit.
+
+use crate::types::{ResultSet, Schema};
+use std::fmt::{self, Display};
+use std::time::Duration;
+
+/// The kind of statement that was executed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementKind {
+    CreateTable,
+    CreateTableAs,
+    CreateSequence,
+    CreateView,
+    DropView,
+    Insert,
+    Select,
+}
+
+impl Display for StatementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            StatementKind::CreateTable => "CREATE TABLE",
+            StatementKind::CreateTableAs => "CREATE TABLE AS",
+            StatementKind::CreateSequence => "CREATE SEQUENCE",
+            StatementKind::CreateView => "CREATE VIEW",
+            StatementKind::DropView => "DROP VIEW",
+            StatementKind::Insert => "INSERT",
+            StatementKind::Select => "SELECT",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// What executing a statement did
+#[derive(Debug, Clone)]
+pub struct ExecutionResult {
+    /// Kind of the statement
+    pub kind: StatementKind,
+    /// Rows returned by SELECT or RETURNING; without columns otherwise
+    pub result_set: ResultSet,
+    /// Number of rows inserted, updated or copied by the statement
+    pub rows_affected: usize,
+    /// Identity (or INTEGER primary key) value of the last row inserted
+    pub last_insert_id: Option<i64>,
+    /// Time taken to execute the statement
+    pub elapsed: Duration,
+}
+
+impl ExecutionResult {
+    /// Create a result for a statement that returns no rows
+    pub fn new(kind: StatementKind) -> Self {
+        Self::with_rows(kind, ResultSet::empty(Schema::new(vec![])))
+    }
+
+    /// Create a result for a statement that returns the given rows
+    pub fn with_rows(kind: StatementKind, result_set: ResultSet) -> Self {
+        Self {
+            kind,
+            result_set,
+            rows_affected: 0,
+            last_insert_id: None,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    /// Set the number of rows affected
+    pub fn with_rows_affected(mut self, rows_affected: usize) -> Self {
+        self.rows_affected = rows_affected;
+        self
+    }
+
+    /// Set the id of the last row inserted
+    pub fn with_last_insert_id(mut self, last_insert_id: Option<i64>) -> Self {
+        self.last_insert_id = last_insert_id;
+        self
+    }
+
+    /// Set the time taken to execute the statement
+    pub fn with_elapsed(mut self, elapsed: Duration) -> Self {
+        self.elapsed = elapsed;
+        self
+    }
+
+    /// Whether the statement returns rows (a SELECT, or RETURNING)
+    pub fn has_rows(&self) -> bool {
+        !self.result_set.schema.columns.is_empty()
+    }
+}
@@ -0,0 +1,120 @@
+//! Built-in table-valued functions, used as row sources in FROM
+//!
+//! A table function produces zero or more rows with a fixed set of columns.
+//! When it follows another source in the FROM list it is called once per row
+//! of that source, so `FROM docs, JSON_EACH(tags)` unnests each document.
+
+use super::ExecutionError;
+use crate::types::{Column, DataType, Row, Value, json};
+
+/// Output columns of a table function, looked up by (case-insensitive) name,
+/// when called with arguments of the given types
+pub fn columns(name: &str, arg_types: &[DataType]) -> Result<Vec<Column>, ExecutionError> {
+    let columns = match name.to_uppercase().as_str() {
+        "UNNEST" => {
+            let element = match arg_types.first() {
+                Some(DataType::Array(element)) => (**element).clone(),
+                _ => DataType::Text,
+            };
+            vec![Column::new("unnest".to_string(), element, true)]
+        }
+        "JSON_EACH" => vec![
+            Column::new("key".to_string(), DataType::Text, true),
+            Column::new("value".to_string(), DataType::Json, false),
+            Column::new("type".to_string(), DataType::Text, false),
+        ],
+        _ => return Err(ExecutionError::FunctionNotFound(name.to_string())),
+    };
+    Ok(columns)
+}
+
+/// Call a table function with already evaluated arguments
+pub fn call(name: &str, args: Vec<Value>) -> Result<Vec<Row>, ExecutionError> {
+    match name.to_uppercase().as_str() {
+        "UNNEST" => unnest(args),
+        "JSON_EACH" => json_each(args),
+        _ => Err(ExecutionError::FunctionNotFound(name.to_string())),
+    }
+}
+
+/// `UNNEST(array)`: one row per element of the array, in order; NULL
+/// produces no rows
+fn unnest(args: Vec<Value>) -> Result<Vec<Row>, ExecutionError> {
+    match <[Value; 1]>::try_from(args) {
+        Ok([Value::Array(items)]) => {
+            Ok(items.into_iter().map(|item| Row::new(vec![item])).collect())
+        }
+        Ok([Value::Null]) => Ok(vec![]),
+        Ok([other]) => Err(ExecutionError::InvalidValue(format!(
+            "Invalid argument 1 to UNNEST: {}",
+            other
+        ))),
+        Err(args) => Err(ExecutionError::InvalidValue(format!(
+            "Wrong number of arguments to UNNEST: {}",
+            args.len()
+        ))),
+    }
+}
+
+/// `JSON_EACH(json [, path])`: one row per array element or object member,
+/// in document order
+///
+/// The key of an array element is its index from 0, as text, and that of an
+/// object member is its name. A scalar produces a single row with a NULL
+/// key, and NULL or a missing path produce none.
+fn json_each(args: Vec<Value>) -> Result<Vec<Row>, ExecutionError> {
+    if !(1..=2).contains(&args.len()) {
+        return Err(ExecutionError::InvalidValue(format!(
+            "Wrong number of arguments to JSON_EACH: {}",
+            args.len()
+        )));
+    }
+    if args.iter().any(Value::is_null) {
+        return Ok(vec![]);
+    }
+
+    let document = match args[0].as_type(&DataType::Json)? {
+        Value::Json(document) => document,
+        other => {
+            return Err(ExecutionError::InvalidValue(format!(
+                "Invalid argument 1 to JSON_EACH: {}",
+                other
+            )));
+        }
+    };
+    let target = match args.get(1) {
+        Some(path) => {
+            let path = match path.as_type(&DataType::Text)? {
+                Value::Text(path) => json::parse_path(&path)?,
+                other => {
+                    return Err(ExecutionError::InvalidValue(format!(
+                        "Invalid argument 2 to JSON_EACH: {}",
+                        other
+                    )));
+                }
+            };
+            match json::lookup(&document, &path) {
+                Some(target) => target.clone(),
+                None => return Ok(vec![]),
+            }
+        }
+        None => document,
+    };
+
+    let row = |key: Value, value: serde_json::Value| {
+        let type_name = Value::Text(json::type_name(&value).to_string());
+        Row::new(vec![key, Value::Json(value), type_name])
+    };
+    Ok(match target {
+        serde_json::Value::Array(items) => items
+            .into_iter()
+            .enumerate()
+            .map(|(index, item)| row(Value::Text(index.to_string()), item))
+            .collect(),
+        serde_json::Value::Object(members) => members
+            .into_iter()
+            .map(|(key, value)| row(Value::Text(key), value))
+            .collect(),
+        scalar => vec![row(Value::Null, scalar)],
+    })
+}
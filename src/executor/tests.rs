@@ -0,0 +1,1003 @@
+//! Behaviour tests of the executor, run as SQL against an empty database
+
+use super::*;
+use crate::parser::parse_sql;
+
+fn database() -> QueryExecutor {
+    QueryExecutor::new(Database::new())
+}
+
+fn run(executor: &QueryExecutor, sql: &str) -> Result<ExecutionResult, String> {
+    let statement = parse_sql(sql).map_err(|e| e.to_string())?;
+    executor.execute(statement).map_err(|e| e.to_string())
+}
+
+/// Execute statements that must succeed
+fn exec(executor: &QueryExecutor, statements: &[&str]) {
+    for sql in statements {
+        if let Err(error) = run(executor, sql) {
+            panic!("{} failed: {}", sql, error);
+        }
+    }
+}
+
+/// The rows a statement returns, each as its values separated by commas
+fn query(executor: &QueryExecutor, sql: &str) -> Vec<String> {
+    let result = run(executor, sql).unwrap_or_else(|e| panic!("{} failed: {}", sql, e));
+    result
+        .result_set
+        .rows
+        .iter()
+        .map(|row| {
+            row.values
+                .iter()
+                .map(|value| value.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        })
+        .collect()
+}
+
+/// The error of a statement that must fail
+fn error(executor: &QueryExecutor, sql: &str) -> String {
+    match run(executor, sql) {
+        Ok(_) => panic!("{} succeeded", sql),
+        Err(error) => error,
+    }
+}
+
+fn referential_tables() -> QueryExecutor {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE p (id INTEGER PRIMARY KEY, code INTEGER UNIQUE)",
+            "CREATE TABLE c (id INTEGER PRIMARY KEY, pid INTEGER REFERENCES p (id) \
+             ON DELETE CASCADE ON UPDATE CASCADE)",
+            "CREATE TABLE g (id INTEGER PRIMARY KEY, cid INTEGER REFERENCES c (id) \
+             ON DELETE CASCADE)",
+            "CREATE TABLE n (id INTEGER PRIMARY KEY, pcode INTEGER REFERENCES p (code) \
+             ON DELETE SET NULL ON UPDATE SET NULL, \
+             twice INTEGER GENERATED ALWAYS AS (pcode * 2) STORED)",
+            "CREATE TABLE d (id INTEGER PRIMARY KEY, pcode INTEGER DEFAULT 0 \
+             REFERENCES p (code) ON DELETE SET DEFAULT)",
+            "INSERT INTO p VALUES (0, 0), (1, 10), (2, 20)",
+            "INSERT INTO c VALUES (1, 1), (2, 2)",
+            "INSERT INTO g VALUES (1, 1), (2, 2)",
+            "INSERT INTO n (id, pcode) VALUES (1, 10), (2, 20)",
+            "INSERT INTO d VALUES (1, 10), (2, 20)",
+        ],
+    );
+    db
+}
+
+#[test]
+fn replace_cascades_delete_through_children() {
+    let db = referential_tables();
+    exec(&db, &["INSERT OR REPLACE INTO p VALUES (3, 20)"]);
+    assert_eq!(query(&db, "SELECT * FROM p"), ["0, 0", "1, 10", "3, 20"]);
+    assert_eq!(query(&db, "SELECT * FROM c"), ["1, 1"]);
+    assert_eq!(query(&db, "SELECT * FROM g"), ["1, 1"]);
+    // The replacing row keeps code 20, so rows referring to it stay
+    assert_eq!(query(&db, "SELECT * FROM n"), ["1, 10, 20", "2, 20, 40"]);
+    assert_eq!(query(&db, "SELECT * FROM d"), ["1, 10", "2, 20"]);
+}
+
+#[test]
+fn replace_sets_null_and_default() {
+    let db = referential_tables();
+    exec(&db, &["INSERT OR REPLACE INTO p VALUES (1, 11)"]);
+    assert_eq!(query(&db, "SELECT * FROM c"), ["1, 1", "2, 2"]);
+    assert_eq!(
+        query(&db, "SELECT * FROM n"),
+        ["1, NULL, NULL", "2, 20, 40"]
+    );
+    assert_eq!(query(&db, "SELECT * FROM d"), ["1, 0", "2, 20"]);
+}
+
+#[test]
+fn update_cascades_new_key() {
+    let db = referential_tables();
+    exec(
+        &db,
+        &["INSERT INTO p VALUES (2, 0) ON CONFLICT (id) DO UPDATE SET id = 5"],
+    );
+    assert_eq!(query(&db, "SELECT * FROM p"), ["0, 0", "1, 10", "5, 20"]);
+    assert_eq!(query(&db, "SELECT * FROM c"), ["1, 1", "2, 5"]);
+    assert_eq!(query(&db, "SELECT * FROM g"), ["1, 1", "2, 2"]);
+}
+
+#[test]
+fn self_reference_cascades_update() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, boss INTEGER REFERENCES t (id) \
+             ON UPDATE CASCADE)",
+            "INSERT INTO t VALUES (1, NULL), (2, 1), (3, 2)",
+            "INSERT INTO t VALUES (2, 2) ON CONFLICT (id) DO UPDATE SET id = 5",
+        ],
+    );
+    assert_eq!(query(&db, "SELECT * FROM t"), ["1, NULL", "5, 1", "3, 5"]);
+}
+
+#[test]
+fn rejected_action_leaves_every_table_unchanged() {
+    let db = referential_tables();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE r (id INTEGER PRIMARY KEY, pid INTEGER NOT NULL \
+             REFERENCES p (id) ON UPDATE SET NULL)",
+            "INSERT INTO r VALUES (1, 2)",
+        ],
+    );
+    // r cannot take NULL, after c has already been updated
+    let message = error(
+        &db,
+        "INSERT INTO p VALUES (2, 0) ON CONFLICT (id) DO UPDATE SET id = 5",
+    );
+    assert!(
+        message.contains("NOT NULL constraint failed: r.pid"),
+        "{}",
+        message
+    );
+    assert_eq!(query(&db, "SELECT * FROM p"), ["0, 0", "1, 10", "2, 20"]);
+    assert_eq!(query(&db, "SELECT * FROM c"), ["1, 1", "2, 2"]);
+
+    // NO ACTION rejects deleting a key that rows still refer to
+    exec(
+        &db,
+        &[
+            "CREATE TABLE k (id INTEGER PRIMARY KEY, pid INTEGER REFERENCES p (id))",
+            "INSERT INTO k VALUES (1, 1)",
+        ],
+    );
+    let message = error(&db, "INSERT OR REPLACE INTO p VALUES (4, 10)");
+    assert!(
+        message.contains("k(pid) still references key (1)"),
+        "{}",
+        message
+    );
+    assert_eq!(query(&db, "SELECT * FROM c"), ["1, 1", "2, 2"]);
+}
+
+fn stock() -> QueryExecutor {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE stock (id INTEGER PRIMARY KEY, sku TEXT UNIQUE, \
+             quantity INTEGER, updates INTEGER DEFAULT 0)",
+            "INSERT INTO stock VALUES (1, 'a', 5, 0), (2, 'b', 7, 0)",
+        ],
+    );
+    db
+}
+
+#[test]
+fn on_conflict_do_nothing_matches_any_or_given_key() {
+    let db = stock();
+    let result = run(
+        &db,
+        "INSERT INTO stock VALUES (3, 'a', 1, 0) ON CONFLICT DO NOTHING",
+    );
+    assert_eq!(result.unwrap().rows_affected, 0);
+    exec(
+        &db,
+        &["INSERT INTO stock VALUES (1, 'c', 1, 0) ON CONFLICT (id) DO NOTHING"],
+    );
+    // A conflict on a key other than the target is still an error
+    let message = error(
+        &db,
+        "INSERT INTO stock VALUES (3, 'a', 1, 0) ON CONFLICT (id) DO NOTHING",
+    );
+    assert!(message.contains("UNIQUE constraint failed"), "{}", message);
+    assert_eq!(
+        query(&db, "SELECT * FROM stock"),
+        ["1, 'a', 5, 0", "2, 'b', 7, 0"]
+    );
+}
+
+#[test]
+fn on_conflict_do_update_resolves_excluded_and_table_columns() {
+    let db = stock();
+    exec(
+        &db,
+        &[
+            "INSERT INTO stock (sku, quantity, id) VALUES ('a', 3, 9) ON CONFLICT (sku) \
+           DO UPDATE SET quantity = stock.quantity + EXCLUDED.quantity, \
+           updates = updates + 1",
+        ],
+    );
+    assert_eq!(
+        query(&db, "SELECT * FROM stock"),
+        ["1, 'a', 8, 1", "2, 'b', 7, 0"]
+    );
+}
+
+#[test]
+fn on_conflict_do_update_where_skips_rows() {
+    let db = stock();
+    let result = run(
+        &db,
+        "INSERT INTO stock VALUES (1, 'a', 3, 0), (2, 'b', 9, 0) ON CONFLICT (id) \
+         DO UPDATE SET quantity = excluded.quantity WHERE excluded.quantity > quantity",
+    );
+    assert_eq!(result.unwrap().rows_affected, 1);
+    assert_eq!(
+        query(&db, "SELECT * FROM stock"),
+        ["1, 'a', 5, 0", "2, 'b', 9, 0"]
+    );
+}
+
+#[test]
+fn on_conflict_cannot_change_a_row_twice() {
+    let db = stock();
+    let message = error(
+        &db,
+        "INSERT INTO stock VALUES (1, 'a', 1, 0), (1, 'a', 2, 0) ON CONFLICT (id) \
+         DO UPDATE SET quantity = excluded.quantity",
+    );
+    assert!(
+        message.contains("that the same INSERT wrote"),
+        "{}",
+        message
+    );
+    let message = error(
+        &db,
+        "INSERT INTO stock VALUES (3, 'c', 1, 0), (3, 'd', 2, 0) ON CONFLICT (id) \
+         DO UPDATE SET quantity = excluded.quantity",
+    );
+    assert!(
+        message.contains("that the same INSERT wrote"),
+        "{}",
+        message
+    );
+    // DO NOTHING skips the second row instead
+    exec(
+        &db,
+        &["INSERT INTO stock VALUES (3, 'c', 1, 0), (3, 'd', 2, 0) ON CONFLICT DO NOTHING"],
+    );
+    assert_eq!(
+        query(&db, "SELECT * FROM stock"),
+        ["1, 'a', 5, 0", "2, 'b', 7, 0", "3, 'c', 1, 0"]
+    );
+}
+
+#[test]
+fn or_replace_removes_every_conflicting_row() {
+    let db = stock();
+    let result = run(&db, "INSERT OR REPLACE INTO stock VALUES (1, 'b', 0, 0)").unwrap();
+    assert_eq!(result.rows_affected, 1);
+    assert_eq!(query(&db, "SELECT * FROM stock"), ["1, 'b', 0, 0"]);
+    exec(&db, &["INSERT OR IGNORE INTO stock VALUES (1, 'z', 4, 0)"]);
+    assert_eq!(query(&db, "SELECT * FROM stock"), ["1, 'b', 0, 0"]);
+}
+
+#[test]
+fn on_conflict_errors() {
+    let db = stock();
+    let cases = [
+        (
+            "INSERT INTO stock VALUES (1, 'a', 1, 0) ON CONFLICT (quantity) DO NOTHING",
+            "are not a PRIMARY KEY or UNIQUE key of stock",
+        ),
+        (
+            "INSERT INTO stock VALUES (1, 'a', 1, 0) ON CONFLICT DO UPDATE SET quantity = 1",
+            "needs the columns of a key",
+        ),
+        (
+            "INSERT INTO stock VALUES (1, 'a', 1, 0) ON CONFLICT (id) DO UPDATE SET nope = 1",
+            "Column not found: nope",
+        ),
+        (
+            "INSERT INTO stock VALUES (1, 'a', 1, 0) ON CONFLICT (id) \
+             DO UPDATE SET quantity = 1, quantity = 2",
+            "Column quantity is given more than once",
+        ),
+        (
+            "INSERT INTO stock VALUES (1, 'a', 1, 0) ON CONFLICT (id) \
+             DO UPDATE SET sku = 'b'",
+            "UNIQUE constraint failed",
+        ),
+    ];
+    for (sql, expected) in cases {
+        let message = error(&db, sql);
+        assert!(message.contains(expected), "{}: {}", sql, message);
+    }
+    assert_eq!(
+        query(&db, "SELECT * FROM stock"),
+        ["1, 'a', 5, 0", "2, 'b', 7, 0"]
+    );
+}
+
+#[test]
+fn failing_row_leaves_table_unchanged() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE t (id INTEGER PRIMARY KEY, v INTEGER CHECK (v > 0))",
+            "INSERT INTO t VALUES (1, 1)",
+            "CREATE TABLE source (id INTEGER, v INTEGER)",
+            "INSERT INTO source VALUES (2, 2), (3, 3), (4, 0)",
+        ],
+    );
+    for sql in [
+        "INSERT INTO t VALUES (2, 2), (3, 3), (4, 0)",
+        "INSERT INTO t VALUES (2, 2), (3, 3), (2, 4)",
+        "INSERT INTO t VALUES (2, 2), (3, 3), (1, 4)",
+        "INSERT INTO t SELECT id, v FROM source",
+    ] {
+        error(&db, sql);
+        assert_eq!(query(&db, "SELECT * FROM t"), ["1, 1"], "{}", sql);
+    }
+}
+
+#[test]
+fn failed_create_table_as_leaves_no_table() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE source (id INTEGER, v TEXT)",
+            "INSERT INTO source VALUES (1, '1'), (2, 'x')",
+        ],
+    );
+    // The column is INTEGER, as the first branch, and the second row's 'x'
+    // does not fit it
+    let message = error(
+        &db,
+        "CREATE TABLE copy AS SELECT id, CASE WHEN id = 1 THEN 1 ELSE v END AS c FROM source",
+    );
+    assert!(
+        message.contains("does not match column type INTEGER"),
+        "{}",
+        message
+    );
+    assert!(!db.storage.table_exists("copy").unwrap());
+    exec(&db, &["CREATE TABLE copy AS SELECT id, v FROM source"]);
+    assert_eq!(query(&db, "SELECT * FROM copy"), ["1, '1'", "2, 'x'"]);
+}
+
+fn strict_table() -> QueryExecutor {
+    let db = database();
+    db.storage.set_type_mode(TypeMode::Strict).unwrap();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE t (n INTEGER, s TEXT, b BLOB, a INTEGER[])",
+            "INSERT INTO t VALUES (1, 'x', X'01', ARRAY[1])",
+        ],
+    );
+    db
+}
+
+#[test]
+fn strict_mode_rejects_implicit_conversions() {
+    let db = strict_table();
+    let cases = [
+        (
+            "SELECT n || s FROM t",
+            "Cannot concatenate INTEGER value n as TEXT",
+        ),
+        (
+            "SELECT s || n FROM t",
+            "Cannot concatenate INTEGER value n as TEXT",
+        ),
+        (
+            "SELECT b || s FROM t",
+            "Cannot concatenate TEXT value s as BLOB",
+        ),
+        (
+            "SELECT a || s FROM t",
+            "Cannot concatenate TEXT with INTEGER",
+        ),
+        ("SELECT LENGTH(n) FROM t", "Argument 1 to LENGTH must be"),
+        (
+            "SELECT n FROM t WHERE n = s",
+            "Cannot compare INTEGER with TEXT",
+        ),
+        ("SELECT s + 1 FROM t", "Cannot apply + to TEXT value s"),
+        (
+            "SELECT CASE WHEN n = 1 THEN n ELSE s END FROM t",
+            "Cannot mix INTEGER and TEXT results",
+        ),
+        (
+            "INSERT INTO t (n) VALUES ('5')",
+            "Cannot store TEXT value '5' in INTEGER column n",
+        ),
+    ];
+    for (sql, expected) in cases {
+        let message = error(&db, sql);
+        assert!(message.contains(expected), "{}: {}", sql, message);
+    }
+    assert_eq!(query(&db, "SELECT * FROM t").len(), 1);
+}
+
+#[test]
+fn strict_mode_accepts_matching_types() {
+    let db = strict_table();
+    assert_eq!(
+        query(
+            &db,
+            "SELECT s || s, s || NULL, b || b, a || n, CAST(n AS TEXT) || s FROM t"
+        ),
+        ["'xx', NULL, X'0101', ARRAY[1, 1], '1x'"]
+    );
+    db.storage.set_type_mode(TypeMode::Lenient).unwrap();
+    assert_eq!(query(&db, "SELECT n || s FROM t"), ["'1x'"]);
+}
+
+fn accounts() -> QueryExecutor {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE acc (id INTEGER PRIMARY KEY, balance INTEGER, note TEXT DEFAULT 'n')",
+            "INSERT INTO acc VALUES (1, 500, 'a'), (2, 5, 'b')",
+            "CREATE VIEW rich (num, bal) AS SELECT id, balance FROM acc WHERE balance > 100",
+            "CREATE VIEW richer AS SELECT num, bal FROM rich WHERE bal < 1000",
+        ],
+    );
+    db
+}
+
+#[test]
+fn views_show_current_rows() {
+    let db = accounts();
+    assert_eq!(query(&db, "SELECT * FROM rich"), ["1, 500"]);
+    exec(&db, &["INSERT INTO acc VALUES (3, 5000, 'c')"]);
+    assert_eq!(query(&db, "SELECT * FROM rich"), ["1, 500", "3, 5000"]);
+    assert_eq!(query(&db, "SELECT num FROM richer"), ["1"]);
+}
+
+#[test]
+fn create_view_does_not_run_the_query() {
+    let db = accounts();
+    exec(
+        &db,
+        &[
+            "CREATE SEQUENCE s",
+            "CREATE VIEW numbered AS SELECT id, NEXTVAL('s') AS n FROM acc",
+        ],
+    );
+    assert_eq!(
+        query(&db, "SELECT NEXTVAL('s') FROM acc WHERE id = 1"),
+        ["1"]
+    );
+    let message = error(&db, "CREATE VIEW bad AS SELECT id FROM acc WHERE nope = 1");
+    assert!(message.contains("Column not found: nope"), "{}", message);
+    let message = error(&db, "CREATE VIEW bad AS SELECT id FROM acc ORDER BY nope");
+    assert!(message.contains("Column not found: nope"), "{}", message);
+}
+
+#[test]
+fn views_cannot_refer_to_themselves() {
+    let db = accounts();
+    let message = error(&db, "CREATE VIEW loop AS SELECT id FROM loop");
+    assert!(
+        message.contains("View loop cannot refer to itself"),
+        "{}",
+        message
+    );
+    // Nor through another view
+    let message = error(
+        &db,
+        "CREATE OR REPLACE VIEW rich (num, bal) AS SELECT num, bal FROM richer",
+    );
+    assert!(
+        message.contains("View rich cannot refer to itself"),
+        "{}",
+        message
+    );
+}
+
+#[test]
+fn views_with_dependents_are_kept() {
+    let db = accounts();
+    let message = error(&db, "DROP VIEW rich");
+    assert!(message.contains("view richer depends on it"), "{}", message);
+
+    // Dropping, renaming or retyping a column that richer reads is rejected
+    for sql in [
+        "CREATE OR REPLACE VIEW rich (num) AS SELECT id FROM acc",
+        "CREATE OR REPLACE VIEW rich (num, amount) AS SELECT id, balance FROM acc",
+        "CREATE OR REPLACE VIEW rich (num, bal) AS SELECT id, note FROM acc",
+    ] {
+        let message = error(&db, sql);
+        assert!(message.contains("Cannot replace view rich"), "{}", message);
+        assert_eq!(query(&db, "SELECT * FROM richer"), ["1, 500"], "{}", sql);
+    }
+
+    // Adding a column is allowed
+    exec(
+        &db,
+        &["CREATE OR REPLACE VIEW rich (num, bal, note) AS \
+           SELECT id, balance, note FROM acc WHERE balance > 1"],
+    );
+    assert_eq!(query(&db, "SELECT * FROM richer"), ["1, 500", "2, 5"]);
+    exec(&db, &["DROP VIEW richer", "DROP VIEW rich"]);
+    let message = error(&db, "DROP VIEW rich");
+    assert!(message.contains("View not found: rich"), "{}", message);
+    exec(&db, &["DROP VIEW IF EXISTS rich"]);
+}
+
+#[test]
+fn insert_through_views() {
+    let db = accounts();
+    exec(
+        &db,
+        &[
+            "INSERT INTO rich VALUES (3, 300)",
+            "INSERT INTO richer (bal, num) VALUES (400, 4)",
+        ],
+    );
+    assert_eq!(
+        query(&db, "SELECT * FROM acc WHERE id > 2"),
+        ["3, 300, 'n'", "4, 400, 'n'"]
+    );
+
+    // A row must show in every view it goes through
+    for (sql, clause) in [
+        (
+            "INSERT INTO rich VALUES (5, 50)",
+            "view rich (balance > 100)",
+        ),
+        (
+            "INSERT INTO rich (num) VALUES (5)",
+            "view rich (balance > 100)",
+        ),
+        (
+            "INSERT INTO richer VALUES (5, 5000)",
+            "view richer (bal < 1000)",
+        ),
+        (
+            "INSERT INTO richer VALUES (5, 50)",
+            "view rich (balance > 100)",
+        ),
+    ] {
+        let message = error(&db, sql);
+        assert!(message.contains(clause), "{}: {}", sql, message);
+    }
+    assert_eq!(query(&db, "SELECT id FROM acc"), ["1", "2", "3", "4"]);
+
+    exec(
+        &db,
+        &["CREATE VIEW doubled AS SELECT id, balance * 2 AS twice FROM acc"],
+    );
+    let message = error(&db, "INSERT INTO doubled VALUES (6, 2)");
+    assert!(
+        message.contains("balance * 2 is not a column of acc"),
+        "{}",
+        message
+    );
+    let message = error(&db, "INSERT INTO rich VALUES (6, 600) RETURNING num");
+    assert!(message.contains("not supported on views"), "{}", message);
+}
+
+fn with_nulls() -> QueryExecutor {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE t (id INTEGER, v INTEGER)",
+            "INSERT INTO t VALUES (1, 1), (2, NULL), (3, 3)",
+        ],
+    );
+    db
+}
+
+#[test]
+fn three_valued_logic() {
+    let db = with_nulls();
+    assert_eq!(
+        query(
+            &db,
+            "SELECT id, v IN (1, NULL), v NOT IN (2, NULL), NOT (v > 1), \
+             v = 1 OR v IS NULL, v > 1 AND NULL FROM t"
+        ),
+        [
+            "1, TRUE, NULL, TRUE, TRUE, FALSE",
+            "2, NULL, NULL, NULL, TRUE, NULL",
+            "3, NULL, NULL, FALSE, FALSE, NULL",
+        ]
+    );
+}
+
+#[test]
+fn where_keeps_only_true_rows() {
+    let db = with_nulls();
+    assert_eq!(query(&db, "SELECT id FROM t WHERE v IN (1, NULL)"), ["1"]);
+    // NOT IN with a NULL in the list is never TRUE
+    assert!(query(&db, "SELECT id FROM t WHERE v NOT IN (1, NULL)").is_empty());
+    assert_eq!(query(&db, "SELECT id FROM t WHERE v NOT IN (1, 2)"), ["3"]);
+    assert_eq!(query(&db, "SELECT id FROM t WHERE NOT (v = 1)"), ["3"]);
+    assert_eq!(
+        query(&db, "SELECT id FROM t WHERE NOT (v = 1) OR v IS NULL"),
+        ["2", "3"]
+    );
+}
+
+#[test]
+fn any_and_all_with_null_elements() {
+    let db = with_nulls();
+    assert_eq!(
+        query(
+            &db,
+            "SELECT id, v = ANY(ARRAY[1, NULL]), v = ALL(ARRAY[1, NULL]), \
+             v <> ALL(ARRAY[2, NULL]), v > ANY(ARRAY[0, NULL]), \
+             v = ALL(ARRAY[]::INTEGER[]) FROM t"
+        ),
+        [
+            "1, TRUE, NULL, NULL, TRUE, TRUE",
+            "2, NULL, NULL, NULL, NULL, TRUE",
+            "3, NULL, FALSE, NULL, TRUE, TRUE",
+        ]
+    );
+    assert_eq!(
+        query(&db, "SELECT id FROM t WHERE v = ANY(ARRAY[1, NULL])"),
+        ["1"]
+    );
+}
+
+#[test]
+fn composite_keys_with_nulls() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE k (a INTEGER, b INTEGER, c INTEGER, PRIMARY KEY (a, b), UNIQUE (b, c))",
+            // Keys with a NULL are never duplicates
+            "INSERT INTO k VALUES (1, 1, NULL), (1, 2, NULL), (2, 1, NULL), (5, 1, NULL)",
+        ],
+    );
+    let cases = [
+        (
+            "INSERT INTO k VALUES (1, 1, 5)",
+            "PRIMARY KEY constraint failed: k(a, b) already has key (1, 1)",
+        ),
+        // Primary key columns are NOT NULL
+        (
+            "INSERT INTO k VALUES (1, NULL, 5)",
+            "NOT NULL constraint failed: k.b",
+        ),
+        (
+            "INSERT INTO k VALUES (3, 1, 7), (4, 1, 7)",
+            "UNIQUE constraint failed: k(b, c) already has key (1, 7)",
+        ),
+    ];
+    for (sql, expected) in cases {
+        let message = error(&db, sql);
+        assert!(message.contains(expected), "{}: {}", sql, message);
+    }
+    assert_eq!(query(&db, "SELECT * FROM k").len(), 4);
+}
+
+#[test]
+fn defaults_and_generated_columns() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE g (id INTEGER PRIMARY KEY, price INTEGER DEFAULT 10, \
+             qty INTEGER DEFAULT 2, \
+             total INTEGER GENERATED ALWAYS AS (price * qty) STORED, \
+             label TEXT GENERATED ALWAYS AS ('#' || CAST(id AS TEXT)) VIRTUAL)",
+            "INSERT INTO g (id) VALUES (1)",
+            "INSERT INTO g (id, price, qty) VALUES (2, 3, DEFAULT)",
+            "INSERT INTO g VALUES (3, 1, 1, DEFAULT, DEFAULT)",
+        ],
+    );
+    assert_eq!(
+        query(&db, "SELECT * FROM g"),
+        ["1, 10, 2, 20, '#1'", "2, 3, 2, 6, '#2'", "3, 1, 1, 1, '#3'"]
+    );
+    for (sql, column) in [
+        ("INSERT INTO g VALUES (4, 1, 1, 5, DEFAULT)", "total"),
+        ("INSERT INTO g (id, label) VALUES (4, 'x')", "label"),
+    ] {
+        let message = error(&db, sql);
+        assert!(
+            message.contains(&format!(
+                "Cannot insert a value into generated column {}",
+                column
+            )),
+            "{}: {}",
+            sql,
+            message
+        );
+    }
+}
+
+#[test]
+fn named_check_failures() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE ch (id INTEGER, v INTEGER CHECK (v > 0), w INTEGER, \
+           CONSTRAINT w_small CHECK (w < 10), CHECK (v < w))",
+        ],
+    );
+    let cases = [
+        ("INSERT INTO ch VALUES (1, 0, 5)", "ch_v_check (v > 0)"),
+        ("INSERT INTO ch VALUES (1, 1, 50)", "w_small (w < 10)"),
+        ("INSERT INTO ch VALUES (1, 5, 2)", "ch_check (v < w)"),
+    ];
+    for (sql, expected) in cases {
+        let message = error(&db, sql);
+        assert!(
+            message.contains(&format!("CHECK constraint failed: {}", expected)),
+            "{}: {}",
+            sql,
+            message
+        );
+    }
+    // UNKNOWN does not fail a check
+    exec(
+        &db,
+        &[
+            "INSERT INTO ch VALUES (1, NULL, NULL)",
+            "INSERT INTO ch VALUES (2, 1, 2)",
+        ],
+    );
+    assert_eq!(query(&db, "SELECT * FROM ch"), ["1, NULL, NULL", "2, 1, 2"]);
+}
+
+/// The single value a query over a one-row table returns
+fn value(executor: &QueryExecutor, expr: &str) -> String {
+    let rows = query(executor, &format!("SELECT {} FROM one", expr));
+    assert_eq!(rows.len(), 1, "{}", expr);
+    rows[0].clone()
+}
+
+fn one_row() -> QueryExecutor {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE one (id INTEGER)",
+            "INSERT INTO one VALUES (1)",
+        ],
+    );
+    db
+}
+
+#[test]
+fn nextval_currval_and_setval() {
+    let db = one_row();
+    exec(&db, &["CREATE SEQUENCE s START 5 INCREMENT 5 MAXVALUE 15"]);
+    let message = error(&db, "SELECT CURRVAL('s') FROM one");
+    assert!(
+        message.contains("CURRVAL of s is not yet defined"),
+        "{}",
+        message
+    );
+    assert_eq!(value(&db, "NEXTVAL('s'), CURRVAL('s')"), "5, 5");
+    assert_eq!(value(&db, "NEXTVAL('s')"), "10");
+    assert_eq!(value(&db, "NEXTVAL('s')"), "15");
+    let message = error(&db, "SELECT NEXTVAL('s') FROM one");
+    assert!(
+        message.contains("s reached its maximum value (15)"),
+        "{}",
+        message
+    );
+
+    assert_eq!(value(&db, "SETVAL('s', 7)"), "7");
+    assert_eq!(value(&db, "NEXTVAL('s')"), "12");
+    assert_eq!(value(&db, "SETVAL('s', 7, FALSE)"), "7");
+    assert_eq!(value(&db, "NEXTVAL('s')"), "7");
+    let message = error(&db, "SELECT SETVAL('s', 100) FROM one");
+    assert!(
+        message.contains("out of bounds for s (1..15)"),
+        "{}",
+        message
+    );
+
+    exec(&db, &["CREATE SEQUENCE c START 1 MAXVALUE 2 CYCLE"]);
+    let values: Vec<String> = (0..3).map(|_| value(&db, "NEXTVAL('c')")).collect();
+    assert_eq!(values, ["1", "2", "1"]);
+}
+
+#[test]
+fn identity_skips_past_explicit_values() {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE i (id INTEGER GENERATED BY DEFAULT AS IDENTITY PRIMARY KEY, v TEXT)",
+            "INSERT INTO i (v) VALUES ('a')",
+            "INSERT INTO i VALUES (5, 'b')",
+        ],
+    );
+    let result = run(&db, "INSERT INTO i (v) VALUES ('c')").unwrap();
+    assert_eq!(result.last_insert_id, Some(6));
+    assert_eq!(
+        query(&db, "SELECT * FROM i"),
+        ["1, 'a'", "5, 'b'", "6, 'c'"]
+    );
+
+    exec(
+        &db,
+        &[
+            "CREATE TABLE always (id INTEGER GENERATED ALWAYS AS IDENTITY, v TEXT)",
+            "CREATE TABLE serial (id SERIAL, v TEXT)",
+            "INSERT INTO serial (v) VALUES ('a'), ('b')",
+        ],
+    );
+    let message = error(&db, "INSERT INTO always VALUES (5, 'b')");
+    assert!(
+        message.contains("Cannot insert a value into identity column id (GENERATED ALWAYS)"),
+        "{}",
+        message
+    );
+    assert_eq!(query(&db, "SELECT * FROM serial"), ["1, 'a'", "2, 'b'"]);
+}
+
+#[test]
+fn returning_stored_rows() {
+    let db = database();
+    exec(
+        &db,
+        &["CREATE TABLE st (id INTEGER PRIMARY KEY, sku TEXT UNIQUE, q INTEGER)"],
+    );
+    assert_eq!(
+        query(
+            &db,
+            "INSERT INTO st VALUES (1, 'a', 1), (2, 'b', 2) RETURNING id, q * 10 AS ten"
+        ),
+        ["1, 10", "2, 20"]
+    );
+    // Updated rows are returned with their new values
+    assert_eq!(
+        query(
+            &db,
+            "INSERT INTO st VALUES (1, 'a', 5), (3, 'c', 3) ON CONFLICT (id) \
+             DO UPDATE SET q = st.q + excluded.q RETURNING *"
+        ),
+        ["1, 'a', 6", "3, 'c', 3"]
+    );
+    // Skipped rows are not
+    assert_eq!(
+        query(
+            &db,
+            "INSERT INTO st VALUES (2, 'b', 9), (4, 'd', 4) ON CONFLICT DO NOTHING RETURNING id"
+        ),
+        ["4"]
+    );
+    let message = error(&db, "INSERT INTO st VALUES (5, 'e', 5) RETURNING COUNT(*)");
+    assert!(
+        message.contains("Aggregate functions cannot be used in RETURNING"),
+        "{}",
+        message
+    );
+    assert_eq!(query(&db, "SELECT id FROM st"), ["1", "2", "3", "4"]);
+}
+
+fn documents() -> QueryExecutor {
+    let db = database();
+    exec(
+        &db,
+        &[
+            "CREATE TABLE docs (id INTEGER PRIMARY KEY, value TEXT, doc JSON)",
+            "INSERT INTO docs VALUES (1, 'a', '{\"x\": 1, \"y\": [2, 3]}'), (2, 'b', '[4]')",
+        ],
+    );
+    db
+}
+
+/// The names of the columns a query returns
+fn column_names(executor: &QueryExecutor, sql: &str) -> Vec<String> {
+    let result = run(executor, sql).unwrap_or_else(|e| panic!("{} failed: {}", sql, e));
+    let columns = result.result_set.schema.columns;
+    columns.into_iter().map(|column| column.name).collect()
+}
+
+#[test]
+fn table_function_columns_clash_with_table_columns() {
+    let db = documents();
+    for sql in [
+        "SELECT value FROM docs, JSON_EACH(doc)",
+        "SELECT id FROM docs, JSON_EACH(doc) WHERE value = 'a'",
+        "SELECT id FROM docs, JSON_EACH(doc) ORDER BY value",
+        "SELECT id FROM docs, JSON_EACH(doc) AS a, JSON_EACH(doc) AS b WHERE a.key = key",
+        "CREATE VIEW both_values AS SELECT value FROM docs, JSON_EACH(doc)",
+    ] {
+        let message = error(&db, sql);
+        assert!(
+            message.contains("Column value is ambiguous")
+                || message.contains("Column key is ambiguous"),
+            "{}: {}",
+            sql,
+            message
+        );
+    }
+    assert_eq!(
+        query(
+            &db,
+            "SELECT docs.value, json_each.value FROM docs, JSON_EACH(doc) ORDER BY id, key"
+        ),
+        ["'a', JSON '1'", "'a', JSON '[2,3]'", "'b', JSON '4'"]
+    );
+    // Names only one source has need no qualifier
+    assert_eq!(
+        query(
+            &db,
+            "SELECT id, key, type FROM docs, JSON_EACH(doc) WHERE id = 1 ORDER BY key"
+        ),
+        ["1, 'x', 'integer'", "1, 'y', 'array'"]
+    );
+    assert_eq!(
+        column_names(&db, "SELECT * FROM docs, JSON_EACH(doc)"),
+        ["id", "value", "doc", "key", "value", "type"]
+    );
+    assert_eq!(
+        column_names(
+            &db,
+            "SELECT docs.id, json_each.key FROM docs, JSON_EACH(doc)"
+        ),
+        ["id", "key"]
+    );
+}
+
+#[test]
+fn table_function_aliases() {
+    let db = documents();
+    assert_eq!(
+        column_names(&db, "SELECT * FROM docs, JSON_EACH(doc) AS j(k, v)"),
+        ["id", "value", "doc", "k", "v", "type"]
+    );
+    assert_eq!(
+        query(
+            &db,
+            "SELECT id, v FROM docs, JSON_EACH(doc) j(k, v) WHERE j.k = 'x'"
+        ),
+        ["1, JSON '1'"]
+    );
+    assert_eq!(
+        query(
+            &db,
+            "SELECT a.key, b.key, b.value FROM docs, JSON_EACH(doc, '$.y') AS a, \
+             JSON_EACH(a.value) AS b"
+        ),
+        ["'0', NULL, JSON '2'", "'1', NULL, JSON '3'"]
+    );
+    // A single column is named after the alias
+    assert_eq!(
+        query(&db, "SELECT n FROM UNNEST(ARRAY[3, 1, 2]) AS n ORDER BY n"),
+        ["1", "2", "3"]
+    );
+    assert_eq!(
+        column_names(&db, "SELECT * FROM UNNEST(ARRAY[1])"),
+        ["unnest"]
+    );
+    assert_eq!(
+        column_names(&db, "SELECT * FROM UNNEST(ARRAY[1]) AS u(n)"),
+        ["n"]
+    );
+
+    for (sql, expected) in [
+        (
+            "SELECT * FROM UNNEST(ARRAY[1]) AS u(a, b)",
+            "UNNEST has 1 column(s) but 2 column aliases were given",
+        ),
+        (
+            "SELECT * FROM docs, JSON_EACH(doc), JSON_EACH(doc)",
+            "json_each is used more than once in FROM",
+        ),
+        (
+            "SELECT * FROM docs, JSON_EACH(doc) AS docs",
+            "docs is used more than once in FROM",
+        ),
+        (
+            "SELECT * FROM docs, JSON_EACH(doc) AS j(a, a)",
+            "Column a of j is named more than once",
+        ),
+    ] {
+        let message = error(&db, sql);
+        assert!(message.contains(expected), "{}: {}", sql, message);
+    }
+}
@@ -4,11 +4,11 @@ mod storage;
 mod types;
 
 use anyhow::{Context, Result};
-use executor::QueryExecutor;
+use executor::{ExecutionResult, QueryExecutor, StatementKind};
 use parser::parse_sql;
 use std::io::{self, BufRead, Write};
 use storage::Database;
-use types::{Column, DataType, Schema};
+use types::{Column, DataType, ForeignKey, KeyConstraint, Schema, TypeMode};
 
 /// Print the welcome message and usage instructions
 fn print_welcome() {
@@ -20,7 +20,9 @@ fn print_welcome() {
     println!("Special commands:");
     println!("  .help - Display this help message");
     println!("  .exit, .quit - Exit the program");
-    println!("  .tables - Show all tables");
+    println!("  .tables - Show all tables and views");
+    println!("  .typemode [strict|lenient] - Show or set how types are mixed");
+    println!("  .timer [on|off] - Show or set whether statements are timed");
     println!("Examples:");
     println!("  CREATE TABLE users (id INTEGER, name TEXT);");
     println!("  INSERT INTO users VALUES (1, 'Alice');");
@@ -29,7 +31,7 @@ fn print_welcome() {
 }
 
 /// Process a special command (starting with .)
-fn process_special_command(cmd: &str, executor: &QueryExecutor) -> Result<bool> {
+fn process_special_command(cmd: &str, executor: &QueryExecutor, timer: &mut bool) -> Result<bool> {
     match cmd.trim().to_lowercase().as_str() {
         ".exit" | ".quit" => {
             println!("Exiting LangDB. Goodbye!");
@@ -43,16 +45,54 @@ fn process_special_command(cmd: &str, executor: &QueryExecutor) -> Result<bool>
                 .get_storage()
                 .get_table_names()
                 .context("Failed to get table list")?;
+            let views = executor
+                .get_storage()
+                .get_views()
+                .context("Failed to get view list")?;
 
-            if table_names.is_empty() {
+            if table_names.is_empty() && views.is_empty() {
                 println!("No tables defined");
             } else {
                 println!("Tables:");
                 for name in table_names {
                     println!("  {}", name);
                 }
+                for view in views {
+                    println!("  {} (view)", view.name);
+                }
             }
         }
+        ".typemode" => {
+            let mode = executor
+                .get_storage()
+                .type_mode()
+                .context("Failed to get type mode")?;
+            println!("Type mode: {}", mode);
+        }
+        command if command.starts_with(".typemode ") => {
+            let argument = command.trim_start_matches(".typemode");
+            match argument.parse::<TypeMode>() {
+                Ok(mode) => {
+                    executor
+                        .get_storage()
+                        .set_type_mode(mode)
+                        .context("Failed to set type mode")?;
+                    println!("Type mode: {}", mode);
+                }
+                Err(_) => println!("Unknown type mode: {}", argument.trim()),
+            }
+        }
+        ".timer" => {
+            println!("Timer: {}", if *timer { "on" } else { "off" });
+        }
+        ".timer on" => {
+            *timer = true;
+            println!("Timer: on");
+        }
+        ".timer off" => {
+            *timer = false;
+            println!("Timer: off");
+        }
         _ => {
             println!("Unknown command: {}", cmd);
             println!("Type .help for usage information");
@@ -62,11 +102,15 @@ fn process_special_command(cmd: &str, executor: &QueryExecutor) -> Result<bool>
 }
 
 /// Create a schema from string column definitions
+///
+/// Each definition is a name and type, optionally followed by NOT NULL or
+/// PRIMARY KEY; columns are nullable otherwise.
 fn create_schema_from_strs(column_defs: Vec<&str>) -> Result<Schema> {
     let mut columns = Vec::new();
+    let mut keys = Vec::new();
 
     for def in column_defs {
-        let parts: Vec<&str> = def.trim().split_whitespace().collect();
+        let parts: Vec<&str> = def.split_whitespace().collect();
         if parts.len() < 2 {
             return Err(anyhow::anyhow!("Invalid column definition: {}", def));
         }
@@ -78,11 +122,33 @@ fn create_schema_from_strs(column_defs: Vec<&str>) -> Result<Schema> {
             _ => return Err(anyhow::anyhow!("Unsupported data type: {}", parts[1])),
         };
 
-        let nullable = parts.len() > 2 && parts[2].to_uppercase() == "NULL";
-        columns.push(Column::new(name, data_type, nullable));
+        let constraints = parts[2..].join(" ").to_uppercase();
+        let primary = match constraints.as_str() {
+            "" | "NOT NULL" => false,
+            "PRIMARY KEY" => true,
+            _ => return Err(anyhow::anyhow!("Invalid column definition: {}", def)),
+        };
+        if primary {
+            keys.push(KeyConstraint {
+                columns: vec![name.clone()],
+                primary,
+            });
+        }
+        columns.push(Column::new(name, data_type, constraints.is_empty()));
     }
 
-    Ok(Schema::new(columns))
+    Ok(Schema::new(columns).with_keys(keys))
+}
+
+/// A foreign key from a column to a table's `id` column
+fn references(column: &str, table: &str) -> ForeignKey {
+    ForeignKey {
+        columns: vec![column.to_string()],
+        table: table.to_string(),
+        referenced: vec!["id".to_string()],
+        on_delete: Default::default(),
+        on_update: Default::default(),
+    }
 }
 
 /// Run the REPL (Read-Eval-Print Loop)
@@ -91,22 +157,29 @@ fn run_repl() -> Result<()> {
 
     // Create initial tables if they don't exist
     if !storage.table_exists("users")? {
-        let schema = create_schema_from_strs(vec!["id INTEGER", "name TEXT"])?;
+        let schema = create_schema_from_strs(vec!["id INTEGER PRIMARY KEY", "name TEXT"])?;
         storage
             .create_table("users".to_string(), schema)
             .context("Failed to create users table")?;
     }
 
     if !storage.table_exists("products")? {
-        let schema = create_schema_from_strs(vec!["id INTEGER", "name TEXT"])?;
+        let schema = create_schema_from_strs(vec!["id INTEGER PRIMARY KEY", "name TEXT"])?;
         storage
             .create_table("products".to_string(), schema)
             .context("Failed to create products table")?;
     }
 
     if !storage.table_exists("orders")? {
-        let schema =
-            create_schema_from_strs(vec!["id INTEGER", "user_id INTEGER", "product_id INTEGER"])?;
+        let schema = create_schema_from_strs(vec![
+            "id INTEGER PRIMARY KEY",
+            "user_id INTEGER",
+            "product_id INTEGER",
+        ])?
+        .with_foreign_keys(vec![
+            references("user_id", "users"),
+            references("product_id", "products"),
+        ]);
         storage
             .create_table("orders".to_string(), schema)
             .context("Failed to create orders table")?;
@@ -116,6 +189,7 @@ fn run_repl() -> Result<()> {
     print_welcome();
 
     let mut input_buffer = String::new();
+    let mut timer = false;
     let stdin = io::stdin();
     let mut handle = stdin.lock();
 
@@ -139,7 +213,7 @@ fn run_repl() -> Result<()> {
         let line = line.trim();
 
         if line.starts_with(".") {
-            if process_special_command(line, &executor)? {
+            if process_special_command(line, &executor, &mut timer)? {
                 break;
             }
             continue;
@@ -156,7 +230,7 @@ fn run_repl() -> Result<()> {
         input_buffer.pop(); // Remove the space
         input_buffer.pop(); // Remove the semicolon
 
-        match process_sql_command(&input_buffer, &executor) {
+        match process_sql_command(&input_buffer, &executor, timer) {
             Ok(_) => {}
             Err(e) => println!("Error: {}", e),
         }
@@ -168,7 +242,7 @@ fn run_repl() -> Result<()> {
 }
 
 /// Process a SQL command
-fn process_sql_command(sql: &str, executor: &QueryExecutor) -> Result<()> {
+fn process_sql_command(sql: &str, executor: &QueryExecutor, timer: bool) -> Result<()> {
     let statement = match parse_sql(sql) {
         Ok(stmt) => stmt,
         Err(e) => {
@@ -178,15 +252,45 @@ fn process_sql_command(sql: &str, executor: &QueryExecutor) -> Result<()> {
 
     match executor.execute(statement) {
         Ok(result) => {
-            if !result.is_empty() {
-                println!("{}", result.to_string());
-            }
+            print_result(&result, timer);
             Ok(())
         }
         Err(e) => Err(anyhow::anyhow!("Execution error: {}", e)),
     }
 }
 
+/// Print what a statement did: the rows it returned, the rows it changed,
+/// and how long it took when the timer is on
+fn print_result(result: &ExecutionResult, timer: bool) {
+    if result.has_rows() {
+        println!("{}", result.result_set.to_string());
+    }
+    match result.kind {
+        StatementKind::Insert => match result.last_insert_id {
+            Some(id) => println!(
+                "{} row(s) affected (last insert id {})",
+                result.rows_affected, id
+            ),
+            None => println!("{} row(s) affected", result.rows_affected),
+        },
+        StatementKind::CreateTableAs => {
+            println!("Table created with {} row(s)", result.rows_affected)
+        }
+        StatementKind::CreateTable
+        | StatementKind::CreateSequence
+        | StatementKind::CreateView
+        | StatementKind::DropView
+        | StatementKind::Select => {}
+    }
+    if timer {
+        println!(
+            "Run time ({}): {:.3} ms",
+            result.kind,
+            result.elapsed.as_secs_f64() * 1000.0
+        );
+    }
+}
+
 fn main() -> Result<()> {
     match run_repl() {
         Ok(_) => Ok(()),
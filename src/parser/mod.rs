@@ -1,14 +1,16 @@
-use crate::types::{DataType, Value};
+use crate::types::{DataType, Decimal, ReferentialAction, Value, decode_hex};
 use nom::{
-    IResult,
     branch::alt,
     bytes::complete::{tag, tag_no_case, take_while},
-    character::complete::{alpha1, alphanumeric1, char, digit1, multispace0, multispace1},
-    combinator::{map, map_res, opt, recognize},
-    multi::{many0, separated_list1},
-    sequence::{delimited, pair, preceded, tuple},
+    character::complete::{
+        alpha1, alphanumeric1, char, digit0, digit1, multispace0, multispace1, one_of, satisfy,
+    },
+    combinator::{consumed, map, map_res, not, opt, peek, recognize, verify},
+    error::{ErrorKind, FromExternalError},
+    multi::{many0, many1, separated_list1},
+    sequence::{delimited, pair, preceded, separated_pair, terminated, tuple},
 };
-use std::str::FromStr;
+use std::{fmt, str::FromStr};
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -21,12 +23,72 @@ pub enum ParseError {
     #[error("Invalid token: {0}")]
     #[allow(dead_code)]
     InvalidToken(String),
+    #[error("Invalid literal: {0}")]
+    InvalidLiteral(String),
+}
+
+/// Result of the parser combinators
+type IResult<I, O> = nom::IResult<I, O, SyntaxFailure<I>>;
+
+/// Error of the parser combinators: nom's error, plus a message for input
+/// shaped like a literal whose value is invalid, such as
+/// `INTERVAL '9999999999 years'`
+#[derive(Debug)]
+struct SyntaxFailure<I> {
+    error: nom::error::Error<I>,
+    message: Option<String>,
+}
+
+impl<I> nom::error::ParseError<I> for SyntaxFailure<I> {
+    fn from_error_kind(input: I, kind: ErrorKind) -> Self {
+        SyntaxFailure {
+            error: nom::error::Error::new(input, kind),
+            message: None,
+        }
+    }
+
+    fn append(_: I, _: ErrorKind, other: Self) -> Self {
+        other
+    }
+}
+
+impl<I, E> FromExternalError<I, E> for SyntaxFailure<I> {
+    fn from_external_error(input: I, kind: ErrorKind, _: E) -> Self {
+        nom::error::ParseError::from_error_kind(input, kind)
+    }
+}
+
+/// Stop parsing at an invalid literal, reporting `message` instead of
+/// trying the other alternatives
+fn invalid_literal<I, O>(input: I, message: String) -> IResult<I, O> {
+    Err(nom::Err::Failure(SyntaxFailure {
+        error: nom::error::Error::new(input, ErrorKind::Verify),
+        message: Some(message),
+    }))
+}
+
+/// Convert the error of a failed parse into a [`ParseError`]
+fn syntax_error(error: nom::Err<SyntaxFailure<&str>>) -> ParseError {
+    match error {
+        nom::Err::Failure(SyntaxFailure {
+            message: Some(message),
+            ..
+        }) => ParseError::InvalidLiteral(message),
+        error => ParseError::SyntaxError(format!(
+            "SQL parsing error: {:?}",
+            error.map(|failure| failure.error)
+        )),
+    }
 }
 
 /// Different types of SQL statements
 #[derive(Debug, Clone)]
 pub enum Statement {
     CreateTable(CreateTableStatement),
+    CreateTableAs(CreateTableAsStatement),
+    CreateSequence(CreateSequenceStatement),
+    CreateView(CreateViewStatement),
+    DropView(DropViewStatement),
     Insert(InsertStatement),
     Select(SelectStatement),
     // Can be extended with more statement types
@@ -37,6 +99,58 @@ pub enum Statement {
 pub struct CreateTableStatement {
     pub table_name: String,
     pub columns: Vec<ColumnDef>,
+    pub constraints: Vec<TableConstraint>,
+}
+
+/// CREATE TABLE ... AS SELECT statement
+#[derive(Debug, Clone)]
+pub struct CreateTableAsStatement {
+    pub table_name: String,
+    /// Names for the query's columns, which otherwise keep their own
+    pub columns: Option<Vec<String>>,
+    pub query: SelectStatement,
+}
+
+/// CREATE SEQUENCE statement
+#[derive(Debug, Clone)]
+pub struct CreateSequenceStatement {
+    pub name: String,
+    pub options: SequenceOptions,
+}
+
+/// CREATE [OR REPLACE] VIEW statement
+#[derive(Debug, Clone)]
+pub struct CreateViewStatement {
+    pub name: String,
+    /// Names for the query's columns, which otherwise keep their own
+    pub columns: Option<Vec<String>>,
+    pub query: SelectStatement,
+    /// The query's SQL text, which is what the view keeps
+    pub query_sql: String,
+    pub or_replace: bool,
+}
+
+/// DROP VIEW [IF EXISTS] statement
+#[derive(Debug, Clone)]
+pub struct DropViewStatement {
+    pub name: String,
+    pub if_exists: bool,
+}
+
+/// Options of CREATE SEQUENCE and identity columns; bounds and start left
+/// out depend on the direction of the increment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SequenceOptions {
+    /// `START [WITH] n`
+    pub start: Option<i64>,
+    /// `INCREMENT [BY] n`, 1 by default
+    pub increment: Option<i64>,
+    /// `MINVALUE n`
+    pub min_value: Option<i64>,
+    /// `MAXVALUE n`
+    pub max_value: Option<i64>,
+    /// `CYCLE`
+    pub cycle: bool,
 }
 
 /// Column definition for CREATE TABLE
@@ -44,37 +158,317 @@ pub struct CreateTableStatement {
 pub struct ColumnDef {
     pub name: String,
     pub data_type: DataType,
-    pub nullable: bool,
+    pub constraints: Vec<ColumnConstraint>,
+}
+
+/// Constraint written after a column's type
+#[derive(Debug, Clone, PartialEq)]
+pub enum ColumnConstraint {
+    /// `NOT NULL`
+    NotNull,
+    /// `NULL`, the default
+    Null,
+    /// `PRIMARY KEY`, which implies NOT NULL
+    PrimaryKey,
+    /// `UNIQUE`
+    Unique,
+    /// `REFERENCES table [(col)] ...`
+    References(References),
+    /// `DEFAULT expr`, kept as the expression's SQL text
+    Default(String),
+    /// `GENERATED ALWAYS AS (expr) [STORED | VIRTUAL]`, kept as the
+    /// expression's SQL text; VIRTUAL is the default
+    Generated { expr: String, stored: bool },
+    /// `[CONSTRAINT name] CHECK (expr)`, kept as the condition's SQL text
+    Check { name: Option<String>, expr: String },
+    /// `GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY [(options)]`, also
+    /// written `AUTOINCREMENT` or as the type `SERIAL`
+    Identity {
+        always: bool,
+        options: SequenceOptions,
+    },
+}
+
+/// Constraint written as a separate item of CREATE TABLE
+#[derive(Debug, Clone)]
+pub enum TableConstraint {
+    /// `PRIMARY KEY (col, ...)`
+    PrimaryKey(Vec<String>),
+    /// `UNIQUE (col, ...)`
+    Unique(Vec<String>),
+    /// `FOREIGN KEY (col, ...) REFERENCES ...`
+    ForeignKey {
+        columns: Vec<String>,
+        references: References,
+    },
+    /// `[CONSTRAINT name] CHECK (expr)`, kept as the condition's SQL text
+    Check { name: Option<String>, expr: String },
+}
+
+/// `REFERENCES table [(col, ...)] [ON DELETE action] [ON UPDATE action]`
+#[derive(Debug, Clone, PartialEq)]
+pub struct References {
+    pub table: String,
+    /// Referenced columns; empty for the table's primary key
+    pub columns: Vec<String>,
+    pub on_delete: ReferentialAction,
+    pub on_update: ReferentialAction,
 }
 
 /// INSERT statement
+///
+/// `DEFAULT VALUES` is a single VALUES row with an empty column list.
 #[derive(Debug, Clone)]
 pub struct InsertStatement {
     pub table_name: String,
     pub columns: Option<Vec<String>>,
-    pub values: Vec<Vec<Value>>,
+    pub source: InsertSource,
+    pub on_conflict: Option<OnConflict>,
+    /// `RETURNING` list; empty when the statement returns no rows
+    pub returning: Vec<SelectItem>,
+}
+
+/// Rows inserted by an INSERT statement
+#[derive(Debug, Clone)]
+pub enum InsertSource {
+    /// `VALUES (...), (...)`
+    Values(Vec<Vec<InsertValue>>),
+    /// `SELECT ...`, whose result rows are inserted
+    Select(Box<SelectStatement>),
+}
+
+/// What an INSERT does with a row whose key is already in the table:
+/// `ON CONFLICT [(col, ...)] DO ...`, `INSERT OR REPLACE` or
+/// `INSERT OR IGNORE`
+#[derive(Debug, Clone)]
+pub struct OnConflict {
+    /// Columns of the PRIMARY KEY or UNIQUE key checked; empty for any key
+    pub target: Vec<String>,
+    pub action: ConflictAction,
+}
+
+/// Action taken for a conflicting row
+#[derive(Debug, Clone)]
+pub enum ConflictAction {
+    /// `DO NOTHING` (or `INSERT OR IGNORE`): skip the row
+    Nothing,
+    /// `DO UPDATE SET col = expr, ... [WHERE condition]`: update the existing
+    /// row, where `EXCLUDED.col` is the value the new row would have had
+    Update {
+        assignments: Vec<(String, Expression)>,
+        condition: Option<Expression>,
+    },
+    /// `INSERT OR REPLACE`: delete every conflicting row, then insert
+    Replace,
+}
+
+/// One item of a VALUES row
+#[derive(Debug, Clone)]
+pub enum InsertValue {
+    /// An expression giving the value
+    Expression(Expression),
+    /// The `DEFAULT` keyword: the column's default value
+    Default,
 }
 
 /// SELECT statement
 #[derive(Debug, Clone)]
 pub struct SelectStatement {
-    pub columns: Vec<String>,
-    pub table_name: String,
+    pub columns: Vec<SelectItem>,
+    pub from: Vec<TableReference>,
     pub where_clause: Option<WhereClause>,
+    pub order_by: Vec<OrderByItem>,
+}
+
+/// Source of rows in a FROM list
+#[derive(Debug, Clone)]
+pub enum TableReference {
+    /// A stored table
+    Table(String),
+    /// Call of a table-valued function such as `JSON_EACH(doc)`; its
+    /// arguments may refer to columns of the sources before it
+    ///
+    /// `AS alias (col, ...)` names the function's result and renames its
+    /// first output columns.
+    Function {
+        name: String,
+        args: Vec<Expression>,
+        alias: Option<String>,
+        column_aliases: Vec<String>,
+    },
+}
+
+/// Item in a SELECT list
+#[derive(Debug, Clone)]
+pub enum SelectItem {
+    /// `*`: every column of the source table
+    Wildcard,
+    /// `expr [[AS] alias]`, with the expression's SQL text as written
+    Expression {
+        expr: Expression,
+        alias: Option<String>,
+        text: String,
+    },
+}
+
+impl SelectItem {
+    /// Name of the output column produced by this item
+    ///
+    /// An explicit alias wins; otherwise a column keeps its name, without
+    /// the source it is qualified with, and any other expression is named
+    /// after its SQL text, as written.
+    pub fn output_name(&self) -> String {
+        match self {
+            SelectItem::Wildcard => "*".to_string(),
+            SelectItem::Expression {
+                alias: Some(alias), ..
+            } => alias.clone(),
+            SelectItem::Expression {
+                expr: Expression::Column(name),
+                ..
+            } => match name.split_once('.') {
+                Some((_, column)) => column.to_string(),
+                None => name.clone(),
+            },
+            SelectItem::Expression { text, .. } => text.clone(),
+        }
+    }
+}
+
+/// ORDER BY item
+#[derive(Debug, Clone)]
+pub struct OrderByItem {
+    pub expr: Expression,
+    pub descending: bool,
 }
 
 /// WHERE clause condition
 #[derive(Debug, Clone)]
 pub struct WhereClause {
-    pub conditions: Vec<Condition>,
+    pub condition: Expression,
 }
 
-/// Condition in WHERE clause
+/// SQL expression, used in SELECT lists, WHERE and ORDER BY
 #[derive(Debug, Clone)]
-pub struct Condition {
-    pub column: String,
-    pub operator: Operator,
-    pub value: Value,
+pub enum Expression {
+    /// Reference to a column by name
+    Column(String),
+    /// Literal value
+    Literal(Value),
+    /// `left <op> right`
+    Comparison {
+        left: Box<Expression>,
+        operator: Operator,
+        right: Box<Expression>,
+    },
+    /// `left <op> ANY (array)`, or `ALL` when `all`
+    Quantified {
+        left: Box<Expression>,
+        operator: Operator,
+        all: bool,
+        right: Box<Expression>,
+    },
+    /// `expr IS [NOT] NULL`
+    IsNull {
+        expr: Box<Expression>,
+        negated: bool,
+    },
+    /// `expr [NOT] IN (item, ...)`
+    InList {
+        expr: Box<Expression>,
+        list: Vec<Expression>,
+        negated: bool,
+    },
+    /// `expr [NOT] BETWEEN low AND high`
+    Between {
+        expr: Box<Expression>,
+        low: Box<Expression>,
+        high: Box<Expression>,
+        negated: bool,
+    },
+    /// `expr [NOT] LIKE pattern [ESCAPE escape]`, or ILIKE when `case_insensitive`
+    Like {
+        expr: Box<Expression>,
+        pattern: Box<Expression>,
+        escape: Option<Box<Expression>>,
+        case_insensitive: bool,
+        negated: bool,
+    },
+    /// `left AND right`
+    And(Box<Expression>, Box<Expression>),
+    /// `left OR right`
+    Or(Box<Expression>, Box<Expression>),
+    /// `NOT expr`
+    Not(Box<Expression>),
+    /// `CASE [operand] WHEN ... THEN ... [ELSE ...] END`
+    ///
+    /// Without an operand (searched CASE) each WHEN is a condition; with an
+    /// operand (simple CASE) each WHEN is a value compared to it with `=`.
+    Case {
+        operand: Option<Box<Expression>>,
+        when_clauses: Vec<(Expression, Expression)>,
+        else_result: Option<Box<Expression>>,
+    },
+    /// `COALESCE(expr, ...)`, also produced by `IFNULL(a, b)`
+    Coalesce(Vec<Expression>),
+    /// `NULLIF(a, b)`
+    NullIf(Box<Expression>, Box<Expression>),
+    /// `left <op> right` for operators that produce a value
+    BinaryOp {
+        left: Box<Expression>,
+        op: BinaryOperator,
+        right: Box<Expression>,
+    },
+    /// Unary minus, `-expr`
+    Negate(Box<Expression>),
+    /// `CAST(expr AS type)`, also written `expr::type`; `TRY_CAST` gives NULL
+    /// instead of an error when the value cannot be converted
+    Cast {
+        expr: Box<Expression>,
+        data_type: DataType,
+        try_cast: bool,
+    },
+    /// `ARRAY[item, ...]`
+    Array(Vec<Expression>),
+    /// Array element `expr[index]`, counting from 1
+    Subscript {
+        expr: Box<Expression>,
+        index: Box<Expression>,
+    },
+    /// Call of a built-in scalar function, e.g. `UPPER(name)`
+    Function { name: String, args: Vec<Expression> },
+}
+
+/// Operators that combine two values into a new value
+#[derive(Debug, Clone)]
+pub enum BinaryOperator {
+    /// String concatenation (`||`)
+    Concat,
+    Plus,
+    Minus,
+    Multiply,
+    Divide,
+    Modulo,
+    /// JSON member or element (`->`)
+    JsonGet,
+    /// JSON member or element as text (`->>`)
+    JsonGetText,
+}
+
+impl fmt::Display for BinaryOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            BinaryOperator::Concat => "||",
+            BinaryOperator::Plus => "+",
+            BinaryOperator::Minus => "-",
+            BinaryOperator::Multiply => "*",
+            BinaryOperator::Divide => "/",
+            BinaryOperator::Modulo => "%",
+            BinaryOperator::JsonGet => "->",
+            BinaryOperator::JsonGetText => "->>",
+        };
+        write!(f, "{}", symbol)
+    }
 }
 
 /// Comparison operators
@@ -88,15 +482,259 @@ pub enum Operator {
     LessThanOrEqual,
 }
 
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            Operator::Equals => "=",
+            Operator::NotEquals => "<>",
+            Operator::GreaterThan => ">",
+            Operator::LessThan => "<",
+            Operator::GreaterThanOrEqual => ">=",
+            Operator::LessThanOrEqual => "<=",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// Join expressions with ", " for display
+fn join_expressions(exprs: &[Expression]) -> String {
+    exprs
+        .iter()
+        .map(|expr| expr.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl Expression {
+    /// The expressions this one is built from, in the order they appear
+    pub fn children(&self) -> Vec<&Expression> {
+        match self {
+            Expression::Column(_) | Expression::Literal(_) => vec![],
+            Expression::Comparison { left, right, .. }
+            | Expression::Quantified { left, right, .. }
+            | Expression::BinaryOp { left, right, .. }
+            | Expression::And(left, right)
+            | Expression::Or(left, right)
+            | Expression::NullIf(left, right) => vec![left, right],
+            Expression::IsNull { expr, .. }
+            | Expression::Not(expr)
+            | Expression::Negate(expr)
+            | Expression::Cast { expr, .. } => vec![expr],
+            Expression::InList { expr, list, .. } => {
+                let mut children = vec![expr.as_ref()];
+                children.extend(list);
+                children
+            }
+            Expression::Between {
+                expr, low, high, ..
+            } => vec![expr, low, high],
+            Expression::Like {
+                expr,
+                pattern,
+                escape,
+                ..
+            } => {
+                let mut children = vec![expr.as_ref(), pattern.as_ref()];
+                children.extend(escape.as_deref());
+                children
+            }
+            Expression::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => {
+                let mut children: Vec<&Expression> = operand.as_deref().into_iter().collect();
+                for (when, then) in when_clauses {
+                    children.push(when);
+                    children.push(then);
+                }
+                children.extend(else_result.as_deref());
+                children
+            }
+            Expression::Coalesce(args)
+            | Expression::Array(args)
+            | Expression::Function { args, .. } => args.iter().collect(),
+            Expression::Subscript { expr, index } => vec![expr, index],
+        }
+    }
+}
+
+impl fmt::Display for Expression {
+    /// Render the expression as SQL text, used to name unaliased output columns
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let not = |negated: &bool| if *negated { "NOT " } else { "" };
+
+        match self {
+            Expression::Column(name) => write!(f, "{}", name),
+            Expression::Literal(value) => write!(f, "{}", value),
+            Expression::Comparison {
+                left,
+                operator,
+                right,
+            } => write!(f, "{} {} {}", left, operator, right),
+            Expression::Quantified {
+                left,
+                operator,
+                all,
+                right,
+            } => {
+                let quantifier = if *all { "ALL" } else { "ANY" };
+                write!(f, "{} {} {} ({})", left, operator, quantifier, right)
+            }
+            Expression::IsNull { expr, negated } => {
+                write!(f, "{} IS {}NULL", expr, not(negated))
+            }
+            Expression::InList {
+                expr,
+                list,
+                negated,
+            } => write!(
+                f,
+                "{} {}IN ({})",
+                expr,
+                not(negated),
+                join_expressions(list)
+            ),
+            Expression::Between {
+                expr,
+                low,
+                high,
+                negated,
+            } => write!(f, "{} {}BETWEEN {} AND {}", expr, not(negated), low, high),
+            Expression::Like {
+                expr,
+                pattern,
+                escape,
+                case_insensitive,
+                negated,
+            } => {
+                let like = if *case_insensitive { "ILIKE" } else { "LIKE" };
+                write!(f, "{} {}{} {}", expr, not(negated), like, pattern)?;
+                match escape {
+                    Some(escape) => write!(f, " ESCAPE {}", escape),
+                    None => Ok(()),
+                }
+            }
+            Expression::And(left, right) => {
+                // OR binds looser than AND, so it needs parentheses here
+                let wrap = |expr: &Expression| match expr {
+                    Expression::Or(..) => format!("({})", expr),
+                    _ => expr.to_string(),
+                };
+                write!(f, "{} AND {}", wrap(left), wrap(right))
+            }
+            Expression::Or(left, right) => write!(f, "{} OR {}", left, right),
+            Expression::Not(expr) => match expr.as_ref() {
+                Expression::And(..) | Expression::Or(..) => write!(f, "NOT ({})", expr),
+                _ => write!(f, "NOT {}", expr),
+            },
+            Expression::Case {
+                operand,
+                when_clauses,
+                else_result,
+            } => {
+                write!(f, "CASE")?;
+                if let Some(operand) = operand {
+                    write!(f, " {}", operand)?;
+                }
+                for (when, then) in when_clauses {
+                    write!(f, " WHEN {} THEN {}", when, then)?;
+                }
+                if let Some(else_result) = else_result {
+                    write!(f, " ELSE {}", else_result)?;
+                }
+                write!(f, " END")
+            }
+            Expression::Coalesce(args) => write!(f, "COALESCE({})", join_expressions(args)),
+            Expression::NullIf(left, right) => write!(f, "NULLIF({}, {})", left, right),
+            Expression::BinaryOp {
+                left,
+                op: op @ (BinaryOperator::JsonGet | BinaryOperator::JsonGetText),
+                right,
+            } => write!(f, "{}{}{}", left, op, right),
+            Expression::BinaryOp { left, op, right } => write!(f, "{} {} {}", left, op, right),
+            Expression::Negate(expr) => write!(f, "-{}", expr),
+            Expression::Cast {
+                expr,
+                data_type,
+                try_cast,
+            } => {
+                let cast = if *try_cast { "TRY_CAST" } else { "CAST" };
+                write!(f, "{}({} AS {})", cast, expr, data_type)
+            }
+            Expression::Array(items) => write!(f, "ARRAY[{}]", join_expressions(items)),
+            Expression::Subscript { expr, index } => write!(f, "{}[{}]", expr, index),
+            Expression::Function { name, args } => match args.as_slice() {
+                [Expression::Literal(Value::Text(field)), source]
+                    if name.eq_ignore_ascii_case("EXTRACT") =>
+                {
+                    write!(f, "EXTRACT({} FROM {})", field, source)
+                }
+                [] if name.eq_ignore_ascii_case("COUNT") => write!(f, "{}(*)", name),
+                _ => write!(f, "{}({})", name, join_expressions(args)),
+            },
+        }
+    }
+}
+
 // Basic parser functions
 
+/// Keywords that cannot be used as bare identifiers
+const RESERVED_KEYWORDS: &[&str] = &[
+    "AND",
+    "AS",
+    "ASC",
+    "BETWEEN",
+    "BY",
+    "CASE",
+    "CHECK",
+    "CONSTRAINT",
+    "CREATE",
+    "DEFAULT",
+    "DESC",
+    "ELSE",
+    "END",
+    "ESCAPE",
+    "FALSE",
+    "FROM",
+    "ILIKE",
+    "IN",
+    "INSERT",
+    "INTO",
+    "IS",
+    "LIKE",
+    "NOT",
+    "NULL",
+    "OR",
+    "ORDER",
+    "PRIMARY",
+    "FOREIGN",
+    "REFERENCES",
+    "RETURNING",
+    "SELECT",
+    "TABLE",
+    "THEN",
+    "TRUE",
+    "UNIQUE",
+    "VALUES",
+    "WHEN",
+    "WHERE",
+];
+
 /// Parse SQL identifier (table name, column name, etc.)
 fn parse_identifier(input: &str) -> IResult<&str, String> {
     map(
-        recognize(pair(
-            alt((alpha1, tag("_"))),
-            many0(alt((alphanumeric1, tag("_")))),
-        )),
+        verify(
+            recognize(pair(
+                alt((alpha1, tag("_"))),
+                many0(alt((alphanumeric1, tag("_")))),
+            )),
+            |s: &str| {
+                !RESERVED_KEYWORDS
+                    .iter()
+                    .any(|word| word.eq_ignore_ascii_case(s))
+            },
+        ),
         |s: &str| s.to_string(),
     )(input)
 }
@@ -107,10 +745,16 @@ fn parse_whitespace(input: &str) -> IResult<&str, ()> {
 }
 
 /// Case-insensitive keyword parser
+///
+/// The keyword must not be immediately followed by an identifier character,
+/// so `keyword("IN")` does not match the start of `INTEGER`.
 fn keyword<'a>(word: &'static str) -> impl Fn(&'a str) -> IResult<&'a str, &'a str> {
     move |input: &'a str| {
         let (input, _) = parse_whitespace(input)?;
-        tag_no_case(word)(input)
+        terminated(
+            tag_no_case(word),
+            not(peek(satisfy(|c: char| c.is_alphanumeric() || c == '_'))),
+        )(input)
     }
 }
 
@@ -124,35 +768,164 @@ fn parse_string_literal(input: &str) -> IResult<&str, String> {
     )(input)
 }
 
-/// Parse an integer literal
-fn parse_integer_literal(input: &str) -> IResult<&str, i64> {
+/// Parse a numeric literal
+///
+/// Digits with an exponent (`2e10`, `6.02E+23`) are FLOAT. Digits with a
+/// decimal point (`1.50`, `.5`) are an exact DECIMAL with the digits as
+/// written, or FLOAT if they have more than 38 digits. Plain digits are
+/// INTEGER, or DECIMAL if they are too large for an INTEGER. A leading `-` is
+/// part of the literal so negative values can be inserted.
+fn parse_number_literal(input: &str) -> IResult<&str, Value> {
     let (input, _) = parse_whitespace(input)?;
-    map_res(digit1, |s: &str| s.parse::<i64>())(input)
+    map_res(
+        recognize(tuple((
+            opt(char('-')),
+            alt((
+                recognize(tuple((digit1, opt(pair(char('.'), digit0))))),
+                recognize(pair(char('.'), digit1)),
+            )),
+            opt(tuple((one_of("eE"), opt(one_of("+-")), digit1))),
+        ))),
+        |s: &str| {
+            if s.contains(['e', 'E']) {
+                s.parse::<f64>()
+                    .map(Value::Float)
+                    .map_err(|e| e.to_string())
+            } else if s.contains('.') {
+                s.parse::<Decimal>().map(Value::Decimal).or_else(|_| {
+                    s.parse::<f64>()
+                        .map(Value::Float)
+                        .map_err(|e| e.to_string())
+                })
+            } else {
+                s.parse::<i64>().map(Value::Integer).or_else(|_| {
+                    s.parse::<Decimal>()
+                        .map(Value::Decimal)
+                        .map_err(|e| e.to_string())
+                })
+            }
+        },
+    )(input)
 }
 
-/// Parse a SQL value (string, integer, or NULL)
+/// Parse a SQL value (string, number, boolean, typed literal, or NULL)
 fn parse_value(input: &str) -> IResult<&str, Value> {
     let (input, _) = parse_whitespace(input)?;
     alt((
         map(parse_string_literal, Value::Text),
-        map(parse_integer_literal, Value::Integer),
-        map(tag_no_case("NULL"), |_| Value::Null),
+        parse_blob_literal,
+        parse_number_literal,
+        map(keyword("NULL"), |_| Value::Null),
+        map(keyword("TRUE"), |_| Value::Boolean(true)),
+        map(keyword("FALSE"), |_| Value::Boolean(false)),
+        parse_typed_literal,
     ))(input)
 }
 
+/// Parse a hexadecimal BLOB literal such as `X'DEADBEEF'`
+fn parse_blob_literal(input: &str) -> IResult<&str, Value> {
+    let (rest, hex) = preceded(
+        tag_no_case("X"),
+        delimited(char('\''), take_while(|c: char| c != '\''), char('\'')),
+    )(input)?;
+    if let Some(digit) = hex.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return invalid_literal(
+            input,
+            format!("invalid hex digit '{}' in X'{}'", digit, hex),
+        );
+    }
+    match decode_hex(hex) {
+        Some(bytes) => Ok((rest, Value::Blob(bytes))),
+        None => invalid_literal(
+            input,
+            format!(
+                "blob literal must have an even number of hex digits: X'{}'",
+                hex
+            ),
+        ),
+    }
+}
+
+/// Parse a string literal prefixed with its type, such as
+/// `DATE '2024-01-01'` or `INTERVAL '1 day'`
+///
+/// Text that does not convert to the type, or is out of its range, is an
+/// invalid literal rather than a syntax error.
+fn parse_typed_literal(input: &str) -> IResult<&str, Value> {
+    let (input, data_type) = parse_data_type(input)?;
+    let (rest, text) = parse_string_literal(input)?;
+    match Value::Text(text.clone()).as_type(&data_type) {
+        Ok(value) => Ok((rest, value)),
+        Err(e) => invalid_literal(input, format!("{} '{}' ({})", data_type, text, e)),
+    }
+}
+
 /// Parse a data type (INTEGER, TEXT, etc.)
 fn parse_data_type(input: &str) -> IResult<&str, DataType> {
     let (input, _) = parse_whitespace(input)?;
     map_res(
+        recognize(pair(
+            parse_base_data_type,
+            many0(pair(parse_whitespace, tag("[]"))),
+        )),
+        |s: &str| DataType::from_str(s),
+    )(input)
+}
+
+/// Parse the name of a data type other than an array type
+fn parse_base_data_type(input: &str) -> IResult<&str, &str> {
+    alt((
+        recognize(tuple((
+            tag_no_case("TIMESTAMP"),
+            multispace1,
+            alt((tag_no_case("WITHOUT"), tag_no_case("WITH"))),
+            multispace1,
+            tag_no_case("TIME"),
+            multispace1,
+            tag_no_case("ZONE"),
+        ))),
+        tag_no_case("TIMESTAMPTZ"),
+        tag_no_case("TIMESTAMP"),
+        tag_no_case("TIME"),
+        tag_no_case("DATE"),
+        tag_no_case("INTERVAL"),
+        recognize(pair(
+            alt((tag_no_case("DECIMAL"), tag_no_case("NUMERIC"))),
+            opt(tuple((
+                parse_whitespace,
+                char('('),
+                parse_whitespace,
+                digit1,
+                opt(tuple((
+                    parse_whitespace,
+                    char(','),
+                    parse_whitespace,
+                    digit1,
+                ))),
+                parse_whitespace,
+                char(')'),
+            ))),
+        )),
         alt((
             tag_no_case("INTEGER"),
             tag_no_case("INT"),
+            tag_no_case("FLOAT"),
+            tag_no_case("REAL"),
+            tag_no_case("DOUBLE PRECISION"),
+            tag_no_case("DOUBLE"),
+        )),
+        alt((
             tag_no_case("TEXT"),
+            tag_no_case("BLOB"),
+            tag_no_case("BYTEA"),
+            tag_no_case("BOOLEAN"),
+            tag_no_case("BOOL"),
             tag_no_case("VARCHAR"),
             tag_no_case("STRING"),
+            tag_no_case("JSON"),
+            tag_no_case("UUID"),
         )),
-        |s: &str| DataType::from_str(s),
-    )(input)
+    ))(input)
 }
 
 /// Parse a column definition for CREATE TABLE
@@ -160,20 +933,278 @@ fn parse_column_def(input: &str) -> IResult<&str, ColumnDef> {
     let (input, _) = parse_whitespace(input)?;
     let (input, name) = parse_identifier(input)?;
     let (input, _) = parse_whitespace(input)?;
-    let (input, data_type) = parse_data_type(input)?;
-    let (input, nullable) = opt(preceded(multispace1, tag_no_case("NULL")))(input)?;
+    let (input, (data_type, serial)) = alt((
+        map(keyword("SERIAL"), |_| (DataType::Integer, true)),
+        map(parse_data_type, |data_type| (data_type, false)),
+    ))(input)?;
+    let (input, mut constraints) = many0(parse_column_constraint)(input)?;
+
+    // SERIAL is shorthand for an INTEGER NOT NULL identity column
+    if serial {
+        let identity = ColumnConstraint::Identity {
+            always: false,
+            options: SequenceOptions::default(),
+        };
+        constraints.splice(0..0, [ColumnConstraint::NotNull, identity]);
+    }
 
-    let nullable = nullable.is_some();
     Ok((
         input,
         ColumnDef {
             name,
             data_type,
-            nullable,
+            constraints,
         },
     ))
 }
 
+/// Parse a constraint following a column's type
+fn parse_column_constraint(input: &str) -> IResult<&str, ColumnConstraint> {
+    alt((
+        map(pair(keyword("NOT"), keyword("NULL")), |_| {
+            ColumnConstraint::NotNull
+        }),
+        map(keyword("NULL"), |_| ColumnConstraint::Null),
+        map(pair(keyword("PRIMARY"), keyword("KEY")), |_| {
+            ColumnConstraint::PrimaryKey
+        }),
+        map(keyword("UNIQUE"), |_| ColumnConstraint::Unique),
+        map(parse_references, ColumnConstraint::References),
+        map(
+            preceded(keyword("DEFAULT"), parse_expression_text),
+            ColumnConstraint::Default,
+        ),
+        parse_identity,
+        parse_generated,
+        map(keyword("AUTOINCREMENT"), |_| ColumnConstraint::Identity {
+            always: false,
+            options: SequenceOptions::default(),
+        }),
+        map(parse_check, |(name, expr)| ColumnConstraint::Check {
+            name,
+            expr,
+        }),
+    ))(input)
+}
+
+/// Parse `[CONSTRAINT name] CHECK (expr)` into the name and the condition's
+/// SQL text
+fn parse_check(input: &str) -> IResult<&str, (Option<String>, String)> {
+    let (input, name) = opt(preceded(
+        pair(keyword("CONSTRAINT"), parse_whitespace),
+        parse_identifier,
+    ))(input)?;
+    let (input, _) = keyword("CHECK")(input)?;
+    let (input, expr) = delimited(
+        tuple((parse_whitespace, char('('))),
+        parse_expression_text,
+        tuple((parse_whitespace, char(')'))),
+    )(input)?;
+    Ok((input, (name, expr)))
+}
+
+/// Parse an expression, returning its SQL text as written
+fn parse_expression_text(input: &str) -> IResult<&str, String> {
+    map(consumed(parse_expression), |(text, _): (&str, _)| {
+        text.trim().to_string()
+    })(input)
+}
+
+/// Parse `GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY [(options)]`
+fn parse_identity(input: &str) -> IResult<&str, ColumnConstraint> {
+    let (input, _) = keyword("GENERATED")(input)?;
+    let (input, always) = alt((
+        map(keyword("ALWAYS"), |_| true),
+        map(pair(keyword("BY"), keyword("DEFAULT")), |_| false),
+    ))(input)?;
+    let (input, _) = pair(keyword("AS"), keyword("IDENTITY"))(input)?;
+    let (input, options) = opt(delimited(
+        tuple((parse_whitespace, char('('))),
+        parse_sequence_options,
+        tuple((parse_whitespace, char(')'))),
+    ))(input)?;
+
+    Ok((
+        input,
+        ColumnConstraint::Identity {
+            always,
+            options: options.unwrap_or_default(),
+        },
+    ))
+}
+
+/// One option of a sequence definition
+enum SequenceOption {
+    Start(i64),
+    Increment(i64),
+    MinValue(Option<i64>),
+    MaxValue(Option<i64>),
+    Cycle(bool),
+}
+
+/// Parse sequence options, such as `START WITH 100 INCREMENT BY 10`, in
+/// any order
+fn parse_sequence_options(input: &str) -> IResult<&str, SequenceOptions> {
+    let (input, items) = many0(alt((
+        map(
+            preceded(pair(keyword("START"), opt(keyword("WITH"))), parse_integer),
+            SequenceOption::Start,
+        ),
+        map(
+            preceded(
+                pair(keyword("INCREMENT"), opt(keyword("BY"))),
+                parse_integer,
+            ),
+            SequenceOption::Increment,
+        ),
+        map(preceded(keyword("MINVALUE"), parse_integer), |n| {
+            SequenceOption::MinValue(Some(n))
+        }),
+        map(preceded(keyword("MAXVALUE"), parse_integer), |n| {
+            SequenceOption::MaxValue(Some(n))
+        }),
+        map(pair(keyword("NO"), keyword("MINVALUE")), |_| {
+            SequenceOption::MinValue(None)
+        }),
+        map(pair(keyword("NO"), keyword("MAXVALUE")), |_| {
+            SequenceOption::MaxValue(None)
+        }),
+        map(keyword("CYCLE"), |_| SequenceOption::Cycle(true)),
+        map(pair(keyword("NO"), keyword("CYCLE")), |_| {
+            SequenceOption::Cycle(false)
+        }),
+    )))(input)?;
+
+    let mut options = SequenceOptions::default();
+    for item in items {
+        match item {
+            SequenceOption::Start(n) => options.start = Some(n),
+            SequenceOption::Increment(n) => options.increment = Some(n),
+            SequenceOption::MinValue(n) => options.min_value = n,
+            SequenceOption::MaxValue(n) => options.max_value = n,
+            SequenceOption::Cycle(cycle) => options.cycle = cycle,
+        }
+    }
+    Ok((input, options))
+}
+
+/// Parse an INTEGER literal, which may be negative
+fn parse_integer(input: &str) -> IResult<&str, i64> {
+    map_res(parse_number_literal, |value| match value {
+        Value::Integer(n) => Ok(n),
+        _ => Err("expected an integer"),
+    })(input)
+}
+
+/// Parse `GENERATED ALWAYS AS (expr) [STORED | VIRTUAL]`
+fn parse_generated(input: &str) -> IResult<&str, ColumnConstraint> {
+    let (input, _) = tuple((keyword("GENERATED"), keyword("ALWAYS"), keyword("AS")))(input)?;
+    let (input, expr) = delimited(
+        tuple((parse_whitespace, char('('))),
+        parse_expression_text,
+        tuple((parse_whitespace, char(')'))),
+    )(input)?;
+    let (input, stored) = opt(alt((
+        map(keyword("STORED"), |_| true),
+        map(keyword("VIRTUAL"), |_| false),
+    )))(input)?;
+
+    Ok((
+        input,
+        ColumnConstraint::Generated {
+            expr,
+            stored: stored.unwrap_or(false),
+        },
+    ))
+}
+
+/// Parse `REFERENCES table [(col, ...)]` and its ON DELETE / ON UPDATE actions
+fn parse_references(input: &str) -> IResult<&str, References> {
+    let (input, _) = keyword("REFERENCES")(input)?;
+    let (input, _) = parse_whitespace(input)?;
+    let (input, table) = parse_identifier(input)?;
+    let (input, columns) = opt(parse_column_list)(input)?;
+    let (input, actions) = many0(pair(
+        preceded(
+            keyword("ON"),
+            alt((
+                map(keyword("DELETE"), |_| true),
+                map(keyword("UPDATE"), |_| false),
+            )),
+        ),
+        parse_referential_action,
+    ))(input)?;
+
+    let mut references = References {
+        table,
+        columns: columns.unwrap_or_default(),
+        on_delete: ReferentialAction::default(),
+        on_update: ReferentialAction::default(),
+    };
+    for (on_delete, action) in actions {
+        if on_delete {
+            references.on_delete = action;
+        } else {
+            references.on_update = action;
+        }
+    }
+    Ok((input, references))
+}
+
+/// Parse a referential action: RESTRICT, CASCADE, SET NULL, SET DEFAULT or
+/// NO ACTION
+fn parse_referential_action(input: &str) -> IResult<&str, ReferentialAction> {
+    alt((
+        map(keyword("RESTRICT"), |_| ReferentialAction::Restrict),
+        map(keyword("CASCADE"), |_| ReferentialAction::Cascade),
+        map(pair(keyword("SET"), keyword("NULL")), |_| {
+            ReferentialAction::SetNull
+        }),
+        map(pair(keyword("SET"), keyword("DEFAULT")), |_| {
+            ReferentialAction::SetDefault
+        }),
+        map(pair(keyword("NO"), keyword("ACTION")), |_| {
+            ReferentialAction::NoAction
+        }),
+    ))(input)
+}
+
+/// Parse a table constraint: `PRIMARY KEY (col, ...)`, `UNIQUE (col, ...)`,
+/// `FOREIGN KEY (col, ...) REFERENCES ...` or `[CONSTRAINT name] CHECK (expr)`
+fn parse_table_constraint(input: &str) -> IResult<&str, TableConstraint> {
+    let (input, _) = parse_whitespace(input)?;
+    alt((
+        map(
+            preceded(pair(keyword("PRIMARY"), keyword("KEY")), parse_column_list),
+            TableConstraint::PrimaryKey,
+        ),
+        map(
+            preceded(keyword("UNIQUE"), parse_column_list),
+            TableConstraint::Unique,
+        ),
+        map(
+            preceded(
+                pair(keyword("FOREIGN"), keyword("KEY")),
+                pair(parse_column_list, parse_references),
+            ),
+            |(columns, references)| TableConstraint::ForeignKey {
+                columns,
+                references,
+            },
+        ),
+        map(parse_check, |(name, expr)| TableConstraint::Check {
+            name,
+            expr,
+        }),
+    ))(input)
+}
+
+/// One item of a CREATE TABLE body: a column or a table constraint
+enum TableElement {
+    Column(ColumnDef),
+    Constraint(TableConstraint),
+}
+
 /// Parse a CREATE TABLE statement
 fn parse_create_table(input: &str) -> IResult<&str, CreateTableStatement> {
     let (input, _) = tuple((
@@ -184,20 +1215,108 @@ fn parse_create_table(input: &str) -> IResult<&str, CreateTableStatement> {
     ))(input)?;
 
     let (input, table_name) = parse_identifier(input)?;
-    let (input, columns) = delimited(
+    let (input, elements) = delimited(
         tuple((parse_whitespace, char('('), parse_whitespace)),
         separated_list1(
             tuple((parse_whitespace, char(','), parse_whitespace)),
-            parse_column_def,
+            alt((
+                map(parse_table_constraint, TableElement::Constraint),
+                map(parse_column_def, TableElement::Column),
+            )),
         ),
         tuple((parse_whitespace, char(')'))),
     )(input)?;
 
+    let mut columns = Vec::new();
+    let mut constraints = Vec::new();
+    for element in elements {
+        match element {
+            TableElement::Column(column) => columns.push(column),
+            TableElement::Constraint(constraint) => constraints.push(constraint),
+        }
+    }
+
     Ok((
         input,
         CreateTableStatement {
             table_name,
             columns,
+            constraints,
+        },
+    ))
+}
+
+/// Parse CREATE TABLE name [(col, ...)] AS SELECT ...
+fn parse_create_table_as(input: &str) -> IResult<&str, CreateTableAsStatement> {
+    let (input, _) = tuple((
+        keyword("CREATE"),
+        multispace1,
+        keyword("TABLE"),
+        multispace1,
+    ))(input)?;
+    let (input, table_name) = parse_identifier(input)?;
+    let (input, columns) = opt(parse_column_list)(input)?;
+    let (input, _) = keyword("AS")(input)?;
+    let (input, query) = parse_select(input)?;
+
+    Ok((
+        input,
+        CreateTableAsStatement {
+            table_name,
+            columns,
+            query,
+        },
+    ))
+}
+
+/// Parse CREATE SEQUENCE name [options]
+fn parse_create_sequence(input: &str) -> IResult<&str, CreateSequenceStatement> {
+    let (input, _) = tuple((
+        keyword("CREATE"),
+        multispace1,
+        keyword("SEQUENCE"),
+        multispace1,
+    ))(input)?;
+    let (input, name) = parse_identifier(input)?;
+    let (input, options) = parse_sequence_options(input)?;
+
+    Ok((input, CreateSequenceStatement { name, options }))
+}
+
+/// Parse CREATE [OR REPLACE] VIEW name [(col, ...)] AS SELECT ...
+fn parse_create_view(input: &str) -> IResult<&str, CreateViewStatement> {
+    let (input, _) = keyword("CREATE")(input)?;
+    let (input, or_replace) = opt(tuple((keyword("OR"), keyword("REPLACE"))))(input)?;
+    let (input, _) = tuple((keyword("VIEW"), multispace1))(input)?;
+    let (input, name) = parse_identifier(input)?;
+    let (input, columns) = opt(parse_column_list)(input)?;
+    let (input, _) = tuple((keyword("AS"), parse_whitespace))(input)?;
+    let (input, (query_sql, query)) = consumed(parse_select)(input)?;
+
+    Ok((
+        input,
+        CreateViewStatement {
+            name,
+            columns,
+            query,
+            query_sql: query_sql.trim().to_string(),
+            or_replace: or_replace.is_some(),
+        },
+    ))
+}
+
+/// Parse DROP VIEW [IF EXISTS] name
+fn parse_drop_view(input: &str) -> IResult<&str, DropViewStatement> {
+    let (input, _) = tuple((keyword("DROP"), keyword("VIEW")))(input)?;
+    let (input, if_exists) = opt(tuple((keyword("IF"), keyword("EXISTS"))))(input)?;
+    let (input, _) = multispace1(input)?;
+    let (input, name) = parse_identifier(input)?;
+
+    Ok((
+        input,
+        DropViewStatement {
+            name,
+            if_exists: if_exists.is_some(),
         },
     ))
 }
@@ -214,20 +1333,23 @@ fn parse_column_list(input: &str) -> IResult<&str, Vec<String>> {
     )(input)
 }
 
-/// Parse a list of values for INSERT
-fn parse_value_list(input: &str) -> IResult<&str, Vec<Value>> {
+/// Parse a parenthesized list of value expressions (or DEFAULT) for INSERT
+fn parse_value_list(input: &str) -> IResult<&str, Vec<InsertValue>> {
     delimited(
         tuple((parse_whitespace, char('('), parse_whitespace)),
         separated_list1(
             tuple((parse_whitespace, char(','), parse_whitespace)),
-            parse_value,
+            alt((
+                map(keyword("DEFAULT"), |_| InsertValue::Default),
+                map(parse_expression, InsertValue::Expression),
+            )),
         ),
         tuple((parse_whitespace, char(')'))),
     )(input)
 }
 
 /// Parse multiple value lists for INSERT
-fn parse_values_clause(input: &str) -> IResult<&str, Vec<Vec<Value>>> {
+fn parse_values_clause(input: &str) -> IResult<&str, Vec<Vec<InsertValue>>> {
     let (input, _) = tuple((keyword("VALUES"), multispace1))(input)?;
 
     separated_list1(
@@ -238,19 +1360,110 @@ fn parse_values_clause(input: &str) -> IResult<&str, Vec<Vec<Value>>> {
 
 /// Parse an INSERT statement
 fn parse_insert(input: &str) -> IResult<&str, InsertStatement> {
-    let (input, _) = tuple((keyword("INSERT"), multispace1, keyword("INTO"), multispace1))(input)?;
+    let (input, _) = keyword("INSERT")(input)?;
+    let (input, or_action) = opt(preceded(
+        keyword("OR"),
+        alt((
+            map(keyword("REPLACE"), |_| ConflictAction::Replace),
+            map(keyword("IGNORE"), |_| ConflictAction::Nothing),
+        )),
+    ))(input)?;
+    let (input, _) = tuple((multispace1, keyword("INTO"), multispace1))(input)?;
 
     let (input, table_name) = parse_identifier(input)?;
+    let or_conflict = or_action.map(|action| OnConflict {
+        target: vec![],
+        action,
+    });
+
+    // DEFAULT VALUES inserts one row with every column omitted
+    if let Ok((input, _)) = pair(keyword("DEFAULT"), keyword("VALUES"))(input) {
+        let (input, returning) = parse_returning(input)?;
+        return Ok((
+            input,
+            InsertStatement {
+                table_name,
+                columns: Some(vec![]),
+                source: InsertSource::Values(vec![vec![]]),
+                on_conflict: or_conflict,
+                returning,
+            },
+        ));
+    }
+
     let (input, columns) = opt(parse_column_list)(input)?;
     let (input, _) = parse_whitespace(input)?;
-    let (input, values) = parse_values_clause(input)?;
+    let (input, source) = alt((
+        map(parse_values_clause, InsertSource::Values),
+        map(parse_select, |query| InsertSource::Select(Box::new(query))),
+    ))(input)?;
+    let (input, on_conflict) = match or_conflict {
+        Some(on_conflict) => (input, Some(on_conflict)),
+        None => opt(parse_on_conflict)(input)?,
+    };
+    let (input, returning) = parse_returning(input)?;
 
     Ok((
         input,
         InsertStatement {
             table_name,
             columns,
-            values,
+            source,
+            on_conflict,
+            returning,
+        },
+    ))
+}
+
+/// Parse an optional `RETURNING item, ...` list, with the items of a
+/// SELECT list
+fn parse_returning(input: &str) -> IResult<&str, Vec<SelectItem>> {
+    map(
+        opt(preceded(
+            keyword("RETURNING"),
+            separated_list1(
+                tuple((parse_whitespace, char(','), parse_whitespace)),
+                parse_select_item,
+            ),
+        )),
+        Option::unwrap_or_default,
+    )(input)
+}
+
+/// Parse `ON CONFLICT [(col, ...)] DO NOTHING` or
+/// `ON CONFLICT [(col, ...)] DO UPDATE SET col = expr, ... [WHERE condition]`
+fn parse_on_conflict(input: &str) -> IResult<&str, OnConflict> {
+    let (input, _) = pair(keyword("ON"), keyword("CONFLICT"))(input)?;
+    let (input, target) = opt(parse_column_list)(input)?;
+    let (input, _) = keyword("DO")(input)?;
+    let (input, action) = alt((
+        map(keyword("NOTHING"), |_| ConflictAction::Nothing),
+        map(
+            tuple((
+                keyword("UPDATE"),
+                keyword("SET"),
+                separated_list1(
+                    tuple((parse_whitespace, char(','))),
+                    separated_pair(
+                        preceded(parse_whitespace, parse_identifier),
+                        tuple((parse_whitespace, char('='))),
+                        parse_expression,
+                    ),
+                ),
+                opt(preceded(parse_whitespace, parse_where_clause)),
+            )),
+            |(_, _, assignments, where_clause)| ConflictAction::Update {
+                assignments,
+                condition: where_clause.map(|where_clause| where_clause.condition),
+            },
+        ),
+    ))(input)?;
+
+    Ok((
+        input,
+        OnConflict {
+            target: target.unwrap_or_default(),
+            action,
         },
     ))
 }
@@ -269,65 +1482,552 @@ fn parse_operator(input: &str) -> IResult<&str, Operator> {
     ))(input)
 }
 
-/// Parse a single condition in a WHERE clause
-fn parse_condition(input: &str) -> IResult<&str, Condition> {
-    let (input, _) = parse_whitespace(input)?;
-    let (input, column) = parse_identifier(input)?;
-    let (input, _) = parse_whitespace(input)?;
-    let (input, operator) = parse_operator(input)?;
+/// Parse an operand: a parenthesized expression, CASE, a function call, a
+/// literal value or a column reference
+fn parse_operand(input: &str) -> IResult<&str, Expression> {
     let (input, _) = parse_whitespace(input)?;
-    let (input, value) = parse_value(input)?;
+    alt((
+        delimited(
+            pair(char('('), parse_whitespace),
+            parse_expression,
+            pair(parse_whitespace, char(')')),
+        ),
+        parse_case,
+        parse_null_function,
+        parse_extract,
+        parse_cast,
+        parse_array,
+        parse_function_call,
+        map(parse_value, Expression::Literal),
+        // `table.col`, such as `EXCLUDED.col` in ON CONFLICT DO UPDATE
+        map(
+            separated_pair(parse_identifier, char('.'), parse_identifier),
+            |(table, name)| match table.eq_ignore_ascii_case("EXCLUDED") {
+                true => Expression::Column(format!("excluded.{}", name)),
+                false => Expression::Column(format!("{}.{}", table, name)),
+            },
+        ),
+        map(parse_identifier, Expression::Column),
+    ))(input)
+}
+
+/// Parse a function call such as `UPPER(name)` or `NOW()`
+fn parse_function_call(input: &str) -> IResult<&str, Expression> {
+    map(parse_call, |(name, args)| Expression::Function {
+        name,
+        args,
+    })(input)
+}
+
+/// Parse a function name and its parenthesized argument list
+///
+/// `name(*)`, as in `COUNT(*)`, is a call without arguments.
+fn parse_call(input: &str) -> IResult<&str, (String, Vec<Expression>)> {
+    let (input, name) = parse_identifier(input)?;
+    let (input, args) = delimited(
+        tuple((parse_whitespace, char('('), parse_whitespace)),
+        alt((
+            map(char('*'), |_| None),
+            opt(separated_list1(
+                tuple((parse_whitespace, char(','), parse_whitespace)),
+                parse_expression,
+            )),
+        )),
+        tuple((parse_whitespace, char(')'))),
+    )(input)?;
+
+    Ok((input, (name, args.unwrap_or_default())))
+}
 
+/// Parse an operand followed by any number of array subscripts (`tags[1]`),
+/// JSON accesses (`doc -> 'items' ->> 0`) and casts (`price::INTEGER`)
+fn parse_postfix_expression(input: &str) -> IResult<&str, Expression> {
+    let (mut input, mut expr) = parse_operand(input)?;
+    loop {
+        if let Ok((rest, data_type)) =
+            preceded(pair(parse_whitespace, tag("::")), parse_data_type)(input)
+        {
+            expr = Expression::Cast {
+                expr: Box::new(expr),
+                data_type,
+                try_cast: false,
+            };
+            input = rest;
+        } else if let Ok((rest, index)) = delimited(
+            pair(parse_whitespace, char('[')),
+            parse_expression,
+            pair(parse_whitespace, char(']')),
+        )(input)
+        {
+            expr = Expression::Subscript {
+                expr: Box::new(expr),
+                index: Box::new(index),
+            };
+            input = rest;
+        } else if let Ok((rest, (op, right))) = pair(
+            preceded(
+                parse_whitespace,
+                alt((
+                    map(tag("->>"), |_| BinaryOperator::JsonGetText),
+                    map(tag("->"), |_| BinaryOperator::JsonGet),
+                )),
+            ),
+            parse_operand,
+        )(input)
+        {
+            expr = fold_binary(expr, vec![(op, right)]);
+            input = rest;
+        } else {
+            return Ok((input, expr));
+        }
+    }
+}
+
+/// Parse `CAST(expr AS type)` or `TRY_CAST(expr AS type)`
+fn parse_cast(input: &str) -> IResult<&str, Expression> {
+    let (input, cast) = alt((keyword("TRY_CAST"), keyword("CAST")))(input)?;
+    let (input, (expr, data_type)) = delimited(
+        pair(parse_whitespace, char('(')),
+        pair(
+            parse_expression,
+            preceded(pair(keyword("AS"), multispace1), parse_data_type),
+        ),
+        pair(parse_whitespace, char(')')),
+    )(input)?;
     Ok((
         input,
-        Condition {
-            column,
-            operator,
-            value,
+        Expression::Cast {
+            expr: Box::new(expr),
+            data_type,
+            try_cast: cast.eq_ignore_ascii_case("TRY_CAST"),
         },
     ))
 }
 
-/// Parse a WHERE clause
-fn parse_where_clause(input: &str) -> IResult<&str, WhereClause> {
-    let (input, _) = tuple((keyword("WHERE"), multispace1))(input)?;
+/// Parse an array constructor, `ARRAY[item, ...]`
+fn parse_array(input: &str) -> IResult<&str, Expression> {
+    let (input, _) = keyword("ARRAY")(input)?;
+    let (input, items) = delimited(
+        tuple((parse_whitespace, char('['), parse_whitespace)),
+        opt(separated_list1(
+            tuple((parse_whitespace, char(','), parse_whitespace)),
+            parse_expression,
+        )),
+        tuple((parse_whitespace, char(']'))),
+    )(input)?;
+    Ok((input, Expression::Array(items.unwrap_or_default())))
+}
+
+/// Parse an optionally negated operand (`-expr`)
+fn parse_unary_expression(input: &str) -> IResult<&str, Expression> {
+    alt((
+        parse_postfix_expression,
+        map(
+            preceded(pair(parse_whitespace, char('-')), parse_unary_expression),
+            |expr| Expression::Negate(Box::new(expr)),
+        ),
+    ))(input)
+}
+
+/// Fold `first (op operand)*` into left-associative binary operations
+fn fold_binary(first: Expression, rest: Vec<(BinaryOperator, Expression)>) -> Expression {
+    rest.into_iter()
+        .fold(first, |left, (op, right)| Expression::BinaryOp {
+            left: Box::new(left),
+            op,
+            right: Box::new(right),
+        })
+}
+
+/// Parse operands joined by `*`, `/` and `%`
+fn parse_multiplicative_expression(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = parse_unary_expression(input)?;
+    let (input, rest) = many0(pair(
+        preceded(
+            parse_whitespace,
+            alt((
+                map(char('*'), |_| BinaryOperator::Multiply),
+                map(char('/'), |_| BinaryOperator::Divide),
+                map(char('%'), |_| BinaryOperator::Modulo),
+            )),
+        ),
+        parse_unary_expression,
+    ))(input)?;
+    Ok((input, fold_binary(first, rest)))
+}
+
+/// Parse terms joined by `+` and `-` (binding tighter than `||`)
+fn parse_additive_expression(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = parse_multiplicative_expression(input)?;
+    let (input, rest) = many0(pair(
+        preceded(
+            parse_whitespace,
+            alt((
+                map(char('+'), |_| BinaryOperator::Plus),
+                map(char('-'), |_| BinaryOperator::Minus),
+            )),
+        ),
+        parse_multiplicative_expression,
+    ))(input)?;
+    Ok((input, fold_binary(first, rest)))
+}
+
+/// Parse arithmetic expressions joined by the `||` concatenation operator
+fn parse_concat_expression(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = parse_additive_expression(input)?;
+    let (input, rest) = many0(pair(
+        preceded(parse_whitespace, map(tag("||"), |_| BinaryOperator::Concat)),
+        parse_additive_expression,
+    ))(input)?;
+    Ok((input, fold_binary(first, rest)))
+}
+
+/// Parse a CASE expression, either searched or simple
+fn parse_case(input: &str) -> IResult<&str, Expression> {
+    let (input, _) = keyword("CASE")(input)?;
+
+    // A searched CASE goes straight to WHEN; a simple CASE has an operand first
+    let (input, operand) = match peek(keyword("WHEN"))(input) {
+        Ok(_) => (input, None),
+        Err(_) => map(parse_expression, |expr| Some(Box::new(expr)))(input)?,
+    };
+
+    let when = |input| match operand {
+        None => parse_expression(input),
+        Some(_) => parse_concat_expression(input),
+    };
+    let (input, when_clauses) = many1(pair(
+        preceded(keyword("WHEN"), when),
+        preceded(keyword("THEN"), parse_expression),
+    ))(input)?;
+    let (input, else_result) = opt(preceded(keyword("ELSE"), parse_expression))(input)?;
+    let (input, _) = keyword("END")(input)?;
 
-    // For simplicity, we'll only handle one condition for now
-    // In a more complete implementation, we would parse multiple conditions with AND/OR
-    let (input, condition) = parse_condition(input)?;
     Ok((
         input,
-        WhereClause {
-            conditions: vec![condition],
+        Expression::Case {
+            operand,
+            when_clauses,
+            else_result: else_result.map(Box::new),
         },
     ))
 }
 
-/// Parse a SELECT statement
-fn parse_select(input: &str) -> IResult<&str, SelectStatement> {
-    let (input, _) = tuple((keyword("SELECT"), multispace1))(input)?;
+/// Parse `EXTRACT(field FROM source)` as a call to EXTRACT with the field
+/// name as its first argument
+fn parse_extract(input: &str) -> IResult<&str, Expression> {
+    let (input, _) = keyword("EXTRACT")(input)?;
+    let (input, (field, source)) = delimited(
+        tuple((parse_whitespace, char('('), parse_whitespace)),
+        pair(
+            parse_identifier,
+            preceded(
+                tuple((multispace1, keyword("FROM"), multispace1)),
+                parse_expression,
+            ),
+        ),
+        tuple((parse_whitespace, char(')'))),
+    )(input)?;
+
+    Ok((
+        input,
+        Expression::Function {
+            name: "EXTRACT".to_string(),
+            args: vec![Expression::Literal(Value::Text(field)), source],
+        },
+    ))
+}
+
+/// Parse the NULL-handling forms COALESCE, IFNULL and NULLIF
+fn parse_null_function(input: &str) -> IResult<&str, Expression> {
+    alt((
+        map(
+            preceded(keyword("COALESCE"), parse_expression_list),
+            Expression::Coalesce,
+        ),
+        map_res(
+            preceded(keyword("IFNULL"), parse_expression_list),
+            |args| match args.len() {
+                2 => Ok(Expression::Coalesce(args)),
+                n => Err(ParseError::SyntaxError(format!(
+                    "IFNULL expects 2 arguments, got {}",
+                    n
+                ))),
+            },
+        ),
+        map_res(
+            preceded(keyword("NULLIF"), parse_expression_list),
+            |mut args| match args.len() {
+                2 => {
+                    let b = args.pop().unwrap_or(Expression::Literal(Value::Null));
+                    let a = args.pop().unwrap_or(Expression::Literal(Value::Null));
+                    Ok(Expression::NullIf(Box::new(a), Box::new(b)))
+                }
+                n => Err(ParseError::SyntaxError(format!(
+                    "NULLIF expects 2 arguments, got {}",
+                    n
+                ))),
+            },
+        ),
+    ))(input)
+}
 
-    // Parse column list or * for all columns
-    let (input, columns) = alt((
-        map(tag("*"), |_| vec!["*".to_string()]),
+/// Parse a parenthesized, comma-separated list of expressions
+fn parse_expression_list(input: &str) -> IResult<&str, Vec<Expression>> {
+    delimited(
+        tuple((parse_whitespace, char('('), parse_whitespace)),
         separated_list1(
             tuple((parse_whitespace, char(','), parse_whitespace)),
-            parse_identifier,
+            parse_expression,
         ),
-    ))(input)?;
+        tuple((parse_whitespace, char(')'))),
+    )(input)
+}
 
-    let (input, _) = tuple((multispace1, keyword("FROM"), multispace1))(input)?;
+/// Parse the part of a predicate that follows its left operand
+///
+/// Handles `<op> operand`, `IS [NOT] NULL` and the optionally negated
+/// `IN`, `BETWEEN`, `LIKE` and `ILIKE` forms.
+fn parse_predicate_suffix(left: Expression) -> impl Fn(&str) -> IResult<&str, Expression> {
+    move |input: &str| {
+        // Plain comparison, or comparison with ANY/SOME/ALL elements of an array
+        if let Ok((input, operator)) = parse_operator(input) {
+            if let Ok((input, quantifier)) =
+                alt((keyword("ANY"), keyword("SOME"), keyword("ALL")))(input)
+                && let Ok((input, right)) = delimited(
+                    pair(parse_whitespace, char('(')),
+                    parse_expression,
+                    pair(parse_whitespace, char(')')),
+                )(input)
+            {
+                return Ok((
+                    input,
+                    Expression::Quantified {
+                        left: Box::new(left.clone()),
+                        operator,
+                        all: quantifier.eq_ignore_ascii_case("ALL"),
+                        right: Box::new(right),
+                    },
+                ));
+            }
+            let (input, right) = parse_concat_expression(input)?;
+            return Ok((
+                input,
+                Expression::Comparison {
+                    left: Box::new(left.clone()),
+                    operator,
+                    right: Box::new(right),
+                },
+            ));
+        }
 
-    let (input, table_name) = parse_identifier(input)?;
+        // IS [NOT] NULL
+        if let Ok((input, _)) = keyword("IS")(input) {
+            let (input, negated) = opt(keyword("NOT"))(input)?;
+            let (input, _) = keyword("NULL")(input)?;
+            return Ok((
+                input,
+                Expression::IsNull {
+                    expr: Box::new(left.clone()),
+                    negated: negated.is_some(),
+                },
+            ));
+        }
+
+        let (input, negated) = opt(keyword("NOT"))(input)?;
+        let negated = negated.is_some();
+        let expr = Box::new(left.clone());
+
+        // [NOT] IN (...)
+        if let Ok((input, _)) = keyword("IN")(input) {
+            let (input, list) = parse_expression_list(input)?;
+            return Ok((
+                input,
+                Expression::InList {
+                    expr,
+                    list,
+                    negated,
+                },
+            ));
+        }
+
+        // [NOT] BETWEEN low AND high
+        if let Ok((input, _)) = keyword("BETWEEN")(input) {
+            let (input, low) = parse_concat_expression(input)?;
+            let (input, _) = keyword("AND")(input)?;
+            let (input, high) = parse_concat_expression(input)?;
+            return Ok((
+                input,
+                Expression::Between {
+                    expr,
+                    low: Box::new(low),
+                    high: Box::new(high),
+                    negated,
+                },
+            ));
+        }
+
+        // [NOT] LIKE / ILIKE pattern [ESCAPE escape]
+        let (input, like) = alt((keyword("LIKE"), keyword("ILIKE")))(input)?;
+        let (input, pattern) = parse_concat_expression(input)?;
+        let (input, escape) = opt(preceded(keyword("ESCAPE"), parse_concat_expression))(input)?;
+        Ok((
+            input,
+            Expression::Like {
+                expr,
+                pattern: Box::new(pattern),
+                escape: escape.map(Box::new),
+                case_insensitive: like.eq_ignore_ascii_case("ILIKE"),
+                negated,
+            },
+        ))
+    }
+}
+
+/// Parse a predicate such as `age > 25` or `name LIKE 'A%'`, or a plain
+/// operand when no predicate follows it
+fn parse_predicate(input: &str) -> IResult<&str, Expression> {
+    let (input, left) = parse_concat_expression(input)?;
+    match parse_predicate_suffix(left.clone())(input) {
+        Err(nom::Err::Error(_)) => Ok((input, left)),
+        result => result,
+    }
+}
+
+/// Parse an optionally negated expression (NOT binds tighter than AND)
+fn parse_not_expression(input: &str) -> IResult<&str, Expression> {
+    alt((
+        map(preceded(keyword("NOT"), parse_not_expression), |expr| {
+            Expression::Not(Box::new(expr))
+        }),
+        parse_predicate,
+    ))(input)
+}
+
+/// Parse expressions joined by AND (AND binds tighter than OR)
+fn parse_and_expression(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = parse_not_expression(input)?;
+    let (input, rest) = many0(preceded(keyword("AND"), parse_not_expression))(input)?;
+    let expr = rest.into_iter().fold(first, |left, right| {
+        Expression::And(Box::new(left), Box::new(right))
+    });
+    Ok((input, expr))
+}
+
+/// Parse a full expression: AND-groups joined by OR
+fn parse_expression(input: &str) -> IResult<&str, Expression> {
+    let (input, first) = parse_and_expression(input)?;
+    let (input, rest) = many0(preceded(keyword("OR"), parse_and_expression))(input)?;
+    let expr = rest.into_iter().fold(first, |left, right| {
+        Expression::Or(Box::new(left), Box::new(right))
+    });
+    Ok((input, expr))
+}
+
+/// Parse a WHERE clause
+fn parse_where_clause(input: &str) -> IResult<&str, WhereClause> {
+    let (input, _) = tuple((keyword("WHERE"), multispace1))(input)?;
+    let (input, condition) = parse_expression(input)?;
+    Ok((input, WhereClause { condition }))
+}
+
+/// Parse an item of the SELECT list: `*` or `expr [[AS] alias]`
+fn parse_select_item(input: &str) -> IResult<&str, SelectItem> {
+    let (input, _) = parse_whitespace(input)?;
+    alt((
+        map(char('*'), |_| SelectItem::Wildcard),
+        map(
+            pair(
+                consumed(parse_expression),
+                opt(preceded(
+                    pair(opt(keyword("AS")), parse_whitespace),
+                    parse_identifier,
+                )),
+            ),
+            |((text, expr), alias)| SelectItem::Expression {
+                expr,
+                alias,
+                text: text.trim().to_string(),
+            },
+        ),
+    ))(input)
+}
+
+/// Parse an ORDER BY clause
+fn parse_order_by(input: &str) -> IResult<&str, Vec<OrderByItem>> {
+    let (input, _) = tuple((keyword("ORDER"), multispace1, keyword("BY")))(input)?;
+
+    separated_list1(
+        tuple((parse_whitespace, char(','))),
+        map(
+            pair(
+                parse_expression,
+                opt(alt((keyword("ASC"), keyword("DESC")))),
+            ),
+            |(expr, direction)| OrderByItem {
+                expr,
+                descending: direction.is_some_and(|d| d.eq_ignore_ascii_case("DESC")),
+            },
+        ),
+    )(input)
+}
+
+/// Parse an item of a FROM list: a table-valued function call with an
+/// optional alias, or a table name
+fn parse_table_reference(input: &str) -> IResult<&str, TableReference> {
+    alt((
+        map(
+            pair(
+                parse_call,
+                opt(pair(
+                    preceded(pair(opt(keyword("AS")), parse_whitespace), parse_identifier),
+                    opt(parse_column_list),
+                )),
+            ),
+            |((name, args), alias)| {
+                let (alias, column_aliases) = match alias {
+                    Some((alias, columns)) => (Some(alias), columns.unwrap_or_default()),
+                    None => (None, vec![]),
+                };
+                TableReference::Function {
+                    name,
+                    args,
+                    alias,
+                    column_aliases,
+                }
+            },
+        ),
+        map(parse_identifier, TableReference::Table),
+    ))(input)
+}
+
+/// Parse a SELECT statement
+fn parse_select(input: &str) -> IResult<&str, SelectStatement> {
+    let (input, _) = tuple((keyword("SELECT"), multispace1))(input)?;
+
+    // Parse the select list
+    let (input, columns) = separated_list1(
+        tuple((parse_whitespace, char(','), parse_whitespace)),
+        parse_select_item,
+    )(input)?;
+
+    let (input, _) = tuple((keyword("FROM"), multispace1))(input)?;
+
+    let (input, from) = separated_list1(
+        tuple((parse_whitespace, char(','), parse_whitespace)),
+        parse_table_reference,
+    )(input)?;
     let (input, _) = parse_whitespace(input)?;
     let (input, where_clause) = opt(parse_where_clause)(input)?;
+    let (input, order_by) = opt(parse_order_by)(input)?;
 
     Ok((
         input,
         SelectStatement {
             columns,
-            table_name,
+            from,
             where_clause,
+            order_by: order_by.unwrap_or_default(),
         },
     ))
 }
@@ -336,16 +2036,48 @@ fn parse_select(input: &str) -> IResult<&str, SelectStatement> {
 fn parse_statement(input: &str) -> IResult<&str, Statement> {
     let (input, _) = parse_whitespace(input)?;
     alt((
+        map(parse_create_table_as, Statement::CreateTableAs),
         map(parse_create_table, Statement::CreateTable),
+        map(parse_create_sequence, Statement::CreateSequence),
+        map(parse_create_view, Statement::CreateView),
+        map(parse_drop_view, Statement::DropView),
         map(parse_insert, Statement::Insert),
         map(parse_select, Statement::Select),
     ))(input)
 }
 
+/// Parse a standalone expression, such as a stored DEFAULT expression
+pub fn parse_expression_sql(input: &str) -> Result<Expression, ParseError> {
+    let (remainder, expr) = parse_expression(input).map_err(syntax_error)?;
+
+    if remainder.trim().is_empty() {
+        Ok(expr)
+    } else {
+        Err(ParseError::SyntaxError(format!(
+            "Unexpected trailing input: '{}'",
+            remainder
+        )))
+    }
+}
+
+/// Parse a standalone SELECT statement, such as the stored query of a view
+pub fn parse_select_sql(input: &str) -> Result<SelectStatement, ParseError> {
+    let (remainder, select) =
+        preceded(parse_whitespace, parse_select)(input).map_err(syntax_error)?;
+
+    if remainder.trim().is_empty() {
+        Ok(select)
+    } else {
+        Err(ParseError::SyntaxError(format!(
+            "Unexpected trailing input: '{}'",
+            remainder
+        )))
+    }
+}
+
 /// Parse an SQL statement and ensure the input is completely consumed
 pub fn parse_sql(input: &str) -> Result<Statement, ParseError> {
-    let (remainder, stmt) = parse_statement(input)
-        .map_err(|e| ParseError::SyntaxError(format!("SQL parsing error: {:?}", e)))?;
+    let (remainder, stmt) = parse_statement(input).map_err(syntax_error)?;
 
     // Check if the entire input was consumed
     if remainder.trim().is_empty() {
@@ -357,3 +2089,63 @@ pub fn parse_sql(input: &str) -> Result<Statement, ParseError> {
         )))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn literal(sql: &str) -> Value {
+        match parse_expression_sql(sql) {
+            Ok(Expression::Literal(value)) => value,
+            other => panic!("{} is not a literal: {:?}", sql, other),
+        }
+    }
+
+    #[test]
+    fn numeric_literal_types() {
+        let cases = [
+            ("1", DataType::Integer),
+            ("-42", DataType::Integer),
+            ("99999999999999999999", DataType::Decimal(None)),
+            ("1.5", DataType::Decimal(None)),
+            ("-1.10", DataType::Decimal(None)),
+            (".5", DataType::Decimal(None)),
+            ("1.", DataType::Decimal(None)),
+            (
+                "0.1234567890123456789012345678901234567890",
+                DataType::Float,
+            ),
+            ("1e3", DataType::Float),
+            ("1.5e0", DataType::Float),
+            ("-6.02E+23", DataType::Float),
+        ];
+        for (sql, expected) in cases {
+            let value = literal(sql);
+            assert_eq!(value.data_type(), Some(expected), "{}", sql);
+        }
+        assert_eq!(literal("1.10").to_string(), "1.10");
+        assert_eq!(literal("1e400").to_string(), "Infinity");
+    }
+
+    #[test]
+    fn invalid_literals() {
+        let error = |sql: &str| parse_expression_sql(sql).unwrap_err().to_string();
+        assert_eq!(
+            error("INTERVAL '9999999999 years'"),
+            "Invalid literal: INTERVAL '9999999999 years' \
+             (Arithmetic error: INTERVAL out of range)"
+        );
+        assert!(error("DATE '2024-13-01'").contains("Cannot convert '2024-13-01' to DATE"));
+        assert_eq!(
+            error("X'ABC'"),
+            "Invalid literal: blob literal must have an even number of hex digits: X'ABC'"
+        );
+        assert_eq!(
+            error("x'0G'"),
+            "Invalid literal: invalid hex digit 'G' in X'0G'"
+        );
+        assert_eq!(literal("X'00fF'"), Value::Blob(vec![0, 255]));
+        assert_eq!(literal("X''"), Value::Blob(vec![]));
+        assert!(error("(").starts_with("SQL syntax error: SQL parsing error"));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::types::{Column, DataType, Operator, Row, Schema, TypeError, Value};
+use crate::types::{Column, DataType, Operator, Row, Schema, Sequence, TypeError, TypeMode, Value};
 use serde::{Deserialize, Serialize};
 use std::{
     collections::HashMap,
@@ -30,6 +30,42 @@ pub enum StorageError {
     #[error("Value validation error: {0}")]
     ValidationError(#[from] TypeError),
 
+    /// NULL stored in a NOT NULL column
+    #[error("NOT NULL constraint failed: {0}")]
+    NotNullViolation(String),
+
+    /// Duplicate primary key
+    #[error("PRIMARY KEY constraint failed: {0}")]
+    PrimaryKeyViolation(String),
+
+    /// Duplicate key of a UNIQUE constraint
+    #[error("UNIQUE constraint failed: {0}")]
+    UniqueViolation(String),
+
+    /// Reference to a missing key, or removal of a referenced table
+    #[error("FOREIGN KEY constraint failed: {0}")]
+    ForeignKeyViolation(String),
+
+    /// Sequence not found error
+    #[error("Sequence not found: {0}")]
+    SequenceNotFound(String),
+
+    /// Sequence already exists error
+    #[error("Sequence already exists: {0}")]
+    SequenceAlreadyExists(String),
+
+    /// Sequence past its bound, CURRVAL before NEXTVAL or SETVAL out of range
+    #[error("Sequence error: {0}")]
+    SequenceError(String),
+
+    /// View not found error
+    #[error("View not found: {0}")]
+    ViewNotFound(String),
+
+    /// View already exists error
+    #[error("View already exists: {0}")]
+    ViewAlreadyExists(String),
+
     /// Concurrency error
     #[error("Concurrency error: {0}")]
     ConcurrencyError(String),
@@ -52,6 +88,31 @@ pub struct TableMetadata {
     pub schema: Schema,
 }
 
+/// View metadata
+///
+/// A view is a named query, kept as SQL text and run again each time the
+/// view is read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ViewMetadata {
+    /// View name
+    pub name: String,
+    /// Names for the query's columns, which otherwise keep their own
+    pub columns: Option<Vec<String>>,
+    /// SQL text of the view's SELECT statement
+    pub query: String,
+}
+
+/// A change to one row of a table, written by `Database::write_rows`
+#[derive(Debug, Clone)]
+pub enum RowChange {
+    /// Add a row at the end of the table
+    Insert(Row),
+    /// Replace the stored row at a position
+    Update(usize, Row),
+    /// Remove the stored row at a position
+    Delete(usize),
+}
+
 /// Represents a table in the database
 #[derive(Debug, Clone)]
 pub struct Table {
@@ -70,23 +131,129 @@ impl Table {
         }
     }
 
-    /// Insert a row into the table
-    pub fn insert_row(&mut self, row: Row) -> Result<(), StorageError> {
+    /// Prepare a row for insertion, converting values as the type mode
+    /// allows and checking the table's own constraints
+    ///
+    /// `pending` rows are being inserted along with this one. Foreign keys
+    /// involve other tables and are checked by the database.
+    pub fn prepare_row(
+        &self,
+        row: Row,
+        mode: TypeMode,
+        pending: &[Row],
+    ) -> Result<Row, StorageError> {
+        let row = self.metadata.schema.coerce_row(row, mode);
+        self.check_constraints(&row, pending)?;
         self.metadata.schema.validate_row(&row)?;
-        self.rows.push(row);
-        Ok(())
+        Ok(row)
     }
 
-    /// Insert multiple rows into the table
-    #[allow(dead_code)]
-    pub fn insert_rows(&mut self, rows: Vec<Row>) -> Result<(), StorageError> {
-        for row in &rows {
-            self.metadata.schema.validate_row(row)?;
+    /// Check a row against the NOT NULL, PRIMARY KEY and UNIQUE constraints
+    ///
+    /// Keys are compared with the stored rows and with `pending` rows that
+    /// are being inserted along with this one.
+    fn check_constraints(&self, row: &Row, pending: &[Row]) -> Result<(), StorageError> {
+        let schema = &self.metadata.schema;
+        // Leave malformed rows for `validate_row` to reject
+        if row.values.len() != schema.columns.len() {
+            return Ok(());
+        }
+
+        for (value, column) in row.values.iter().zip(&schema.columns) {
+            if value.is_null() && !column.nullable {
+                return Err(StorageError::NotNullViolation(format!(
+                    "{}.{}",
+                    self.metadata.name, column.name
+                )));
+            }
+        }
+
+        for key in &schema.keys {
+            let values = match schema.values_of(row, &key.columns) {
+                // NULLs are never equal, so a key with a NULL is never a duplicate
+                Some(values) if values.iter().all(|value| !value.is_null()) => values,
+                _ => continue,
+            };
+
+            if self.contains_key(&key.columns, &values, pending) {
+                let message = format!(
+                    "{}({}) already has key ({})",
+                    self.metadata.name,
+                    key.columns.join(", "),
+                    values
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+                return Err(if key.primary {
+                    StorageError::PrimaryKeyViolation(message)
+                } else {
+                    StorageError::UniqueViolation(message)
+                });
+            }
         }
-        self.rows.extend(rows);
+
         Ok(())
     }
 
+    /// Whether some row, stored or `pending`, has the given values in the
+    /// named columns
+    fn contains_key(&self, columns: &[String], values: &[&Value], pending: &[Row]) -> bool {
+        self.rows
+            .iter()
+            .chain(pending)
+            .any(|row| self.has_key(row, columns, values))
+    }
+
+    /// Whether a row has the given values in the named columns
+    fn has_key(&self, row: &Row, columns: &[String], values: &[&Value]) -> bool {
+        self.metadata
+            .schema
+            .values_of(row, columns)
+            .is_some_and(|candidate| {
+                candidate.iter().zip(values).all(|(a, b)| {
+                    a.compare(&Operator::Eq, b)
+                        .is_ok_and(|truth| truth.is_true())
+                })
+            })
+    }
+
+    /// Positions of the rows, stored or `pending`, that have the same values
+    /// as `row` in the columns of one of the table's keys, or of the key with
+    /// the given columns
+    ///
+    /// Pending rows are numbered after the stored ones. As in
+    /// `check_constraints`, keys with a NULL never conflict.
+    pub fn conflicts(
+        &self,
+        row: &Row,
+        key_columns: Option<&[String]>,
+        pending: &[Row],
+    ) -> Vec<usize> {
+        let schema = &self.metadata.schema;
+        let mut positions = Vec::new();
+        for key in &schema.keys {
+            if key_columns.is_some_and(|columns| {
+                columns.len() != key.columns.len()
+                    || !columns.iter().all(|column| key.columns.contains(column))
+            }) {
+                continue;
+            }
+            let values = match schema.values_of(row, &key.columns) {
+                Some(values) if values.iter().all(|value| !value.is_null()) => values,
+                _ => continue,
+            };
+            for (position, stored) in self.rows.iter().chain(pending).enumerate() {
+                if self.has_key(stored, &key.columns, &values) && !positions.contains(&position) {
+                    positions.push(position);
+                }
+            }
+        }
+        positions.sort_unstable();
+        positions
+    }
+
     /// Scan all rows
     pub fn scan(&self) -> Vec<Row> {
         self.rows.clone()
@@ -116,10 +283,8 @@ impl Table {
                 ))
             })?;
 
-            match row_value.compare(op, value) {
-                Ok(true) => result.push(row.clone()),
-                Ok(false) => {}
-                Err(e) => return Err(e.into()),
+            if row_value.compare(op, value)?.is_true() {
+                result.push(row.clone());
             }
         }
 
@@ -132,6 +297,12 @@ impl Table {
 pub struct Database {
     /// Collection of tables with read-write lock for concurrent access
     tables: Arc<RwLock<HashMap<String, Table>>>,
+    /// How values of different types are mixed (lenient by default)
+    type_mode: Arc<RwLock<TypeMode>>,
+    /// Sequences, whose values are used as soon as they are handed out
+    sequences: Arc<RwLock<HashMap<String, Sequence>>>,
+    /// Views, which share the namespace of tables
+    views: Arc<RwLock<HashMap<String, ViewMetadata>>>,
 }
 
 impl Database {
@@ -139,9 +310,31 @@ impl Database {
     pub fn new() -> Self {
         Self {
             tables: Arc::new(RwLock::new(HashMap::new())),
+            type_mode: Arc::new(RwLock::new(TypeMode::default())),
+            sequences: Arc::new(RwLock::new(HashMap::new())),
+            views: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
+    /// Get the type mode
+    pub fn type_mode(&self) -> Result<TypeMode, StorageError> {
+        let mode = self.type_mode.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+
+        Ok(*mode)
+    }
+
+    /// Set the type mode, which applies to the statements run after it
+    pub fn set_type_mode(&self, mode: TypeMode) -> Result<(), StorageError> {
+        let mut current = self.type_mode.write().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
+        })?;
+
+        *current = mode;
+        Ok(())
+    }
+
     /// Create a new persistent database with filename
     #[allow(dead_code)]
     pub fn with_persistence(_filename: &str) -> Result<Self, StorageError> {
@@ -155,7 +348,7 @@ impl Database {
             StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
         })?;
 
-        if tables.contains_key(&name) {
+        if tables.contains_key(&name) || self.view_exists(&name)? {
             return Err(StorageError::TableAlreadyExists(name));
         }
 
@@ -184,12 +377,30 @@ impl Database {
     }
 
     /// Drop a table
-    #[allow(dead_code)]
+    ///
+    /// A table that another table's foreign key refers to cannot be dropped.
     pub fn drop_table(&self, name: &str) -> Result<(), StorageError> {
         let mut tables = self.tables.write().map_err(|e| {
             StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
         })?;
 
+        for table in tables.values().filter(|table| table.metadata.name != name) {
+            if let Some(foreign_key) = table
+                .metadata
+                .schema
+                .foreign_keys
+                .iter()
+                .find(|foreign_key| foreign_key.table == name)
+            {
+                return Err(StorageError::ForeignKeyViolation(format!(
+                    "Cannot drop table {}: referenced by {}({})",
+                    name,
+                    table.metadata.name,
+                    foreign_key.columns.join(", ")
+                )));
+            }
+        }
+
         if tables.remove(name).is_none() {
             return Err(StorageError::TableNotFound(name.to_string()));
         }
@@ -220,30 +431,180 @@ impl Database {
     }
 
     /// Insert a row into a table
+    #[allow(dead_code)]
     pub fn insert(&self, table_name: &str, row: Row) -> Result<(), StorageError> {
+        let mode = self.type_mode()?;
         let mut tables = self.tables.write().map_err(|e| {
             StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
         })?;
 
         let table = tables
-            .get_mut(table_name)
+            .get(table_name)
             .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
 
-        table.insert_row(row)
+        let row = table.prepare_row(row, mode, &[])?;
+        check_foreign_keys(&tables, table, &row, &[])?;
+        if let Some(table) = tables.get_mut(table_name) {
+            table.rows.push(row);
+        }
+        Ok(())
     }
 
     /// Insert multiple rows into a table
-    #[allow(dead_code)]
     pub fn insert_many(&self, table_name: &str, rows: Vec<Row>) -> Result<(), StorageError> {
+        let mode = self.type_mode()?;
         let mut tables = self.tables.write().map_err(|e| {
             StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
         })?;
 
         let table = tables
-            .get_mut(table_name)
+            .get(table_name)
+            .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
+
+        let mut prepared = Vec::new();
+        for row in rows {
+            let row = table.prepare_row(row, mode, &prepared)?;
+            check_foreign_keys(&tables, table, &row, &prepared)?;
+            prepared.push(row);
+        }
+        if let Some(table) = tables.get_mut(table_name) {
+            table.rows.extend(prepared);
+        }
+        Ok(())
+    }
+
+    /// Find the rows that an inserted row would duplicate a key of
+    ///
+    /// Only the key with `key_columns` is checked if given, otherwise every
+    /// PRIMARY KEY and UNIQUE key. The row's values must already have the
+    /// column types. Returns the rows with their positions in the table,
+    /// where `pending` rows, which are to be written along with this one,
+    /// follow the stored rows.
+    pub fn find_conflicts(
+        &self,
+        table_name: &str,
+        row: &Row,
+        key_columns: Option<&[String]>,
+        pending: &[Row],
+    ) -> Result<Vec<(usize, Row)>, StorageError> {
+        let tables = self.tables.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+
+        let table = tables
+            .get(table_name)
             .ok_or_else(|| StorageError::TableNotFound(table_name.to_string()))?;
 
-        table.insert_rows(rows)
+        let rows: Vec<&Row> = table.rows.iter().chain(pending).collect();
+        Ok(table
+            .conflicts(row, key_columns, pending)
+            .into_iter()
+            .map(|position| (position, rows[position].clone()))
+            .collect())
+    }
+
+    /// Write the row changes of one statement, to one or more tables, at once
+    ///
+    /// Positions are those of the stored rows (as returned by
+    /// `find_conflicts`); an updated row keeps its place and inserted rows
+    /// are added at the end. The new rows are checked like inserted ones,
+    /// against the rest of the table and each other, and foreign keys are
+    /// checked on the tables as they are after all the changes: the rows
+    /// written must refer to existing keys, and no row may still refer to a
+    /// key that was deleted or updated. The executor carries out the CASCADE,
+    /// SET NULL and SET DEFAULT actions as row changes of the referring
+    /// tables, so only NO ACTION and RESTRICT reach this check. On error no
+    /// table is changed.
+    pub fn write_rows(&self, changes: Vec<(String, Vec<RowChange>)>) -> Result<(), StorageError> {
+        let mode = self.type_mode()?;
+        let mut tables = self.tables.write().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
+        })?;
+
+        let mut saved: HashMap<String, Vec<Row>> = HashMap::new();
+        let checked = (|| {
+            let mut written = Vec::new();
+            let mut removed = Vec::new();
+            for (table_name, table_changes) in changes {
+                let table = tables
+                    .get_mut(&table_name)
+                    .ok_or_else(|| StorageError::TableNotFound(table_name.clone()))?;
+                let mut positions: Vec<usize> = table_changes
+                    .iter()
+                    .filter_map(|change| match change {
+                        RowChange::Insert(_) => None,
+                        RowChange::Update(position, _) | RowChange::Delete(position) => {
+                            Some(*position)
+                        }
+                    })
+                    .collect();
+                positions.sort_unstable();
+                if positions.windows(2).any(|pair| pair[0] == pair[1])
+                    || positions
+                        .last()
+                        .is_some_and(|&position| position >= table.rows.len())
+                    || saved.contains_key(&table_name)
+                {
+                    return Err(StorageError::ConcurrencyError(format!(
+                        "Rows of {} changed while they were being written",
+                        table_name
+                    )));
+                }
+
+                // Check the new rows against the rows that stay
+                let old = saved
+                    .entry(table_name.clone())
+                    .or_insert(std::mem::take(&mut table.rows));
+                table.rows = old
+                    .iter()
+                    .enumerate()
+                    .filter(|(position, _)| positions.binary_search(position).is_err())
+                    .map(|(_, row)| row.clone())
+                    .collect();
+                let mut prepared = Vec::new();
+                let mut rows: Vec<Option<Row>> = old.iter().cloned().map(Some).collect();
+                let mut inserted = Vec::new();
+                for change in table_changes {
+                    match change {
+                        RowChange::Insert(row) => {
+                            let row = table.prepare_row(row, mode, &prepared)?;
+                            prepared.push(row.clone());
+                            inserted.push(row);
+                        }
+                        RowChange::Update(position, row) => {
+                            let row = table.prepare_row(row, mode, &prepared)?;
+                            prepared.push(row.clone());
+                            rows[position] = Some(row);
+                        }
+                        RowChange::Delete(position) => rows[position] = None,
+                    }
+                }
+                table.rows = rows.into_iter().flatten().chain(inserted).collect();
+                removed.extend(
+                    positions
+                        .iter()
+                        .map(|&position| (table_name.clone(), old[position].clone())),
+                );
+                written.extend(prepared.into_iter().map(|row| (table_name.clone(), row)));
+            }
+
+            for (table_name, row) in &written {
+                check_foreign_keys(&tables, &tables[table_name], row, &[])?;
+            }
+            for (table_name, row) in &removed {
+                check_references_kept(&tables, &tables[table_name], row)?;
+            }
+            Ok(())
+        })();
+
+        if checked.is_err() {
+            for (table_name, rows) in saved {
+                if let Some(table) = tables.get_mut(&table_name) {
+                    table.rows = rows;
+                }
+            }
+        }
+        checked
     }
 
     /// Scan all rows in a table
@@ -288,7 +649,161 @@ impl Database {
         Ok(tables.keys().cloned().collect())
     }
 
+    /// Create a view, or replace the view of the same name if `replace` is
+    /// set; a view cannot have the name of a table
+    pub fn create_view(&self, view: ViewMetadata, replace: bool) -> Result<(), StorageError> {
+        let tables = self.tables.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+        let mut views = self.views.write().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
+        })?;
+
+        if tables.contains_key(&view.name) {
+            return Err(StorageError::TableAlreadyExists(view.name));
+        }
+        if views.contains_key(&view.name) && !replace {
+            return Err(StorageError::ViewAlreadyExists(view.name));
+        }
+
+        views.insert(view.name.clone(), view);
+        Ok(())
+    }
+
+    /// Drop a view
+    pub fn drop_view(&self, name: &str) -> Result<(), StorageError> {
+        let mut views = self.views.write().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
+        })?;
+
+        if views.remove(name).is_none() {
+            return Err(StorageError::ViewNotFound(name.to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Check if a view exists
+    pub fn view_exists(&self, name: &str) -> Result<bool, StorageError> {
+        let views = self.views.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+
+        Ok(views.contains_key(name))
+    }
+
+    /// Get view metadata
+    pub fn get_view(&self, name: &str) -> Result<ViewMetadata, StorageError> {
+        let views = self.views.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+
+        views
+            .get(name)
+            .cloned()
+            .ok_or_else(|| StorageError::ViewNotFound(name.to_string()))
+    }
+
+    /// Get all views
+    pub fn get_views(&self) -> Result<Vec<ViewMetadata>, StorageError> {
+        let views = self.views.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+
+        Ok(views.values().cloned().collect())
+    }
+
+    /// Create a new sequence
+    pub fn create_sequence(&self, name: String, sequence: Sequence) -> Result<(), StorageError> {
+        let mut sequences = self.sequences.write().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
+        })?;
+
+        if sequences.contains_key(&name) {
+            return Err(StorageError::SequenceAlreadyExists(name));
+        }
+
+        sequences.insert(name, sequence);
+        Ok(())
+    }
+
+    /// Check if a sequence exists
+    pub fn sequence_exists(&self, name: &str) -> Result<bool, StorageError> {
+        let sequences = self.sequences.read().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
+        })?;
+
+        Ok(sequences.contains_key(name))
+    }
+
+    /// Apply a change to a sequence under the write lock
+    fn update_sequence<T>(
+        &self,
+        name: &str,
+        update: impl FnOnce(&mut Sequence) -> Result<T, String>,
+    ) -> Result<T, StorageError> {
+        let mut sequences = self.sequences.write().map_err(|e| {
+            StorageError::ConcurrencyError(format!("Failed to acquire write lock: {}", e))
+        })?;
+
+        let sequence = sequences
+            .get_mut(name)
+            .ok_or_else(|| StorageError::SequenceNotFound(name.to_string()))?;
+
+        update(sequence).map_err(StorageError::SequenceError)
+    }
+
+    /// Take the next value of a sequence
+    ///
+    /// The value is used up even if the statement that asked for it fails,
+    /// so concurrent statements never receive the same value.
+    pub fn next_value(&self, name: &str) -> Result<i64, StorageError> {
+        self.update_sequence(name, |sequence| {
+            sequence.next_value().ok_or_else(|| {
+                let bound = if sequence.increment > 0 {
+                    ("maximum", sequence.max_value)
+                } else {
+                    ("minimum", sequence.min_value)
+                };
+                format!("{} reached its {} value ({})", name, bound.0, bound.1)
+            })
+        })
+    }
+
+    /// Get the value most recently taken from a sequence
+    pub fn current_value(&self, name: &str) -> Result<i64, StorageError> {
+        self.update_sequence(name, |sequence| {
+            sequence
+                .current_value()
+                .ok_or_else(|| format!("CURRVAL of {} is not yet defined", name))
+        })
+    }
+
+    /// Set the last value of a sequence
+    pub fn set_value(&self, name: &str, value: i64, is_called: bool) -> Result<i64, StorageError> {
+        self.update_sequence(name, |sequence| {
+            if sequence.set_value(value, is_called) {
+                Ok(value)
+            } else {
+                Err(format!(
+                    "Value {} is out of bounds for {} ({}..{})",
+                    value, name, sequence.min_value, sequence.max_value
+                ))
+            }
+        })
+    }
+
+    /// Move a sequence past a value that was stored without taking it from
+    /// the sequence
+    pub fn skip_sequence_past(&self, name: &str, value: i64) -> Result<(), StorageError> {
+        self.update_sequence(name, |sequence| {
+            sequence.skip_past(value);
+            Ok(())
+        })
+    }
+
     /// Get the row count for a table
+    #[allow(dead_code)]
     pub fn get_row_count(&self, table_name: &str) -> Result<usize, StorageError> {
         let tables = self.tables.read().map_err(|e| {
             StorageError::ConcurrencyError(format!("Failed to acquire read lock: {}", e))
@@ -302,6 +817,104 @@ impl Database {
     }
 }
 
+/// Check that each foreign key of a row being inserted into `table` refers
+/// to an existing key
+///
+/// A row may refer to itself or to `pending` rows inserted along with it when
+/// the foreign key refers to the same table.
+fn check_foreign_keys(
+    tables: &HashMap<String, Table>,
+    table: &Table,
+    row: &Row,
+    pending: &[Row],
+) -> Result<(), StorageError> {
+    let schema = &table.metadata.schema;
+    for foreign_key in &schema.foreign_keys {
+        let values = match schema.values_of(row, &foreign_key.columns) {
+            // Rows with a NULL in the key are not checked
+            Some(values) if values.iter().all(|value| !value.is_null()) => values,
+            _ => continue,
+        };
+
+        let parent = tables
+            .get(&foreign_key.table)
+            .ok_or_else(|| StorageError::TableNotFound(foreign_key.table.clone()))?;
+        let found = if foreign_key.table == table.metadata.name {
+            let mut candidates = pending.to_vec();
+            candidates.push(row.clone());
+            parent.contains_key(&foreign_key.referenced, &values, &candidates)
+        } else {
+            parent.contains_key(&foreign_key.referenced, &values, &[])
+        };
+
+        if !found {
+            return Err(StorageError::ForeignKeyViolation(format!(
+                "{}({}) references {}({}), which has no key ({})",
+                table.metadata.name,
+                foreign_key.columns.join(", "),
+                foreign_key.table,
+                foreign_key.referenced.join(", "),
+                values
+                    .iter()
+                    .map(|value| value.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Check that no foreign key refers to a key that a row removed from
+/// `table`, or updated in it, had, unless another row of the table has it
+fn check_references_kept(
+    tables: &HashMap<String, Table>,
+    table: &Table,
+    removed: &Row,
+) -> Result<(), StorageError> {
+    let name = &table.metadata.name;
+    for child in tables.values() {
+        for foreign_key in child
+            .metadata
+            .schema
+            .foreign_keys
+            .iter()
+            .filter(|foreign_key| &foreign_key.table == name)
+        {
+            let values = match table
+                .metadata
+                .schema
+                .values_of(removed, &foreign_key.referenced)
+            {
+                Some(values) if values.iter().all(|value| !value.is_null()) => values,
+                _ => continue,
+            };
+            if table.contains_key(&foreign_key.referenced, &values, &[]) {
+                continue;
+            }
+            if child
+                .rows
+                .iter()
+                .any(|stored| child.has_key(stored, &foreign_key.columns, &values))
+            {
+                return Err(StorageError::ForeignKeyViolation(format!(
+                    "{}({}) still references key ({}) of {}({})",
+                    child.metadata.name,
+                    foreign_key.columns.join(", "),
+                    values
+                        .iter()
+                        .map(|value| value.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    foreign_key.table,
+                    foreign_key.referenced.join(", ")
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
 /// For backward compatibility with existing code
 #[allow(dead_code)]
 pub type MemoryStorage = Database;
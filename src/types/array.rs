@@ -0,0 +1,114 @@
+//! Text form of arrays
+//!
+//! Arrays convert to and from text in the PostgreSQL style: elements in
+//! braces separated by commas, `{1,2,3}`. Elements that are empty, contain
+//! special characters or spell NULL are double-quoted, with `"` and `\`
+//! escaped by a backslash; an unquoted NULL is a NULL element.
+
+use super::{DataType, TypeError, Value};
+
+/// Format array elements as `{a,b,...}`
+pub fn format(items: &[Value]) -> Result<String, TypeError> {
+    let mut elements = Vec::new();
+    for item in items {
+        elements.push(match item.as_type(&DataType::Text)? {
+            Value::Null => "NULL".to_string(),
+            // Nested arrays are already in braces
+            Value::Text(text) if matches!(item, Value::Array(_)) => text,
+            Value::Text(text) => quote(&text),
+            other => other.to_string(),
+        });
+    }
+    Ok(format!("{{{}}}", elements.join(",")))
+}
+
+/// Quote an element if it would otherwise be read back differently
+fn quote(text: &str) -> String {
+    let plain = !text.is_empty()
+        && !text.eq_ignore_ascii_case("NULL")
+        && !text
+            .chars()
+            .any(|c| c.is_whitespace() || matches!(c, ',' | '{' | '}' | '"' | '\\'));
+    if plain {
+        return text.to_string();
+    }
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    format!("\"{}\"", escaped)
+}
+
+/// Parse `{a,b,...}` into its elements: TEXT values, NULL for an unquoted
+/// NULL, and nested arrays as the TEXT of their braces
+pub fn parse(text: &str) -> Result<Vec<Value>, TypeError> {
+    let error = || TypeError::ConversionError(format!("Malformed array literal '{}'", text));
+    let inner = text
+        .trim()
+        .strip_prefix('{')
+        .and_then(|rest| rest.strip_suffix('}'))
+        .ok_or_else(error)?;
+    if inner.trim().is_empty() {
+        return Ok(vec![]);
+    }
+
+    let mut items = Vec::new();
+    let mut chars = inner.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+
+        let item = match chars.peek() {
+            Some('"') => {
+                chars.next();
+                let mut element = String::new();
+                loop {
+                    match chars.next().ok_or_else(error)? {
+                        '"' => break,
+                        '\\' => element.push(chars.next().ok_or_else(error)?),
+                        c => element.push(c),
+                    }
+                }
+                Value::Text(element)
+            }
+            Some('{') => {
+                let mut element = String::new();
+                let mut depth = 0;
+                for c in chars.by_ref() {
+                    element.push(c);
+                    match c {
+                        '{' => depth += 1,
+                        '}' => depth -= 1,
+                        _ => {}
+                    }
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                if depth != 0 {
+                    return Err(error());
+                }
+                Value::Text(element)
+            }
+            _ => {
+                let mut element = String::new();
+                while let Some(c) = chars.next_if(|c| !matches!(c, ',' | '{' | '}' | '"')) {
+                    element.push(c);
+                }
+                let element = element.trim();
+                if element.is_empty() {
+                    return Err(error());
+                }
+                if element.eq_ignore_ascii_case("NULL") {
+                    Value::Null
+                } else {
+                    Value::Text(element.to_string())
+                }
+            }
+        };
+        items.push(item);
+
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.next() {
+            Some(',') => continue,
+            None => return Ok(items),
+            Some(_) => return Err(error()),
+        }
+    }
+}
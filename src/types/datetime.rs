@@ -0,0 +1,880 @@
+//! Date and time values: DATE, TIME, TIMESTAMP, TIMESTAMP WITH TIME ZONE
+//! and INTERVAL
+//!
+//! Timestamps with a time zone are normalised to UTC when they are parsed.
+//! All values have microsecond precision.
+
+use super::{ArithmeticOperator, DataType, TypeError, Value};
+use chrono::{
+    DateTime, Months, NaiveDate, NaiveDateTime, NaiveTime, SubsecRound, TimeDelta, Timelike, Utc,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+};
+
+const MICROS_PER_SECOND: i64 = 1_000_000;
+const MICROS_PER_DAY: i64 = 86_400 * MICROS_PER_SECOND;
+
+/// Formats accepted for timestamps, most specific first
+const TIMESTAMP_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+/// A span of time, kept as separate months, days and microseconds
+///
+/// Months and days do not have a fixed length, so adding `1 month` to
+/// January 31st gives the last day of February. When intervals are compared
+/// a month counts as 30 days.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Interval {
+    /// Whole months (a year is 12 months)
+    pub months: i32,
+    /// Whole days
+    pub days: i32,
+    /// Microseconds
+    pub micros: i64,
+}
+
+impl Interval {
+    /// Parse an interval such as `'1 day'`, `'2 hours 30 minutes'`,
+    /// `'1 year 2 months 3 days 04:05:06'` or `'3 days ago'`
+    ///
+    /// Fractional amounts spill into the smaller units, so `'1.5 days'` is
+    /// 1 day 12 hours. Text that is not an interval is a conversion error,
+    /// while an interval too large to store is out of range.
+    pub fn parse(text: &str) -> Result<Interval, TypeError> {
+        let error = || TypeError::ConversionError(format!("Cannot convert '{}' to INTERVAL", text));
+        let out_of_range = || TypeError::ArithmeticError("INTERVAL out of range".to_string());
+
+        let mut tokens: Vec<&str> = text.split_whitespace().collect();
+        let ago = tokens.last().is_some_and(|t| t.eq_ignore_ascii_case("ago"));
+        if ago {
+            tokens.pop();
+        }
+        if tokens.is_empty() {
+            return Err(error());
+        }
+
+        let mut interval = Interval::default();
+        let mut tokens = tokens.into_iter();
+        while let Some(token) = tokens.next() {
+            let part = if token.contains(':') {
+                parse_clock(token).ok_or_else(error)?
+            } else {
+                let amount = token
+                    .parse::<f64>()
+                    .ok()
+                    .filter(|amount| !amount.is_nan())
+                    .ok_or_else(error)?;
+                let (months, days, micros) = tokens
+                    .next()
+                    .and_then(Interval::unit_size)
+                    .ok_or_else(error)?;
+                Interval::from_parts(amount * months, amount * days, amount * micros)
+                    .ok_or_else(out_of_range)?
+            };
+            interval = interval.checked_add(&part).ok_or_else(out_of_range)?;
+        }
+
+        if ago {
+            interval = interval.checked_neg().ok_or_else(out_of_range)?;
+        }
+        Ok(interval)
+    }
+
+    /// The months, days and microseconds in one of a named unit (`day`,
+    /// `hours`, `mon`, ...)
+    fn unit_size(unit: &str) -> Option<(f64, f64, f64)> {
+        let micros = |scale: i64| Some((0.0, 0.0, scale as f64));
+        match unit.to_lowercase().as_str() {
+            "microsecond" | "microseconds" | "us" => micros(1),
+            "millisecond" | "milliseconds" | "ms" => micros(1_000),
+            "second" | "seconds" | "sec" | "secs" | "s" => micros(MICROS_PER_SECOND),
+            "minute" | "minutes" | "min" | "mins" | "m" => micros(60 * MICROS_PER_SECOND),
+            "hour" | "hours" | "h" => micros(3_600 * MICROS_PER_SECOND),
+            "day" | "days" | "d" => Some((0.0, 1.0, 0.0)),
+            "week" | "weeks" | "w" => Some((0.0, 7.0, 0.0)),
+            "month" | "months" | "mon" | "mons" => Some((1.0, 0.0, 0.0)),
+            "year" | "years" | "y" => Some((12.0, 0.0, 0.0)),
+            "decade" | "decades" => Some((120.0, 0.0, 0.0)),
+            "century" | "centuries" => Some((1_200.0, 0.0, 0.0)),
+            "millennium" | "millennia" => Some((12_000.0, 0.0, 0.0)),
+            _ => None,
+        }
+    }
+
+    /// Build an interval from possibly fractional parts, carrying fractions
+    /// of a month into days (30 per month) and fractions of a day into
+    /// microseconds
+    fn from_parts(months: f64, days: f64, micros: f64) -> Option<Interval> {
+        let whole_months = months.trunc();
+        let days = days + (months - whole_months) * 30.0;
+        let whole_days = days.trunc();
+        let micros = (micros + (days - whole_days) * MICROS_PER_DAY as f64).round();
+
+        let fits = |x: f64, min: f64, max: f64| x.is_finite() && x >= min && x < max;
+        let in_range = fits(whole_months, i32::MIN as f64, i32::MAX as f64)
+            && fits(whole_days, i32::MIN as f64, i32::MAX as f64)
+            && fits(micros, i64::MIN as f64, i64::MAX as f64);
+        in_range.then_some(Interval {
+            months: whole_months as i32,
+            days: whole_days as i32,
+            micros: micros as i64,
+        })
+    }
+
+    /// Add two intervals part by part
+    pub fn checked_add(&self, other: &Interval) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_add(other.months)?,
+            days: self.days.checked_add(other.days)?,
+            micros: self.micros.checked_add(other.micros)?,
+        })
+    }
+
+    /// Negate every part of the interval
+    pub fn checked_neg(&self) -> Option<Interval> {
+        Some(Interval {
+            months: self.months.checked_neg()?,
+            days: self.days.checked_neg()?,
+            micros: self.micros.checked_neg()?,
+        })
+    }
+
+    /// Multiply the interval by a number, spilling fractions into smaller units
+    pub fn checked_mul(&self, factor: f64) -> Option<Interval> {
+        Interval::from_parts(
+            self.months as f64 * factor,
+            self.days as f64 * factor,
+            self.micros as f64 * factor,
+        )
+    }
+
+    /// Length of the interval in microseconds, counting a month as 30 days
+    fn total_micros(&self) -> i128 {
+        (self.months as i128 * 30 + self.days as i128) * MICROS_PER_DAY as i128
+            + self.micros as i128
+    }
+
+    /// Order intervals by length, counting a month as 30 days
+    pub fn cmp_length(&self, other: &Interval) -> Ordering {
+        self.total_micros().cmp(&other.total_micros())
+    }
+
+    /// Length of the interval in seconds, counting a month as 30 days
+    pub fn seconds(&self) -> f64 {
+        self.total_micros() as f64 / MICROS_PER_SECOND as f64
+    }
+
+    /// The interval between two timestamps, as days and microseconds
+    pub fn between(end: &NaiveDateTime, start: &NaiveDateTime) -> Option<Interval> {
+        let micros = end.signed_duration_since(*start).num_microseconds()?;
+        Some(Interval {
+            months: 0,
+            days: i32::try_from(micros / MICROS_PER_DAY).ok()?,
+            micros: micros % MICROS_PER_DAY,
+        })
+    }
+}
+
+impl Display for Interval {
+    /// Formats as e.g. `1 year 2 months 3 days 04:05:06.5`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        for (amount, unit) in [
+            (self.months / 12, "year"),
+            (self.months % 12, "month"),
+            (self.days, "day"),
+        ] {
+            match amount {
+                0 => {}
+                1 | -1 => parts.push(format!("{} {}", amount, unit)),
+                _ => parts.push(format!("{} {}s", amount, unit)),
+            }
+        }
+
+        if self.micros != 0 || parts.is_empty() {
+            let sign = if self.micros < 0 { "-" } else { "" };
+            let micros = self.micros.unsigned_abs();
+            let seconds = micros / MICROS_PER_SECOND as u64;
+            let mut clock = format!(
+                "{}{:02}:{:02}:{:02}",
+                sign,
+                seconds / 3_600,
+                seconds / 60 % 60,
+                seconds % 60
+            );
+            let fraction = micros % MICROS_PER_SECOND as u64;
+            if fraction != 0 {
+                clock.push_str(format!(".{:06}", fraction).trim_end_matches('0'));
+            }
+            parts.push(clock);
+        }
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+/// Parse `[-]HH:MM[:SS[.ffffff]]` as a number of microseconds
+fn parse_clock(text: &str) -> Option<Interval> {
+    let (negative, clock) = match text.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let mut fields = clock.split(':');
+    let hours = fields.next()?.parse::<u32>().ok()? as f64;
+    let minutes = fields.next()?.parse::<u32>().ok()? as f64;
+    let seconds = match fields.next() {
+        Some(seconds) => seconds.parse::<f64>().ok().filter(|s| *s >= 0.0)?,
+        None => 0.0,
+    };
+    if fields.next().is_some() {
+        return None;
+    }
+
+    let micros = ((hours * 60.0 + minutes) * 60.0 + seconds) * MICROS_PER_SECOND as f64;
+    Interval::from_parts(0.0, 0.0, if negative { -micros } else { micros })
+}
+
+/// Parse a date in `YYYY-MM-DD` form
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text.trim(), "%Y-%m-%d").ok()
+}
+
+/// Parse a time of day in `HH:MM[:SS[.ffffff]]` form
+pub fn parse_time(text: &str) -> Option<NaiveTime> {
+    let text = text.trim();
+    NaiveTime::parse_from_str(text, "%H:%M:%S%.f")
+        .or_else(|_| NaiveTime::parse_from_str(text, "%H:%M"))
+        .ok()
+        .map(|time| time.trunc_subsecs(6))
+}
+
+/// Parse a timestamp without time zone; a bare date means midnight
+pub fn parse_timestamp(text: &str) -> Option<NaiveDateTime> {
+    let text = text.trim();
+    TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .or_else(|| Some(parse_date(text)?.and_time(NaiveTime::MIN)))
+        .map(|timestamp| timestamp.trunc_subsecs(6))
+}
+
+/// Parse a timestamp with an optional UTC offset (`+02`, `+02:00`, `Z`),
+/// converting it to UTC; a timestamp without an offset is taken to be UTC
+pub fn parse_timestamptz(text: &str) -> Option<DateTime<Utc>> {
+    let text = text.trim();
+    if let Some(local) = text.strip_suffix(['Z', 'z']) {
+        return parse_timestamp(local).map(|timestamp| timestamp.and_utc());
+    }
+
+    TIMESTAMP_FORMATS
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(text, &format!("{}%#z", format)).ok())
+        .map(|timestamp| timestamp.with_timezone(&Utc).trunc_subsecs(6))
+        .or_else(|| parse_timestamp(text).map(|timestamp| timestamp.and_utc()))
+}
+
+/// Format a time of day, with fractional seconds only when present
+pub fn format_time(time: &NaiveTime) -> String {
+    format!(
+        "{}{}",
+        time.format("%H:%M:%S"),
+        format_fraction(time.nanosecond())
+    )
+}
+
+/// Format a timestamp, with fractional seconds only when present
+pub fn format_timestamp(timestamp: &NaiveDateTime) -> String {
+    format!(
+        "{}{}",
+        timestamp.format("%Y-%m-%d %H:%M:%S"),
+        format_fraction(timestamp.nanosecond())
+    )
+}
+
+/// Fractional seconds without trailing zeros (`.5`), or nothing when zero
+fn format_fraction(nanos: u32) -> String {
+    let micros = nanos / 1_000;
+    if micros == 0 {
+        String::new()
+    } else {
+        format!(".{:06}", micros).trim_end_matches('0').to_string()
+    }
+}
+
+/// Format a UTC timestamp with its offset
+pub fn format_timestamptz(timestamp: &DateTime<Utc>) -> String {
+    format!("{}+00", format_timestamp(&timestamp.naive_utc()))
+}
+
+/// The current time, to microsecond precision
+pub fn now() -> DateTime<Utc> {
+    Utc::now().trunc_subsecs(6)
+}
+
+/// Whether a value is a date, time, timestamp or interval
+pub fn is_temporal(value: &Value) -> bool {
+    value
+        .data_type()
+        .is_some_and(|data_type| data_type.is_temporal())
+}
+
+/// Whether a value is a point in time that can be converted to a timestamp
+fn is_datetime(value: &Value) -> bool {
+    matches!(
+        value,
+        Value::Date(_) | Value::Timestamp(_) | Value::TimestampTz(_)
+    )
+}
+
+/// Shift a date, time or timestamp by an interval
+///
+/// Months are added first (clamping to the end of the month), then days,
+/// then microseconds. A DATE becomes a TIMESTAMP; a TIME ignores the days
+/// and months and wraps around midnight.
+fn shift(value: &Value, interval: &Interval) -> Option<Value> {
+    let shift_datetime = |timestamp: NaiveDateTime| {
+        let months = Months::new(interval.months.unsigned_abs());
+        let timestamp = if interval.months >= 0 {
+            timestamp.checked_add_months(months)?
+        } else {
+            timestamp.checked_sub_months(months)?
+        };
+        timestamp
+            .checked_add_signed(TimeDelta::try_days(interval.days as i64)?)?
+            .checked_add_signed(TimeDelta::microseconds(interval.micros))
+    };
+
+    match value {
+        Value::Date(date) => shift_datetime(date.and_time(NaiveTime::MIN)).map(Value::Timestamp),
+        Value::Timestamp(timestamp) => shift_datetime(*timestamp).map(Value::Timestamp),
+        Value::TimestampTz(timestamp) => shift_datetime(timestamp.naive_utc())
+            .map(|timestamp| Value::TimestampTz(timestamp.and_utc())),
+        Value::Time(time) => Some(Value::Time(
+            time.overflowing_add_signed(TimeDelta::microseconds(interval.micros))
+                .0,
+        )),
+        _ => None,
+    }
+}
+
+/// The point in time of a date or timestamp, in UTC
+fn to_utc(value: &Value) -> Option<NaiveDateTime> {
+    match value {
+        Value::Date(date) => Some(date.and_time(NaiveTime::MIN)),
+        Value::Timestamp(timestamp) => Some(*timestamp),
+        Value::TimestampTz(timestamp) => Some(timestamp.naive_utc()),
+        _ => None,
+    }
+}
+
+/// Arithmetic involving date/time values
+///
+/// Returns None when neither operand is a date/time value (or either is
+/// NULL), leaving the operation to numeric arithmetic.
+///
+/// - DATE ± INTEGER days is a DATE, and DATE - DATE is INTEGER days
+/// - DATE/TIME/TIMESTAMP ± INTERVAL keeps the type (DATE becomes TIMESTAMP)
+/// - TIMESTAMP - TIMESTAMP and TIME - TIME are INTERVALs
+/// - INTERVALs can be added, subtracted, multiplied and divided by numbers
+pub(super) fn arithmetic(
+    left: &Value,
+    op: &ArithmeticOperator,
+    right: &Value,
+) -> Option<Result<Value, TypeError>> {
+    if left.is_null() || right.is_null() || !(is_temporal(left) || is_temporal(right)) {
+        return None;
+    }
+
+    let subtract = matches!(op, ArithmeticOperator::Subtract);
+    let result = match (left, op, right) {
+        (
+            Value::Date(date),
+            ArithmeticOperator::Add | ArithmeticOperator::Subtract,
+            Value::Integer(days),
+        )
+        | (Value::Integer(days), ArithmeticOperator::Add, Value::Date(date)) => {
+            let days = if subtract {
+                days.checked_neg()
+            } else {
+                Some(*days)
+            };
+            days.and_then(TimeDelta::try_days)
+                .and_then(|days| date.checked_add_signed(days))
+                .map(Value::Date)
+                .ok_or("DATE")
+        }
+        (Value::Date(end), ArithmeticOperator::Subtract, Value::Date(start)) => {
+            Ok(Value::Integer(end.signed_duration_since(*start).num_days()))
+        }
+
+        (
+            value,
+            ArithmeticOperator::Add | ArithmeticOperator::Subtract,
+            Value::Interval(interval),
+        )
+        | (Value::Interval(interval), ArithmeticOperator::Add, value)
+            if is_datetime(value) || matches!(value, Value::Time(_)) =>
+        {
+            let interval = if subtract {
+                interval.checked_neg()
+            } else {
+                Some(*interval)
+            };
+            interval
+                .and_then(|interval| shift(value, &interval))
+                .ok_or("TIMESTAMP")
+        }
+
+        (Value::Time(end), ArithmeticOperator::Subtract, Value::Time(start)) => Interval::between(
+            &NaiveDate::MIN.and_time(*end),
+            &NaiveDate::MIN.and_time(*start),
+        )
+        .map(Value::Interval)
+        .ok_or("INTERVAL"),
+        (end, ArithmeticOperator::Subtract, start) if is_datetime(end) && is_datetime(start) => {
+            to_utc(end)
+                .zip(to_utc(start))
+                .and_then(|(end, start)| Interval::between(&end, &start))
+                .map(Value::Interval)
+                .ok_or("INTERVAL")
+        }
+
+        (
+            Value::Interval(a),
+            ArithmeticOperator::Add | ArithmeticOperator::Subtract,
+            Value::Interval(b),
+        ) => {
+            let b = if subtract { b.checked_neg() } else { Some(*b) };
+            b.and_then(|b| a.checked_add(&b))
+                .map(Value::Interval)
+                .ok_or("INTERVAL")
+        }
+        (
+            Value::Interval(interval),
+            ArithmeticOperator::Multiply | ArithmeticOperator::Divide,
+            number @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_)),
+        )
+        | (
+            number @ (Value::Integer(_) | Value::Float(_) | Value::Decimal(_)),
+            ArithmeticOperator::Multiply,
+            Value::Interval(interval),
+        ) => {
+            let factor = match number.as_type(&DataType::Float) {
+                Ok(Value::Float(factor)) => factor,
+                _ => return Some(Err(cannot_apply(left, op, right))),
+            };
+            let factor = if matches!(op, ArithmeticOperator::Divide) {
+                if factor == 0.0 {
+                    return Some(Err(TypeError::ArithmeticError(
+                        "division by zero".to_string(),
+                    )));
+                }
+                1.0 / factor
+            } else {
+                factor
+            };
+            interval
+                .checked_mul(factor)
+                .map(Value::Interval)
+                .ok_or("INTERVAL")
+        }
+
+        _ => return Some(Err(cannot_apply(left, op, right))),
+    };
+
+    Some(
+        result
+            .map_err(|data_type| TypeError::ArithmeticError(format!("{} out of range", data_type))),
+    )
+}
+
+/// Result type of arithmetic involving date/time types, mirroring
+/// `arithmetic`; None if neither operand is a date/time type
+pub(super) fn result_type(
+    left: &DataType,
+    op: &ArithmeticOperator,
+    right: &DataType,
+) -> Option<DataType> {
+    if !(left.is_temporal() || right.is_temporal()) {
+        return None;
+    }
+
+    let datetime = |t: &DataType| {
+        matches!(
+            t,
+            DataType::Date | DataType::Timestamp | DataType::TimestampTz
+        )
+    };
+    let data_type = match (left, op, right) {
+        (DataType::Date, ArithmeticOperator::Subtract, DataType::Date) => DataType::Integer,
+        (DataType::Date, _, DataType::Integer) | (DataType::Integer, _, DataType::Date) => {
+            DataType::Date
+        }
+        (DataType::Date, _, DataType::Interval) | (DataType::Interval, _, DataType::Date) => {
+            DataType::Timestamp
+        }
+        (DataType::Interval, _, DataType::Interval) => DataType::Interval,
+        (t, _, DataType::Interval) | (DataType::Interval, _, t)
+            if datetime(t) || *t == DataType::Time =>
+        {
+            t.clone()
+        }
+        (DataType::Interval, _, _) | (_, _, DataType::Interval) => DataType::Interval,
+        (a, ArithmeticOperator::Subtract, b)
+            if (datetime(a) && datetime(b)) || (*a == DataType::Time && *b == DataType::Time) =>
+        {
+            DataType::Interval
+        }
+        (t, _, _) if t.is_temporal() => t.clone(),
+        (_, _, t) => t.clone(),
+    };
+    Some(data_type)
+}
+
+fn cannot_apply(left: &Value, op: &ArithmeticOperator, right: &Value) -> TypeError {
+    TypeError::ArithmeticError(format!("Cannot apply {} to {} and {}", op, left, right))
+}
+
+/// Compare two date/time values of compatible types
+///
+/// Dates and timestamps compare as points in time (a timestamp without time
+/// zone is taken to be UTC). Returns None for incompatible types.
+pub(super) fn compare(left: &Value, right: &Value) -> Option<Ordering> {
+    match (left, right) {
+        (Value::Time(a), Value::Time(b)) => Some(a.cmp(b)),
+        (Value::Interval(a), Value::Interval(b)) => Some(a.cmp_length(b)),
+        (Value::Date(a), Value::Date(b)) => Some(a.cmp(b)),
+        (a, b) => Some(to_utc(a)?.cmp(&to_utc(b)?)),
+    }
+}
+
+/// Convert a value to or from a date/time type; None if this conversion
+/// does not involve one
+pub(super) fn convert(value: &Value, data_type: &DataType) -> Option<Result<Value, TypeError>> {
+    let converted = match (value, data_type) {
+        (Value::Text(s), DataType::Date) => parse_date(s).map(Value::Date),
+        (Value::Text(s), DataType::Time) => parse_time(s).map(Value::Time),
+        (Value::Text(s), DataType::Timestamp) => parse_timestamp(s).map(Value::Timestamp),
+        (Value::Text(s), DataType::TimestampTz) => parse_timestamptz(s).map(Value::TimestampTz),
+        (Value::Text(s), DataType::Interval) => {
+            return Some(Interval::parse(s).map(Value::Interval));
+        }
+
+        (Value::Date(date), DataType::Text) => Some(Value::Text(date.to_string())),
+        (Value::Time(time), DataType::Text) => Some(Value::Text(format_time(time))),
+        (Value::Timestamp(timestamp), DataType::Text) => {
+            Some(Value::Text(format_timestamp(timestamp)))
+        }
+        (Value::TimestampTz(timestamp), DataType::Text) => {
+            Some(Value::Text(format_timestamptz(timestamp)))
+        }
+        (Value::Interval(interval), DataType::Text) => Some(Value::Text(interval.to_string())),
+
+        (value, DataType::Date) if is_datetime(value) => {
+            to_utc(value).map(|t| Value::Date(t.date()))
+        }
+        (value, DataType::Time) if is_datetime(value) => {
+            to_utc(value).map(|t| Value::Time(t.time()))
+        }
+        (value, DataType::Timestamp) if is_datetime(value) => to_utc(value).map(Value::Timestamp),
+        (value, DataType::TimestampTz) if is_datetime(value) => {
+            to_utc(value).map(|t| Value::TimestampTz(t.and_utc()))
+        }
+
+        _ => return None,
+    };
+
+    Some(converted.ok_or_else(|| {
+        TypeError::ConversionError(format!("Cannot convert {} to {}", value, data_type))
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> Value {
+        Value::Date(parse_date(text).unwrap())
+    }
+
+    fn timestamp(text: &str) -> Value {
+        Value::Timestamp(parse_timestamp(text).unwrap())
+    }
+
+    fn interval(text: &str) -> Value {
+        Value::Interval(Interval::parse(text).unwrap())
+    }
+
+    fn apply(left: &Value, op: ArithmeticOperator, right: &Value) -> Result<Value, TypeError> {
+        arithmetic(left, &op, right).expect("temporal arithmetic")
+    }
+
+    #[test]
+    fn parse_interval_units() {
+        let parsed = Interval::parse("1 year 2 months 3 days 04:05:06.5").unwrap();
+        assert_eq!(parsed.months, 14);
+        assert_eq!(parsed.days, 3);
+        assert_eq!(
+            parsed.micros,
+            (4 * 3_600 + 5 * 60 + 6) * MICROS_PER_SECOND + 500_000
+        );
+
+        let ago = Interval::parse("3 days 2 hours ago").unwrap();
+        assert_eq!((ago.days, ago.micros), (-3, -2 * 3_600 * MICROS_PER_SECOND));
+
+        assert!(Interval::parse("").is_err());
+        assert!(Interval::parse("ago").is_err());
+        assert!(Interval::parse("1 fortnight").is_err());
+        assert!(Interval::parse("1:2:3:4").is_err());
+    }
+
+    #[test]
+    fn parse_interval_fractions_spill_into_smaller_units() {
+        let parsed = Interval::parse("1.5 days").unwrap();
+        assert_eq!((parsed.days, parsed.micros), (1, MICROS_PER_DAY / 2));
+
+        let parsed = Interval::parse("1.5 months").unwrap();
+        assert_eq!((parsed.months, parsed.days, parsed.micros), (1, 15, 0));
+    }
+
+    #[test]
+    fn parse_interval_out_of_range() {
+        for text in [
+            "3000000000 months",
+            "9999999999 years",
+            "2147483647 days 2147483647 days",
+            "1e400 seconds",
+            "-2147483648 months ago",
+        ] {
+            let error = Interval::parse(text).unwrap_err().to_string();
+            assert!(
+                error.contains("INTERVAL out of range"),
+                "{}: {}",
+                text,
+                error
+            );
+        }
+        let error = Interval::parse("nan days").unwrap_err().to_string();
+        assert!(error.contains("Cannot convert"), "{}", error);
+    }
+
+    #[test]
+    fn display_interval() {
+        let parsed = Interval::parse("1 year 2 months 3 days 04:05:06.5").unwrap();
+        assert_eq!(parsed.to_string(), "1 year 2 months 3 days 04:05:06.5");
+        assert_eq!(Interval::default().to_string(), "00:00:00");
+        assert_eq!(Interval::parse("-1 hour").unwrap().to_string(), "-01:00:00");
+        assert_eq!(Interval::parse("1 day").unwrap().to_string(), "1 day");
+    }
+
+    #[test]
+    fn interval_checked_operations() {
+        let max = Interval {
+            months: i32::MAX,
+            days: 0,
+            micros: 0,
+        };
+        let one_month = Interval::parse("1 month").unwrap();
+        assert_eq!(max.checked_add(&one_month), None);
+        assert_eq!(
+            Interval {
+                months: i32::MIN,
+                days: 0,
+                micros: 0
+            }
+            .checked_neg(),
+            None
+        );
+
+        let scaled = one_month.checked_mul(1.5).unwrap();
+        assert_eq!((scaled.months, scaled.days), (1, 15));
+        let halved = Interval::parse("1 day").unwrap().checked_mul(0.5).unwrap();
+        assert_eq!((halved.days, halved.micros), (0, MICROS_PER_DAY / 2));
+        assert_eq!(max.checked_mul(2.0), None);
+    }
+
+    #[test]
+    fn interval_length_counts_a_month_as_30_days() {
+        let month = Interval::parse("1 month").unwrap();
+        assert_eq!(
+            month.cmp_length(&Interval::parse("30 days").unwrap()),
+            Ordering::Equal
+        );
+        assert_eq!(
+            month.cmp_length(&Interval::parse("29 days 23 hours").unwrap()),
+            Ordering::Greater
+        );
+        assert_eq!(Interval::parse("1 minute").unwrap().seconds(), 60.0);
+    }
+
+    #[test]
+    fn adding_months_clamps_to_the_end_of_the_month() {
+        let add = |start: &str, amount: &str| {
+            apply(&date(start), ArithmeticOperator::Add, &interval(amount)).unwrap()
+        };
+        assert_eq!(
+            add("2024-01-31", "1 month"),
+            timestamp("2024-02-29 00:00:00")
+        );
+        assert_eq!(
+            add("2023-01-31", "1 month"),
+            timestamp("2023-02-28 00:00:00")
+        );
+        assert_eq!(
+            add("2024-02-29", "1 year"),
+            timestamp("2025-02-28 00:00:00")
+        );
+        // Months are added before days
+        assert_eq!(
+            add("2024-01-31", "1 month 1 day"),
+            timestamp("2024-03-01 00:00:00")
+        );
+
+        let back = apply(
+            &date("2024-03-31"),
+            ArithmeticOperator::Subtract,
+            &interval("1 month"),
+        );
+        assert_eq!(back.unwrap(), timestamp("2024-02-29 00:00:00"));
+    }
+
+    #[test]
+    fn date_and_day_arithmetic() {
+        assert_eq!(
+            apply(
+                &date("2024-02-28"),
+                ArithmeticOperator::Add,
+                &Value::Integer(2)
+            )
+            .unwrap(),
+            date("2024-03-01")
+        );
+        assert_eq!(
+            apply(
+                &Value::Integer(1),
+                ArithmeticOperator::Add,
+                &date("2024-12-31")
+            )
+            .unwrap(),
+            date("2025-01-01")
+        );
+        assert_eq!(
+            apply(
+                &date("2024-03-01"),
+                ArithmeticOperator::Subtract,
+                &date("2024-02-28")
+            )
+            .unwrap(),
+            Value::Integer(2)
+        );
+        assert!(
+            apply(
+                &Value::Date(NaiveDate::MAX),
+                ArithmeticOperator::Add,
+                &Value::Integer(1)
+            )
+            .is_err()
+        );
+        assert!(
+            apply(
+                &date("2024-01-01"),
+                ArithmeticOperator::Add,
+                &Value::Integer(i64::MIN)
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn timestamp_differences_are_days_and_time() {
+        let difference = apply(
+            &timestamp("2024-03-02 06:00:00"),
+            ArithmeticOperator::Subtract,
+            &timestamp("2024-02-28 00:00:00"),
+        )
+        .unwrap();
+        assert_eq!(difference, interval("3 days 06:00:00"));
+    }
+
+    #[test]
+    fn time_wraps_around_midnight() {
+        let time = Value::Time(parse_time("23:00:00").unwrap());
+        assert_eq!(
+            apply(&time, ArithmeticOperator::Add, &interval("2 hours 1 day")).unwrap(),
+            Value::Time(parse_time("01:00:00").unwrap())
+        );
+    }
+
+    #[test]
+    fn interval_scaling() {
+        assert_eq!(
+            apply(
+                &interval("1 hour"),
+                ArithmeticOperator::Multiply,
+                &Value::Integer(3)
+            )
+            .unwrap(),
+            interval("3 hours")
+        );
+        assert_eq!(
+            apply(
+                &interval("1 day"),
+                ArithmeticOperator::Divide,
+                &Value::Integer(4)
+            )
+            .unwrap(),
+            interval("6 hours")
+        );
+        assert!(
+            apply(
+                &interval("1 day"),
+                ArithmeticOperator::Divide,
+                &Value::Integer(0)
+            )
+            .is_err()
+        );
+        assert!(
+            apply(
+                &interval("1 day"),
+                ArithmeticOperator::Multiply,
+                &interval("1 day")
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn non_temporal_arithmetic_is_left_alone() {
+        let op = ArithmeticOperator::Add;
+        assert!(arithmetic(&Value::Integer(1), &op, &Value::Integer(2)).is_none());
+        assert!(arithmetic(&date("2024-01-01"), &op, &Value::Null).is_none());
+    }
+
+    #[test]
+    fn temporal_result_types() {
+        use ArithmeticOperator::{Add, Subtract};
+        assert_eq!(
+            result_type(&DataType::Date, &Add, &DataType::Interval),
+            Some(DataType::Timestamp)
+        );
+        assert_eq!(
+            result_type(&DataType::Date, &Subtract, &DataType::Date),
+            Some(DataType::Integer)
+        );
+        assert_eq!(
+            result_type(&DataType::TimestampTz, &Subtract, &DataType::Timestamp),
+            Some(DataType::Interval)
+        );
+        assert_eq!(
+            result_type(&DataType::Integer, &Add, &DataType::Integer),
+            None
+        );
+    }
+}
@@ -0,0 +1,548 @@
+//! Exact decimal numbers for DECIMAL / NUMERIC
+//!
+//! A decimal is an `i128` mantissa and a scale (the number of digits after
+//! the decimal point), giving up to 38 significant digits. Addition,
+//! subtraction and multiplication are exact; division and rescaling round
+//! with an explicit `RoundingMode`.
+
+use super::TypeError;
+use serde::{Deserialize, Serialize};
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    str::FromStr,
+};
+
+/// Maximum number of significant digits
+pub const MAX_PRECISION: u32 = 38;
+
+/// Minimum number of digits after the point in the result of a division
+const DIVISION_SCALE: u32 = 16;
+
+/// How to round a decimal when digits are dropped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    /// Round to nearest, ties away from zero (the default)
+    HalfUp,
+    /// Round to nearest, ties toward zero
+    HalfDown,
+    /// Round to nearest, ties to the even neighbour (banker's rounding)
+    HalfEven,
+    /// Round away from zero
+    Up,
+    /// Round toward zero (truncate)
+    Down,
+    /// Round toward positive infinity
+    Ceiling,
+    /// Round toward negative infinity
+    Floor,
+}
+
+impl FromStr for RoundingMode {
+    type Err = TypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().replace(['-', ' '], "_").as_str() {
+            "HALF_UP" => Ok(RoundingMode::HalfUp),
+            "HALF_DOWN" => Ok(RoundingMode::HalfDown),
+            "HALF_EVEN" => Ok(RoundingMode::HalfEven),
+            "UP" => Ok(RoundingMode::Up),
+            "DOWN" | "TRUNCATE" => Ok(RoundingMode::Down),
+            "CEILING" => Ok(RoundingMode::Ceiling),
+            "FLOOR" => Ok(RoundingMode::Floor),
+            _ => Err(TypeError::InvalidValue(
+                "rounding mode".to_string(),
+                s.to_string(),
+            )),
+        }
+    }
+}
+
+/// An exact decimal number: `mantissa * 10^-scale`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+/// 10 to the power `exponent`, if it fits in an i128
+fn pow10(exponent: u32) -> Option<i128> {
+    10i128.checked_pow(exponent)
+}
+
+fn out_of_range() -> TypeError {
+    TypeError::ArithmeticError("DECIMAL out of range".to_string())
+}
+
+impl Decimal {
+    /// Build a decimal, checking it has at most 38 significant digits
+    fn new(mantissa: i128, scale: u32) -> Result<Decimal, TypeError> {
+        let decimal = Decimal { mantissa, scale };
+        if scale > MAX_PRECISION || decimal.digits() > MAX_PRECISION {
+            return Err(out_of_range());
+        }
+        Ok(decimal)
+    }
+
+    /// Number of digits after the decimal point
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Number of significant digits in the mantissa (at least 1)
+    fn digits(&self) -> u32 {
+        self.mantissa.unsigned_abs().checked_ilog10().unwrap_or(0) + 1
+    }
+
+    /// Number of digits before the decimal point
+    pub fn integer_digits(&self) -> u32 {
+        self.digits().saturating_sub(self.scale)
+    }
+
+    /// Check if the value is zero
+    pub fn is_zero(&self) -> bool {
+        self.mantissa == 0
+    }
+
+    /// The same value with `scale` digits after the point, padding with
+    /// zeros or rounding as needed; `scale` may be negative to round to
+    /// tens, hundreds, ... (the result then has scale 0)
+    pub fn round(&self, scale: i32, mode: RoundingMode) -> Result<Decimal, TypeError> {
+        let target = scale.max(0) as u32;
+        if scale >= self.scale as i32 {
+            let factor = pow10(target - self.scale).ok_or_else(out_of_range)?;
+            let mantissa = self.mantissa.checked_mul(factor).ok_or_else(out_of_range)?;
+            return Decimal::new(mantissa, target);
+        }
+
+        // Divide away the dropped digits, rounding the quotient, then scale
+        // back up if rounding to the left of the decimal point
+        let dropped = (self.scale as i32 - scale) as u32;
+        let divisor = match pow10(dropped) {
+            Some(divisor) => divisor,
+            // More digits dropped than an i128 can hold: the result is 0
+            // unless the mode rounds away from zero, which cannot fit
+            None => {
+                return match round_quotient(0, self.mantissa.signum(), i128::MAX, mode) {
+                    0 => Decimal::new(0, target),
+                    _ => Err(out_of_range()),
+                };
+            }
+        };
+        let quotient = round_quotient(
+            self.mantissa / divisor,
+            self.mantissa % divisor,
+            divisor,
+            mode,
+        );
+        let factor = pow10((-scale).max(0) as u32).ok_or_else(out_of_range)?;
+        Decimal::new(
+            quotient.checked_mul(factor).ok_or_else(out_of_range)?,
+            target,
+        )
+    }
+
+    /// Rescale to a column's DECIMAL(precision, scale), rounding half up,
+    /// and check that the integer digits fit
+    pub fn fit(&self, precision: u32, scale: u32) -> Result<Decimal, TypeError> {
+        let rounded = self.round(scale as i32, RoundingMode::HalfUp)?;
+        if rounded.integer_digits() > precision - scale {
+            return Err(TypeError::InvalidValue(
+                format!("DECIMAL({}, {})", precision, scale),
+                format!("{} exceeds the precision", self),
+            ));
+        }
+        Ok(rounded)
+    }
+
+    /// Both mantissas at the larger of the two scales
+    fn align(&self, other: &Decimal) -> Result<(i128, i128, u32), TypeError> {
+        let scale = self.scale.max(other.scale);
+        let a = self.round(scale as i32, RoundingMode::HalfUp)?;
+        let b = other.round(scale as i32, RoundingMode::HalfUp)?;
+        Ok((a.mantissa, b.mantissa, scale))
+    }
+
+    /// Exact addition
+    pub fn checked_add(&self, other: &Decimal) -> Result<Decimal, TypeError> {
+        let (a, b, scale) = self.align(other)?;
+        Decimal::new(a.checked_add(b).ok_or_else(out_of_range)?, scale)
+    }
+
+    /// Exact subtraction
+    pub fn checked_sub(&self, other: &Decimal) -> Result<Decimal, TypeError> {
+        let (a, b, scale) = self.align(other)?;
+        Decimal::new(a.checked_sub(b).ok_or_else(out_of_range)?, scale)
+    }
+
+    /// Exact multiplication; the scale is the sum of the operand scales
+    pub fn checked_mul(&self, other: &Decimal) -> Result<Decimal, TypeError> {
+        let mantissa = self
+            .mantissa
+            .checked_mul(other.mantissa)
+            .ok_or_else(out_of_range)?;
+        let scale = self.scale + other.scale;
+        if scale > MAX_PRECISION {
+            // Drop excess fractional digits rather than failing
+            let excess = pow10(scale - MAX_PRECISION).ok_or_else(out_of_range)?;
+            let mantissa = round_quotient(
+                mantissa / excess,
+                mantissa % excess,
+                excess,
+                RoundingMode::HalfUp,
+            );
+            return Decimal::new(mantissa, MAX_PRECISION);
+        }
+        Decimal::new(mantissa, scale)
+    }
+
+    /// Division rounded with `mode` to at least 16 digits after the point
+    /// (or the larger operand scale), using fewer if the result would not
+    /// fit in 38 digits
+    pub fn checked_div(&self, other: &Decimal, mode: RoundingMode) -> Result<Decimal, TypeError> {
+        if other.is_zero() {
+            return Err(TypeError::ArithmeticError("division by zero".to_string()));
+        }
+
+        let target = DIVISION_SCALE.max(self.scale).max(other.scale);
+        for scale in (0..=target).rev() {
+            // mantissa_a * 10^(scale + scale_b - scale_a) / mantissa_b
+            let shift = scale as i64 + other.scale as i64 - self.scale as i64;
+            let (dividend, divisor) = if shift >= 0 {
+                let factor = pow10(shift as u32);
+                (
+                    factor.and_then(|f| self.mantissa.checked_mul(f)),
+                    Some(other.mantissa),
+                )
+            } else {
+                let factor = pow10((-shift) as u32);
+                (
+                    Some(self.mantissa),
+                    factor.and_then(|f| other.mantissa.checked_mul(f)),
+                )
+            };
+            if let (Some(dividend), Some(divisor)) = (dividend, divisor) {
+                let quotient =
+                    round_quotient(dividend / divisor, dividend % divisor, divisor, mode);
+                if let Ok(result) = Decimal::new(quotient, scale) {
+                    return Ok(result);
+                }
+            }
+        }
+        Err(out_of_range())
+    }
+
+    /// Remainder with the sign of the dividend
+    pub fn checked_rem(&self, other: &Decimal) -> Result<Decimal, TypeError> {
+        if other.is_zero() {
+            return Err(TypeError::ArithmeticError("division by zero".to_string()));
+        }
+        let (a, b, scale) = self.align(other)?;
+        Decimal::new(a % b, scale)
+    }
+
+    /// Compare numerically, ignoring scale (1.0 equals 1.00)
+    pub fn cmp_value(&self, other: &Decimal) -> Ordering {
+        // Compare integer parts, then the fractional parts at a common
+        // scale; neither step can overflow
+        let split = |d: &Decimal| {
+            let unit = pow10(d.scale).unwrap_or(i128::MAX);
+            (d.mantissa / unit, d.mantissa % unit)
+        };
+        let ((a_int, a_frac), (b_int, b_frac)) = (split(self), split(other));
+        let scale = self.scale.max(other.scale);
+        let widen = |frac: i128, from: u32| frac * pow10(scale - from).unwrap_or(1);
+        a_int
+            .cmp(&b_int)
+            .then(widen(a_frac, self.scale).cmp(&widen(b_frac, other.scale)))
+    }
+
+    /// Convert an integer exactly
+    pub fn from_i64(value: i64) -> Decimal {
+        Decimal {
+            mantissa: value as i128,
+            scale: 0,
+        }
+    }
+
+    /// Convert a float using its shortest round-trip representation, so
+    /// `0.1` becomes exactly 0.1
+    pub fn from_f64(value: f64) -> Result<Decimal, TypeError> {
+        if !value.is_finite() {
+            return Err(TypeError::ConversionError(format!(
+                "Cannot convert {} to DECIMAL",
+                value
+            )));
+        }
+        format!("{:?}", value).parse()
+    }
+
+    /// Nearest float
+    pub fn to_f64(self) -> f64 {
+        self.to_string().parse().unwrap_or(f64::NAN)
+    }
+
+    /// Round half up to an integer, if it fits in an i64
+    pub fn to_i64(self) -> Option<i64> {
+        let rounded = self.round(0, RoundingMode::HalfUp).ok()?;
+        i64::try_from(rounded.mantissa).ok()
+    }
+}
+
+/// Round `quotient` (with `remainder` over `divisor`) according to `mode`
+fn round_quotient(quotient: i128, remainder: i128, divisor: i128, mode: RoundingMode) -> i128 {
+    if remainder == 0 {
+        return quotient;
+    }
+
+    // Direction of the exact result away from the truncated quotient
+    let sign = if (remainder < 0) != (divisor < 0) {
+        -1
+    } else {
+        1
+    };
+    let twice = remainder.unsigned_abs().saturating_mul(2);
+    let half = twice.cmp(&divisor.unsigned_abs());
+    let away = match mode {
+        RoundingMode::Up => true,
+        RoundingMode::Down => false,
+        RoundingMode::Ceiling => sign > 0,
+        RoundingMode::Floor => sign < 0,
+        RoundingMode::HalfUp => half != Ordering::Less,
+        RoundingMode::HalfDown => half == Ordering::Greater,
+        RoundingMode::HalfEven => {
+            half == Ordering::Greater || (half == Ordering::Equal && quotient % 2 != 0)
+        }
+    };
+    if away { quotient + sign } else { quotient }
+}
+
+impl FromStr for Decimal {
+    type Err = TypeError;
+
+    /// Parse `[+-]digits[.digits][e[+-]digits]`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || TypeError::ConversionError(format!("Cannot convert '{}' to DECIMAL", s));
+        let text = s.trim();
+
+        let (number, exponent) = match text.find(['e', 'E']) {
+            Some(i) => (
+                &text[..i],
+                text[i + 1..].parse::<i32>().map_err(|_| error())?,
+            ),
+            None => (text, 0),
+        };
+        let (negative, number) = match number.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, number.strip_prefix('+').unwrap_or(number)),
+        };
+        let (integer, fraction) = number.split_once('.').unwrap_or((number, ""));
+        let digits = format!("{}{}", integer, fraction);
+        if digits.is_empty() || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(error());
+        }
+
+        // Strip leading zeros so they do not count against the precision
+        let significant = digits.trim_start_matches('0');
+        if significant.len() > MAX_PRECISION as usize {
+            return Err(error());
+        }
+        let mut mantissa = significant.parse::<i128>().unwrap_or(0);
+        if negative {
+            mantissa = -mantissa;
+        }
+
+        let scale = fraction.len() as i64 - exponent as i64;
+        if scale >= 0 {
+            let scale = u32::try_from(scale).map_err(|_| error())?;
+            Decimal::new(mantissa, scale).map_err(|_| error())
+        } else {
+            let factor = u32::try_from(-scale)
+                .ok()
+                .and_then(pow10)
+                .ok_or_else(error)?;
+            let mantissa = mantissa.checked_mul(factor).ok_or_else(error)?;
+            Decimal::new(mantissa, 0).map_err(|_| error())
+        }
+    }
+}
+
+impl Display for Decimal {
+    /// Formats with exactly `scale` digits after the point (`12.50`)
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        let scale = self.scale as usize;
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (integer, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, integer, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dec(text: &str) -> Decimal {
+        text.parse().unwrap()
+    }
+
+    fn rounded(text: &str, scale: i32, mode: RoundingMode) -> String {
+        dec(text).round(scale, mode).unwrap().to_string()
+    }
+
+    #[test]
+    fn round_half_modes() {
+        use RoundingMode::*;
+        assert_eq!(rounded("2.5", 0, HalfUp), "3");
+        assert_eq!(rounded("-2.5", 0, HalfUp), "-3");
+        assert_eq!(rounded("2.4", 0, HalfUp), "2");
+        assert_eq!(rounded("2.5", 0, HalfDown), "2");
+        assert_eq!(rounded("-2.5", 0, HalfDown), "-2");
+        assert_eq!(rounded("2.6", 0, HalfDown), "3");
+        assert_eq!(rounded("2.5", 0, HalfEven), "2");
+        assert_eq!(rounded("3.5", 0, HalfEven), "4");
+        assert_eq!(rounded("-2.5", 0, HalfEven), "-2");
+        assert_eq!(rounded("2.51", 0, HalfEven), "3");
+    }
+
+    #[test]
+    fn round_directed_modes() {
+        use RoundingMode::*;
+        assert_eq!(rounded("2.1", 0, Up), "3");
+        assert_eq!(rounded("-2.1", 0, Up), "-3");
+        assert_eq!(rounded("2.9", 0, Down), "2");
+        assert_eq!(rounded("-2.9", 0, Down), "-2");
+        assert_eq!(rounded("2.1", 0, Ceiling), "3");
+        assert_eq!(rounded("-2.9", 0, Ceiling), "-2");
+        assert_eq!(rounded("2.9", 0, Floor), "2");
+        assert_eq!(rounded("-2.1", 0, Floor), "-3");
+    }
+
+    #[test]
+    fn round_to_other_scales() {
+        use RoundingMode::*;
+        assert_eq!(rounded("1.5", 2, HalfUp), "1.50");
+        assert_eq!(rounded("1.2345", 3, HalfUp), "1.235");
+        assert_eq!(rounded("1234.5", -2, HalfUp), "1200");
+        assert_eq!(rounded("1250", -2, HalfEven), "1200");
+        assert_eq!(rounded("1350", -2, HalfEven), "1400");
+        assert_eq!(rounded("0.5", -40, HalfUp), "0");
+        assert!(dec("0.5").round(-40, Up).is_err());
+    }
+
+    #[test]
+    fn fit_rounds_and_checks_precision() {
+        assert_eq!(dec("123.456").fit(5, 2).unwrap().to_string(), "123.46");
+        assert_eq!(dec("-0.005").fit(3, 2).unwrap().to_string(), "-0.01");
+        assert_eq!(dec("7").fit(5, 2).unwrap().to_string(), "7.00");
+        assert!(dec("1234.5").fit(5, 2).is_err());
+        assert!(dec("999.995").fit(5, 2).is_err());
+    }
+
+    #[test]
+    fn add_and_subtract() {
+        assert_eq!(
+            dec("1.5").checked_add(&dec("2.25")).unwrap().to_string(),
+            "3.75"
+        );
+        assert_eq!(
+            dec("1.5").checked_sub(&dec("2.25")).unwrap().to_string(),
+            "-0.75"
+        );
+        let max = dec("99999999999999999999999999999999999999");
+        assert!(max.checked_add(&dec("1")).is_err());
+    }
+
+    #[test]
+    fn multiply_truncates_scale() {
+        assert_eq!(
+            dec("1.5").checked_mul(&dec("2.25")).unwrap().to_string(),
+            "3.375"
+        );
+
+        let product = dec("0.12345678901234567890")
+            .checked_mul(&dec("0.5000000000000000000"))
+            .unwrap();
+        assert_eq!(product.scale(), 38);
+        assert_eq!(
+            product.cmp_value(&dec("0.06172839450617283945")),
+            Ordering::Equal
+        );
+
+        // The digit dropped beyond scale 38 is rounded half up
+        let tiny = dec("0.00000000000000000015")
+            .checked_mul(&dec("0.0000000000000000001"))
+            .unwrap();
+        assert_eq!(tiny.to_string(), "0.00000000000000000000000000000000000002");
+
+        let big = dec("99999999999999999999");
+        assert!(big.checked_mul(&big).is_err());
+    }
+
+    #[test]
+    fn divide() {
+        use RoundingMode::*;
+        let div = |a: &str, b: &str, mode| dec(a).checked_div(&dec(b), mode).unwrap().to_string();
+        assert_eq!(div("1", "3", HalfUp), "0.3333333333333333");
+        assert_eq!(div("2", "3", HalfUp), "0.6666666666666667");
+        assert_eq!(div("2", "3", Down), "0.6666666666666666");
+        assert_eq!(div("10", "4", HalfUp), "2.5000000000000000");
+        assert_eq!(
+            div("1.00000000000000000000", "3", HalfUp),
+            "0.33333333333333333333"
+        );
+        // Fewer fractional digits are kept when the quotient is large
+        assert_eq!(
+            div("123456789012345678901234", "7", HalfUp),
+            "17636684144620811271604.857142857142857"
+        );
+        assert!(dec("1").checked_div(&dec("0"), HalfUp).is_err());
+        let max = dec("99999999999999999999999999999999999999");
+        assert!(max.checked_div(&dec("0.1"), HalfUp).is_err());
+    }
+
+    #[test]
+    fn remainder_and_comparison() {
+        assert_eq!(
+            dec("7.5").checked_rem(&dec("2")).unwrap().to_string(),
+            "1.5"
+        );
+        assert_eq!(
+            dec("-7.5").checked_rem(&dec("2")).unwrap().to_string(),
+            "-1.5"
+        );
+        assert!(dec("1").checked_rem(&dec("0")).is_err());
+        assert_eq!(dec("1.0").cmp_value(&dec("1.00")), Ordering::Equal);
+        assert_eq!(dec("-1.5").cmp_value(&dec("-1.25")), Ordering::Less);
+    }
+
+    #[test]
+    fn parse_and_display() {
+        assert_eq!(dec("1.50").to_string(), "1.50");
+        assert_eq!(dec("000123.4").to_string(), "123.4");
+        assert_eq!(dec("1e3").to_string(), "1000");
+        assert_eq!(dec("1.5e-2").to_string(), "0.015");
+        assert_eq!(dec("-0.05").to_string(), "-0.05");
+        assert!("".parse::<Decimal>().is_err());
+        assert!("abc".parse::<Decimal>().is_err());
+        assert!(
+            "999999999999999999999999999999999999999"
+                .parse::<Decimal>()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn conversions() {
+        assert_eq!(Decimal::from_f64(0.1).unwrap().to_string(), "0.1");
+        assert!(Decimal::from_f64(f64::INFINITY).is_err());
+        assert_eq!(Decimal::from_i64(-42).to_string(), "-42");
+        assert_eq!(dec("2.5").to_i64(), Some(3));
+        assert_eq!(dec("1.25").to_f64(), 1.25);
+    }
+}
@@ -0,0 +1,153 @@
+//! JSON values: paths, type names and conversion to and from SQL values
+//!
+//! Paths follow SQLite: `$` is the whole document, `.key` or `."key"` selects
+//! an object member and `[n]` an array element (`[#-n]` counts from the end).
+
+use super::{DataType, Decimal, TypeError, Value, datetime};
+use serde_json::{Number, Value as Json};
+
+/// One step of a JSON path
+#[derive(Debug, Clone, PartialEq)]
+pub enum PathStep {
+    /// Object member
+    Key(String),
+    /// Array element; negative indexes count from the end
+    Index(i64),
+}
+
+/// Parse a JSON path such as `$.items[0].name`
+pub fn parse_path(path: &str) -> Result<Vec<PathStep>, TypeError> {
+    let error = || TypeError::InvalidValue("JSON path".to_string(), path.to_string());
+    let mut rest = path.trim().strip_prefix('$').ok_or_else(error)?;
+    let mut steps = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('.') {
+            let (key, remaining) = match after.strip_prefix('"') {
+                Some(quoted) => {
+                    let end = quoted.find('"').ok_or_else(error)?;
+                    (&quoted[..end], &quoted[end + 1..])
+                }
+                None => {
+                    let end = after.find(['.', '[']).unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            if key.is_empty() {
+                return Err(error());
+            }
+            steps.push(PathStep::Key(key.to_string()));
+            rest = remaining;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').ok_or_else(error)?;
+            let index = after[..end].trim();
+            let index = match index.strip_prefix('#') {
+                Some(from_end) if from_end.trim().is_empty() => return Err(error()),
+                Some(from_end) => from_end.trim().parse::<i64>().map_err(|_| error())?,
+                None => index.parse::<i64>().map_err(|_| error())?,
+            };
+            steps.push(PathStep::Index(index));
+            rest = &after[end + 1..];
+        } else {
+            return Err(error());
+        }
+    }
+    Ok(steps)
+}
+
+/// Follow one step into a JSON document
+pub fn step<'a>(json: &'a Json, step: &PathStep) -> Option<&'a Json> {
+    match (json, step) {
+        (Json::Object(members), PathStep::Key(key)) => members.get(key),
+        (Json::Array(items), PathStep::Index(index)) => {
+            let index = if *index < 0 {
+                items.len().checked_sub(index.unsigned_abs() as usize)?
+            } else {
+                usize::try_from(*index).ok()?
+            };
+            items.get(index)
+        }
+        _ => None,
+    }
+}
+
+/// Follow a path into a JSON document; None if any step is missing
+pub fn lookup<'a>(json: &'a Json, path: &[PathStep]) -> Option<&'a Json> {
+    path.iter().try_fold(json, step)
+}
+
+/// SQLite-style type name of a JSON value: `null`, `true`, `false`,
+/// `integer`, `real`, `text`, `array` or `object`
+pub fn type_name(json: &Json) -> &'static str {
+    match json {
+        Json::Null => "null",
+        Json::Bool(true) => "true",
+        Json::Bool(false) => "false",
+        Json::Number(n) if n.is_i64() || n.is_u64() => "integer",
+        Json::Number(_) => "real",
+        Json::String(_) => "text",
+        Json::Array(_) => "array",
+        Json::Object(_) => "object",
+    }
+}
+
+/// Parse JSON text
+pub fn parse(text: &str) -> Result<Json, TypeError> {
+    serde_json::from_str(text)
+        .map_err(|e| TypeError::ConversionError(format!("Invalid JSON '{}': {}", text, e)))
+}
+
+/// The SQL value of a JSON scalar: JSON null is NULL, strings are TEXT
+/// (unquoted), numbers are INTEGER, DECIMAL or FLOAT and booleans are
+/// BOOLEAN; arrays and objects stay JSON
+pub fn to_sql(json: &Json) -> Value {
+    match json {
+        Json::Null => Value::Null,
+        Json::Bool(b) => Value::Boolean(*b),
+        Json::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None if n.is_u64() => n
+                .to_string()
+                .parse::<Decimal>()
+                .map(Value::Decimal)
+                .unwrap_or(Value::Float(n.as_f64().unwrap_or(f64::NAN))),
+            None => Value::Float(n.as_f64().unwrap_or(f64::NAN)),
+        },
+        Json::String(s) => Value::Text(s.clone()),
+        Json::Array(_) | Json::Object(_) => Value::Json(json.clone()),
+    }
+}
+
+/// The JSON form of a SQL value, for the types that have one
+pub fn from_sql(value: &Value) -> Option<Json> {
+    match value {
+        Value::Null => Some(Json::Null),
+        Value::Integer(i) => Some(Json::from(*i)),
+        Value::Float(f) => Number::from_f64(*f).map(Json::Number),
+        Value::Decimal(d) => serde_json::from_str(&d.to_string()).ok(),
+        Value::Boolean(b) => Some(Json::Bool(*b)),
+        Value::Text(s) => Some(Json::String(s.clone())),
+        Value::Json(json) => Some(json.clone()),
+        Value::Uuid(uuid) => Some(Json::String(uuid.to_string())),
+        Value::Array(items) => items
+            .iter()
+            .map(from_sql)
+            .collect::<Option<_>>()
+            .map(Json::Array),
+        value if datetime::is_temporal(value) => match value.as_type(&DataType::Text) {
+            Ok(Value::Text(text)) => Some(Json::String(text)),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Text form of a JSON value as returned by `->>`: strings unquoted,
+/// null as NULL, anything else as compact JSON text
+pub fn to_text(json: &Json) -> Value {
+    match json {
+        Json::Null => Value::Null,
+        Json::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
@@ -1,5 +1,17 @@
+pub mod array;
+pub mod datetime;
+pub mod decimal;
+pub mod json;
+pub mod uuid;
+
+pub use datetime::Interval;
+pub use decimal::{Decimal, RoundingMode};
+pub use uuid::Uuid;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 use std::{
+    cmp::Ordering,
     fmt::{self, Display},
     str::FromStr,
 };
@@ -16,27 +28,189 @@ pub enum TypeError {
     InvalidValue(String, String),
     #[error("Value comparison error: {0}")]
     ComparisonError(String),
+    #[error("Arithmetic error: {0}")]
+    ArithmeticError(String),
+}
+
+/// How strictly values of different types may be mixed
+///
+/// Every value has a type, and an operation that combines values of
+/// different types either converts one of them implicitly or fails. Some
+/// conversions are made in both modes: INTEGER widens to FLOAT and DECIMAL,
+/// FLOAT and DECIMAL convert to each other, DATE to TIMESTAMP, and array
+/// elements follow the same rules. Numbers of any type compare with each
+/// other, as do dates and timestamps.
+///
+/// In lenient mode TEXT is also converted to the other type wherever it
+/// meets one, with the rules of `Value::as_type`: `'5'` is stored in an
+/// INTEGER column as 5 and `'5' = 5` is true. Values stored in a TEXT column
+/// are converted to text, and integers and booleans convert to each other.
+/// BLOBs are never converted implicitly.
+///
+/// In strict mode these conversions are rejected before a statement runs,
+/// and an explicit CAST or typed literal (`DATE '2024-01-31'`) is needed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TypeMode {
+    /// Convert between types wherever a conversion exists
+    #[default]
+    Lenient,
+    /// Only make conversions that lose nothing
+    Strict,
+}
+
+impl Display for TypeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TypeMode::Lenient => write!(f, "lenient"),
+            TypeMode::Strict => write!(f, "strict"),
+        }
+    }
+}
+
+impl FromStr for TypeMode {
+    type Err = TypeError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "lenient" => Ok(TypeMode::Lenient),
+            "strict" => Ok(TypeMode::Strict),
+            _ => Err(TypeError::InvalidValue(
+                "type mode".to_string(),
+                s.to_string(),
+            )),
+        }
+    }
 }
 
 /// Supported SQL data types
 ///
 /// Currently supports:
 /// - INTEGER: Signed 64-bit integer
+/// - FLOAT: 64-bit IEEE 754 floating point number
+/// - DECIMAL(p, s): exact decimal number
 /// - TEXT: UTF-8 string
+/// - BLOB: binary data
+/// - BOOLEAN: TRUE or FALSE
+/// - DATE, TIME, TIMESTAMP [WITH TIME ZONE]: calendar dates and times
+/// - INTERVAL: a span of months, days and microseconds
+/// - JSON: a JSON document
+/// - UUID: 128-bit universally unique identifier
+/// - type[]: array of values of another type
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum DataType {
     /// 64-bit signed integer
     Integer,
+    /// 64-bit floating point number (FLOAT, REAL, DOUBLE PRECISION)
+    Float,
+    /// Exact decimal number (DECIMAL, NUMERIC) with an optional precision
+    /// and scale; without them any value of up to 38 digits is allowed
+    Decimal(Option<(u32, u32)>),
     /// UTF-8 string
     Text,
-    //TODO: Extend with more types later (e.g., FLOAT, BOOLEAN, DATE, etc.)
+    /// Binary data (BLOB, BYTEA)
+    Blob,
+    /// TRUE or FALSE
+    Boolean,
+    /// Calendar date
+    Date,
+    /// Time of day
+    Time,
+    /// Date and time without time zone
+    Timestamp,
+    /// Date and time, stored in UTC (TIMESTAMP WITH TIME ZONE)
+    TimestampTz,
+    /// Span of time
+    Interval,
+    /// JSON document, validated when stored
+    Json,
+    /// 128-bit universally unique identifier
+    Uuid,
+    /// Array of values of the element type (`INTEGER[]`)
+    Array(Box<DataType>),
+}
+
+impl DataType {
+    /// Check if this is a date, time, timestamp or interval type
+    pub fn is_temporal(&self) -> bool {
+        matches!(
+            self,
+            DataType::Date
+                | DataType::Time
+                | DataType::Timestamp
+                | DataType::TimestampTz
+                | DataType::Interval
+        )
+    }
+
+    /// Check if this is INTEGER, FLOAT or DECIMAL
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            DataType::Integer | DataType::Float | DataType::Decimal(_)
+        )
+    }
+
+    /// Whether values of this type are converted implicitly to `target`, as
+    /// when they are stored in a column of that type (see `TypeMode`)
+    pub fn coerces_to(&self, target: &DataType, mode: TypeMode) -> bool {
+        match (self, target) {
+            (a, b) if a == b => true,
+            (DataType::Integer | DataType::Decimal(_), DataType::Float)
+            | (DataType::Integer | DataType::Float | DataType::Decimal(_), DataType::Decimal(_))
+            | (DataType::Date, DataType::Timestamp | DataType::TimestampTz)
+            | (DataType::Timestamp, DataType::TimestampTz)
+            | (DataType::TimestampTz, DataType::Timestamp) => true,
+            (DataType::Array(a), DataType::Array(b)) => a.coerces_to(b, mode),
+            _ if mode == TypeMode::Strict => false,
+            (DataType::Blob, _) | (_, DataType::Blob) => false,
+            (DataType::Text, _) | (_, DataType::Text) => true,
+            (DataType::Integer, DataType::Boolean) | (DataType::Boolean, DataType::Integer) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether values of the two types can be compared (see `TypeMode`)
+    ///
+    /// In lenient mode any two types may be compared, failing only when a
+    /// particular pair of values cannot be.
+    pub fn comparable_with(&self, other: &DataType, mode: TypeMode) -> bool {
+        let datetime = |t: &DataType| {
+            matches!(
+                t,
+                DataType::Date | DataType::Timestamp | DataType::TimestampTz
+            )
+        };
+        match (self, other) {
+            _ if mode == TypeMode::Lenient => true,
+            (a, b) if a == b => true,
+            (a, b) if a.is_numeric() && b.is_numeric() => true,
+            (a, b) if datetime(a) && datetime(b) => true,
+            (DataType::Array(a), DataType::Array(b)) => a.comparable_with(b, mode),
+            _ => false,
+        }
+    }
 }
 
 impl Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             DataType::Integer => write!(f, "INTEGER"),
+            DataType::Float => write!(f, "FLOAT"),
+            DataType::Decimal(None) => write!(f, "DECIMAL"),
+            DataType::Decimal(Some((precision, scale))) => {
+                write!(f, "DECIMAL({}, {})", precision, scale)
+            }
             DataType::Text => write!(f, "TEXT"),
+            DataType::Blob => write!(f, "BLOB"),
+            DataType::Boolean => write!(f, "BOOLEAN"),
+            DataType::Date => write!(f, "DATE"),
+            DataType::Time => write!(f, "TIME"),
+            DataType::Timestamp => write!(f, "TIMESTAMP"),
+            DataType::TimestampTz => write!(f, "TIMESTAMP WITH TIME ZONE"),
+            DataType::Interval => write!(f, "INTERVAL"),
+            DataType::Json => write!(f, "JSON"),
+            DataType::Uuid => write!(f, "UUID"),
+            DataType::Array(element) => write!(f, "{}[]", element),
         }
     }
 }
@@ -45,26 +219,98 @@ impl FromStr for DataType {
     type Err = TypeError;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        match s.to_uppercase().as_str() {
+        // Multi-word names may be separated by any whitespace
+        let name = s.split_whitespace().collect::<Vec<_>>().join(" ");
+        let upper = name.to_uppercase();
+        if let Some(element) = upper.strip_suffix("[]") {
+            return Ok(DataType::Array(Box::new(element.trim_end().parse()?)));
+        }
+        if let Some(spec) = upper
+            .strip_prefix("DECIMAL")
+            .or_else(|| upper.strip_prefix("NUMERIC"))
+        {
+            return parse_decimal_spec(spec).ok_or(TypeError::UnsupportedType(name));
+        }
+        match upper.as_str() {
             "INTEGER" | "INT" => Ok(DataType::Integer),
+            "FLOAT" | "REAL" | "DOUBLE" | "DOUBLE PRECISION" => Ok(DataType::Float),
             "TEXT" | "VARCHAR" | "STRING" | "CHAR" => Ok(DataType::Text),
+            "BLOB" | "BYTEA" => Ok(DataType::Blob),
+            "BOOLEAN" | "BOOL" => Ok(DataType::Boolean),
+            "DATE" => Ok(DataType::Date),
+            "TIME" => Ok(DataType::Time),
+            "TIMESTAMP" | "TIMESTAMP WITHOUT TIME ZONE" => Ok(DataType::Timestamp),
+            "TIMESTAMPTZ" | "TIMESTAMP WITH TIME ZONE" => Ok(DataType::TimestampTz),
+            "INTERVAL" => Ok(DataType::Interval),
+            "JSON" => Ok(DataType::Json),
+            "UUID" => Ok(DataType::Uuid),
             _ => Err(TypeError::UnsupportedType(s.to_string())),
         }
     }
 }
 
+/// Parse the `(precision[, scale])` suffix of DECIMAL; precision must be
+/// 1 to 38 and the scale at most the precision
+fn parse_decimal_spec(spec: &str) -> Option<DataType> {
+    let spec: String = spec.chars().filter(|c| !c.is_whitespace()).collect();
+    if spec.is_empty() {
+        return Some(DataType::Decimal(None));
+    }
+
+    let args = spec.strip_prefix('(')?.strip_suffix(')')?;
+    let (precision, scale) = match args.split_once(',') {
+        Some((precision, scale)) => (precision.parse().ok()?, scale.parse().ok()?),
+        None => (args.parse().ok()?, 0),
+    };
+    ((1..=decimal::MAX_PRECISION).contains(&precision) && scale <= precision)
+        .then_some(DataType::Decimal(Some((precision, scale))))
+}
+
 /// Represents a SQL value of any supported type
 ///
 /// Values can be:
 /// - Integer: 64-bit signed integer
+/// - Float: 64-bit floating point number
+/// - Decimal: exact decimal number
 /// - Text: UTF-8 string
+/// - Blob: binary data
+/// - Boolean: TRUE or FALSE
+/// - Date, Time, Timestamp, TimestampTz: dates and times
+/// - Interval: span of time
+/// - Json: JSON document
+/// - Uuid: universally unique identifier
+/// - Array: list of values of one type, which may include NULL
 /// - Null: SQL NULL value
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum Value {
     /// 64-bit signed integer value
     Integer(i64),
+    /// 64-bit floating point value
+    Float(f64),
+    /// Exact decimal value
+    Decimal(Decimal),
     /// UTF-8 string value
     Text(String),
+    /// Binary value
+    Blob(Vec<u8>),
+    /// Boolean value
+    Boolean(bool),
+    /// Calendar date
+    Date(NaiveDate),
+    /// Time of day
+    Time(NaiveTime),
+    /// Date and time without time zone
+    Timestamp(NaiveDateTime),
+    /// Date and time in UTC
+    TimestampTz(DateTime<Utc>),
+    /// Span of time
+    Interval(Interval),
+    /// JSON document
+    Json(serde_json::Value),
+    /// Universally unique identifier
+    Uuid(Uuid),
+    /// Array of values
+    Array(Vec<Value>),
     /// SQL NULL value
     Null,
 }
@@ -75,17 +321,87 @@ impl Value {
         matches!(self, Value::Null)
     }
 
+    /// Get the data type of the value (None for NULL)
+    pub fn data_type(&self) -> Option<DataType> {
+        match self {
+            Value::Integer(_) => Some(DataType::Integer),
+            Value::Float(_) => Some(DataType::Float),
+            Value::Decimal(_) => Some(DataType::Decimal(None)),
+            Value::Text(_) => Some(DataType::Text),
+            Value::Blob(_) => Some(DataType::Blob),
+            Value::Boolean(_) => Some(DataType::Boolean),
+            Value::Date(_) => Some(DataType::Date),
+            Value::Time(_) => Some(DataType::Time),
+            Value::Timestamp(_) => Some(DataType::Timestamp),
+            Value::TimestampTz(_) => Some(DataType::TimestampTz),
+            Value::Interval(_) => Some(DataType::Interval),
+            Value::Json(_) => Some(DataType::Json),
+            Value::Uuid(_) => Some(DataType::Uuid),
+            // Arrays take the type of their first non-NULL element
+            Value::Array(items) => Some(DataType::Array(Box::new(
+                items
+                    .iter()
+                    .find_map(Value::data_type)
+                    .unwrap_or(DataType::Text),
+            ))),
+            Value::Null => None,
+        }
+    }
+
     /// Convert value to expected type if possible
-    #[allow(dead_code)]
+    ///
+    /// This is the single conversion matrix used by CAST and by implicit
+    /// conversions. NULL converts to NULL of any type, and every type
+    /// converts to and from TEXT (arrays use the `{a,b}` form of
+    /// `array::format`). Between other types:
+    /// - INTEGER, FLOAT and DECIMAL convert to each other; FLOAT to INTEGER
+    ///   rounds, and DECIMAL(p, s) rounds half up to scale `s` and errors if
+    ///   the value does not fit
+    /// - BOOLEAN and INTEGER convert as 0/1 (any non-zero integer is TRUE)
+    /// - DATE, TIMESTAMP and TIMESTAMPTZ convert to each other and to TIME
+    /// - BLOB converts to UUID when it has 16 bytes, and UUID to BLOB
+    /// - JSON scalars convert to the type of the SQL value they hold, JSON
+    ///   arrays to array types, and numbers, booleans, text, UUIDs, dates,
+    ///   times and arrays to JSON
+    /// - arrays convert element by element
+    ///
+    /// Any other conversion is an error.
     pub fn as_type(&self, data_type: &DataType) -> Result<Value, TypeError> {
+        // Arrays take their type from the first element, so the others may
+        // still need converting
+        if self.data_type().as_ref() == Some(data_type) && !matches!(self, Value::Array(_)) {
+            return Ok(self.clone());
+        }
+
+        // Text to and from dates and times, and between dates and timestamps
+        if let Some(result) = datetime::convert(self, data_type) {
+            return result;
+        }
+
         match (self, data_type) {
-            // Already correct type
-            (Value::Integer(_), DataType::Integer)
-            | (Value::Text(_), DataType::Text)
-            | (Value::Null, _) => Ok(self.clone()),
+            (Value::Null, _) => Ok(self.clone()),
+
+            // Conversions to DECIMAL are exact, then rounded half up to the
+            // target scale and checked against its precision
+            (Value::Decimal(d), DataType::Decimal(spec)) => fit_decimal(*d, spec),
+            (Value::Integer(i), DataType::Decimal(spec)) => {
+                fit_decimal(Decimal::from_i64(*i), spec)
+            }
+            (Value::Float(f), DataType::Decimal(spec)) => fit_decimal(Decimal::from_f64(*f)?, spec),
+            (Value::Text(s), DataType::Decimal(spec)) => fit_decimal(s.parse()?, spec),
+
+            (Value::Decimal(d), DataType::Integer) => match d.to_i64() {
+                Some(i) => Ok(Value::Integer(i)),
+                None => Err(TypeError::ConversionError(format!(
+                    "DECIMAL {} is out of range for INTEGER",
+                    d
+                ))),
+            },
+            (Value::Decimal(d), DataType::Float) => Ok(Value::Float(d.to_f64())),
+            (Value::Decimal(d), DataType::Text) => Ok(Value::Text(d.to_string())),
 
             // Conversion from Text to Integer
-            (Value::Text(s), DataType::Integer) => match s.parse::<i64>() {
+            (Value::Text(s), DataType::Integer) => match s.trim().parse::<i64>() {
                 Ok(i) => Ok(Value::Integer(i)),
                 Err(_) => Err(TypeError::ConversionError(format!(
                     "Cannot convert '{}' to INTEGER",
@@ -93,68 +409,472 @@ impl Value {
                 ))),
             },
 
+            // Conversion from Text to Float ('NaN' and 'Infinity' are accepted)
+            (Value::Text(s), DataType::Float) => match s.trim().parse::<f64>() {
+                Ok(f) => Ok(Value::Float(f)),
+                Err(_) => Err(TypeError::ConversionError(format!(
+                    "Cannot convert '{}' to FLOAT",
+                    s
+                ))),
+            },
+
             // Conversion from Integer to Text
             (Value::Integer(i), DataType::Text) => Ok(Value::Text(i.to_string())),
-        }
-    }
 
-    /// Compare two values
-    pub fn compare(&self, op: &Operator, other: &Value) -> Result<bool, TypeError> {
-        match (self, other) {
-            // NULL comparisons always return false (except IS NULL which is handled separately)
-            (Value::Null, _) | (_, Value::Null) => Ok(false),
-
-            // Integer comparisons
-            (Value::Integer(a), Value::Integer(b)) => match op {
-                Operator::Eq => Ok(a == b),
-                Operator::NotEq => Ok(a != b),
-                Operator::Gt => Ok(a > b),
-                Operator::Lt => Ok(a < b),
-                Operator::GtEq => Ok(a >= b),
-                Operator::LtEq => Ok(a <= b),
+            // Conversion from Float to Text
+            (Value::Float(f), DataType::Text) => Ok(Value::Text(format_float(*f))),
+
+            // Conversion from Integer to Float
+            (Value::Integer(i), DataType::Float) => Ok(Value::Float(*i as f64)),
+
+            // Conversion from Float to Integer rounds to the nearest integer
+            (Value::Float(f), DataType::Integer) => {
+                let rounded = f.round();
+                if rounded.is_finite() && rounded >= i64::MIN as f64 && rounded < i64::MAX as f64 {
+                    Ok(Value::Integer(rounded as i64))
+                } else {
+                    Err(TypeError::ConversionError(format!(
+                        "FLOAT {} is out of range for INTEGER",
+                        format_float(*f)
+                    )))
+                }
+            }
+
+            // Conversions between Boolean and Integer: FALSE is 0, TRUE is 1,
+            // and any non-zero integer is TRUE
+            (Value::Boolean(b), DataType::Integer) => Ok(Value::Integer(*b as i64)),
+            (Value::Integer(i), DataType::Boolean) => Ok(Value::Boolean(*i != 0)),
+
+            // Conversions between Boolean and Text: 'true'/'false' and the
+            // usual spellings ('t', 'yes', 'on', '1', ...) are accepted
+            (Value::Boolean(b), DataType::Text) => Ok(Value::Text(b.to_string())),
+            (Value::Text(s), DataType::Boolean) => match parse_bool(s) {
+                Some(b) => Ok(Value::Boolean(b)),
+                None => Err(TypeError::ConversionError(format!(
+                    "Cannot convert '{}' to BOOLEAN",
+                    s
+                ))),
             },
 
-            // Text comparisons
-            (Value::Text(a), Value::Text(b)) => match op {
-                Operator::Eq => Ok(a == b),
-                Operator::NotEq => Ok(a != b),
-                Operator::Gt => Ok(a > b),
-                Operator::Lt => Ok(a < b),
-                Operator::GtEq => Ok(a >= b),
-                Operator::LtEq => Ok(a <= b),
+            // Conversions between Blob and Text use the UTF-8 bytes of the text
+            (Value::Text(s), DataType::Blob) => Ok(Value::Blob(s.as_bytes().to_vec())),
+            (Value::Blob(bytes), DataType::Text) => match String::from_utf8(bytes.clone()) {
+                Ok(s) => Ok(Value::Text(s)),
+                Err(_) => Err(TypeError::ConversionError(format!(
+                    "BLOB {} is not valid UTF-8 text",
+                    self
+                ))),
             },
 
-            // Mixed type comparisons - convert to compatible type if possible
-            (Value::Integer(a), Value::Text(b)) => match b.parse::<i64>() {
-                Ok(b_int) => match op {
-                    Operator::Eq => Ok(a == &b_int),
-                    Operator::NotEq => Ok(a != &b_int),
-                    Operator::Gt => Ok(a > &b_int),
-                    Operator::Lt => Ok(a < &b_int),
-                    Operator::GtEq => Ok(a >= &b_int),
-                    Operator::LtEq => Ok(a <= &b_int),
-                },
-                Err(_) => Err(TypeError::ComparisonError(format!(
-                    "Cannot compare INTEGER with TEXT: {} and '{}'",
-                    a, b
+            // Arrays convert element by element, and to and from text
+            (Value::Array(items), DataType::Array(element)) => Ok(Value::Array(
+                items
+                    .iter()
+                    .map(|item| item.as_type(element))
+                    .collect::<Result<_, _>>()?,
+            )),
+            (Value::Array(items), DataType::Text) => Ok(Value::Text(array::format(items)?)),
+            (Value::Text(s), DataType::Array(_)) => {
+                Value::Array(array::parse(s)?).as_type(data_type)
+            }
+            (Value::Json(serde_json::Value::Array(items)), DataType::Array(_)) => {
+                Value::Array(items.iter().map(json::to_sql).collect()).as_type(data_type)
+            }
+
+            // UUIDs convert to and from their canonical text and 16 bytes
+            (Value::Text(s), DataType::Uuid) => Ok(Value::Uuid(s.parse()?)),
+            (Value::Uuid(uuid), DataType::Text) => Ok(Value::Text(uuid.to_string())),
+            (Value::Blob(bytes), DataType::Uuid) => match <[u8; 16]>::try_from(bytes.as_slice()) {
+                Ok(bytes) => Ok(Value::Uuid(Uuid::from_bytes(bytes))),
+                Err(_) => Err(TypeError::ConversionError(format!(
+                    "BLOB of {} bytes cannot be a UUID",
+                    bytes.len()
                 ))),
             },
+            (Value::Uuid(uuid), DataType::Blob) => Ok(Value::Blob(uuid.as_bytes().to_vec())),
 
-            (Value::Text(a), Value::Integer(b)) => match a.parse::<i64>() {
-                Ok(a_int) => match op {
-                    Operator::Eq => Ok(&a_int == b),
-                    Operator::NotEq => Ok(&a_int != b),
-                    Operator::Gt => Ok(&a_int > b),
-                    Operator::Lt => Ok(&a_int < b),
-                    Operator::GtEq => Ok(&a_int >= b),
-                    Operator::LtEq => Ok(&a_int <= b),
-                },
-                Err(_) => Err(TypeError::ComparisonError(format!(
-                    "Cannot compare TEXT with INTEGER: '{}' and {}",
-                    a, b
+            // Text is parsed as JSON and JSON becomes its compact text; other
+            // values convert to and from the JSON scalar of the same value
+            (Value::Text(s), DataType::Json) => json::parse(s).map(Value::Json),
+            (Value::Json(j), DataType::Text) => Ok(Value::Text(j.to_string())),
+            (Value::Json(j), data_type)
+                if !matches!(
+                    j,
+                    serde_json::Value::Array(_) | serde_json::Value::Object(_)
+                ) =>
+            {
+                json::to_sql(j).as_type(data_type)
+            }
+            (value, DataType::Json) => json::from_sql(value).map(Value::Json).ok_or_else(|| {
+                TypeError::ConversionError(format!("Cannot convert {} to JSON", value))
+            }),
+
+            (value, data_type) => Err(TypeError::ConversionError(format!(
+                "Cannot convert {} to {}",
+                value, data_type
+            ))),
+        }
+    }
+
+    /// Interpret the value as the result of a condition
+    ///
+    /// BOOLEAN values map to TRUE/FALSE and NULL to UNKNOWN; any other type
+    /// is an error.
+    pub fn to_truth(&self) -> Result<Truth, TypeError> {
+        match self {
+            Value::Boolean(b) | Value::Json(serde_json::Value::Bool(b)) => Ok((*b).into()),
+            Value::Null | Value::Json(serde_json::Value::Null) => Ok(Truth::Unknown),
+            other => Err(TypeError::InvalidValue(
+                "condition".to_string(),
+                format!(
+                    "Expected BOOLEAN, got {} {}",
+                    other.data_type().unwrap_or(DataType::Text),
+                    other
+                ),
+            )),
+        }
+    }
+
+    /// Interpret the value as a number for arithmetic and numeric comparison
+    ///
+    /// Numbers and NULL are returned as-is; text must parse as a number.
+    fn to_numeric(&self) -> Result<Value, TypeError> {
+        match self {
+            Value::Text(s) => parse_number(s).ok_or_else(|| {
+                TypeError::ConversionError(format!("Cannot use '{}' as a number", s))
+            }),
+            Value::Json(j) => match json::to_sql(j) {
+                Value::Json(_) => Err(TypeError::ConversionError(format!(
+                    "Cannot use {} as a number",
+                    self
                 ))),
+                value => value.to_numeric(),
             },
+            _ => Ok(self.clone()),
+        }
+    }
+
+    /// Compare two values
+    ///
+    /// Comparing NULL with anything, including NULL, yields UNKNOWN. A JSON
+    /// null counts as NULL.
+    pub fn compare(&self, op: &Operator, other: &Value) -> Result<Truth, TypeError> {
+        let unknown =
+            |value: &Value| matches!(value, Value::Null | Value::Json(serde_json::Value::Null));
+        if unknown(self) || unknown(other) {
+            return Ok(Truth::Unknown);
         }
+        Ok(op.matches(self.try_cmp(other)?).into())
+    }
+
+    /// Order two values
+    ///
+    /// Integers and floats compare numerically, promoting the integer to a
+    /// float when the types differ. Text compared with a number is parsed as
+    /// a number, and it is an error if it is not numeric. NaN equals itself
+    /// and is greater than every other number, including Infinity. NULL
+    /// sorts after every other value.
+    fn try_cmp(&self, other: &Value) -> Result<Ordering, TypeError> {
+        match (self, other) {
+            (Value::Null, Value::Null) => Ok(Ordering::Equal),
+            (Value::Null, _) => Ok(Ordering::Greater),
+            (_, Value::Null) => Ok(Ordering::Less),
+
+            (Value::Integer(a), Value::Integer(b)) => Ok(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => Ok(float_cmp(*a, *b)),
+            (Value::Integer(a), Value::Float(b)) => Ok(float_cmp(*a as f64, *b)),
+            (Value::Float(a), Value::Integer(b)) => Ok(float_cmp(*a, *b as f64)),
+            (Value::Decimal(a), Value::Decimal(b)) => Ok(a.cmp_value(b)),
+            (Value::Decimal(a), Value::Integer(b)) => Ok(a.cmp_value(&Decimal::from_i64(*b))),
+            (Value::Integer(a), Value::Decimal(b)) => Ok(Decimal::from_i64(*a).cmp_value(b)),
+            (Value::Decimal(a), Value::Float(b)) => Ok(float_cmp(a.to_f64(), *b)),
+            (Value::Float(a), Value::Decimal(b)) => Ok(float_cmp(*a, b.to_f64())),
+            (Value::Text(a), Value::Text(b)) => Ok(a.cmp(b)),
+            (Value::Blob(a), Value::Blob(b)) => Ok(a.cmp(b)),
+            (Value::Boolean(a), Value::Boolean(b)) => Ok(a.cmp(b)),
+
+            // JSON scalars compare as the SQL value they hold; arrays and
+            // objects only compare with each other, by their JSON text
+            (Value::Json(_), _) | (_, Value::Json(_)) => {
+                let unwrap = |value: &Value| match value {
+                    Value::Json(j) => json::to_sql(j),
+                    value => value.clone(),
+                };
+                match (unwrap(self), unwrap(other)) {
+                    (Value::Json(a), Value::Json(b)) if a == b => Ok(Ordering::Equal),
+                    (Value::Json(a), Value::Json(b)) => Ok(a.to_string().cmp(&b.to_string())),
+                    (Value::Json(_), _) | (_, Value::Json(_)) => Err(self.comparison_error(other)),
+                    (a, b) => a.try_cmp(&b),
+                }
+            }
+
+            // Booleans compare with integers and text converted to BOOLEAN
+            (Value::Boolean(_), _) | (_, Value::Boolean(_)) => {
+                match (
+                    self.as_type(&DataType::Boolean),
+                    other.as_type(&DataType::Boolean),
+                ) {
+                    (Ok(a), Ok(b)) => a.try_cmp(&b),
+                    _ => Err(self.comparison_error(other)),
+                }
+            }
+
+            (Value::Uuid(a), Value::Uuid(b)) => Ok(a.cmp(b)),
+
+            // Arrays compare element by element, then by length
+            (Value::Array(a), Value::Array(b)) => {
+                for (a, b) in a.iter().zip(b) {
+                    match a.try_cmp(b)? {
+                        Ordering::Equal => {}
+                        ordering => return Ok(ordering),
+                    }
+                }
+                Ok(a.len().cmp(&b.len()))
+            }
+
+            // Text compared with a date/time value or UUID is converted to its type
+            (Value::Text(_), value) | (value, Value::Text(_))
+                if datetime::is_temporal(value) || matches!(value, Value::Uuid(_)) =>
+            {
+                let data_type = value.data_type().unwrap_or(DataType::Text);
+                self.as_type(&data_type)?
+                    .try_cmp(&other.as_type(&data_type)?)
+            }
+
+            // Mixed type comparisons - convert text to a number if possible
+            (Value::Text(_), _) | (_, Value::Text(_)) => {
+                match (self.to_numeric(), other.to_numeric()) {
+                    (Ok(a), Ok(b)) => a.try_cmp(&b),
+                    _ => Err(self.comparison_error(other)),
+                }
+            }
+
+            _ => datetime::compare(self, other).ok_or_else(|| self.comparison_error(other)),
+        }
+    }
+
+    fn comparison_error(&self, other: &Value) -> TypeError {
+        TypeError::ComparisonError(format!(
+            "Cannot compare {} with {}: {} and {}",
+            self.data_type().unwrap_or(DataType::Text),
+            other.data_type().unwrap_or(DataType::Text),
+            self,
+            other
+        ))
+    }
+
+    /// Total ordering of values, used by ORDER BY
+    ///
+    /// Follows `compare`, with NULL sorting after every other value so it
+    /// comes last in ascending and first in descending order. Text that
+    /// cannot be compared with a number sorts after all numbers.
+    pub fn sort_cmp(&self, other: &Value) -> Ordering {
+        let mismatch = match self {
+            Value::Text(_) => Ordering::Greater,
+            _ => Ordering::Less,
+        };
+        self.try_cmp(other).unwrap_or(mismatch)
+    }
+
+    /// Apply an arithmetic operator
+    ///
+    /// INTEGER with INTEGER stays INTEGER, erroring on overflow and truncating
+    /// on division. If either operand is FLOAT both are promoted to FLOAT.
+    /// Otherwise if either operand is DECIMAL the arithmetic is exact DECIMAL
+    /// arithmetic, with division rounded half up.
+    /// Numeric text is treated as a number, and NULL yields NULL. INTEGER and
+    /// DECIMAL division and modulo by zero are errors, while FLOAT follows
+    /// IEEE 754 as FLOAT literals and conversions do: overflow and division
+    /// by zero give Infinity or -Infinity, and 0 / 0 and modulo by zero give
+    /// NaN. Dates, times and intervals follow the rules in
+    /// `datetime::arithmetic`.
+    pub fn arithmetic(&self, op: &ArithmeticOperator, other: &Value) -> Result<Value, TypeError> {
+        if let Some(result) = datetime::arithmetic(self, op, other) {
+            return result;
+        }
+
+        let division = matches!(op, ArithmeticOperator::Divide | ArithmeticOperator::Modulo);
+
+        match (self.to_numeric()?, other.to_numeric()?) {
+            (Value::Null, _) | (_, Value::Null) => Ok(Value::Null),
+
+            (Value::Integer(a), Value::Integer(b)) => {
+                if division && b == 0 {
+                    return Err(TypeError::ArithmeticError("division by zero".to_string()));
+                }
+                let result = match op {
+                    ArithmeticOperator::Add => a.checked_add(b),
+                    ArithmeticOperator::Subtract => a.checked_sub(b),
+                    ArithmeticOperator::Multiply => a.checked_mul(b),
+                    ArithmeticOperator::Divide => a.checked_div(b),
+                    ArithmeticOperator::Modulo => a.checked_rem(b),
+                };
+                result
+                    .map(Value::Integer)
+                    .ok_or_else(|| TypeError::ArithmeticError("INTEGER out of range".to_string()))
+            }
+
+            (a @ Value::Decimal(_), b) | (a, b @ Value::Decimal(_))
+                if !matches!(a, Value::Float(_)) && !matches!(b, Value::Float(_)) =>
+            {
+                let (a, b) = match (
+                    a.as_type(&DataType::Decimal(None))?,
+                    b.as_type(&DataType::Decimal(None))?,
+                ) {
+                    (Value::Decimal(a), Value::Decimal(b)) => (a, b),
+                    (a, b) => {
+                        return Err(TypeError::ArithmeticError(format!(
+                            "Cannot apply {} to {} and {}",
+                            op, a, b
+                        )));
+                    }
+                };
+                let result = match op {
+                    ArithmeticOperator::Add => a.checked_add(&b),
+                    ArithmeticOperator::Subtract => a.checked_sub(&b),
+                    ArithmeticOperator::Multiply => a.checked_mul(&b),
+                    ArithmeticOperator::Divide => a.checked_div(&b, RoundingMode::HalfUp),
+                    ArithmeticOperator::Modulo => a.checked_rem(&b),
+                };
+                Ok(Value::Decimal(result?))
+            }
+
+            (a, b) => {
+                let (a, b) = match (a.as_type(&DataType::Float)?, b.as_type(&DataType::Float)?) {
+                    (Value::Float(a), Value::Float(b)) => (a, b),
+                    (a, b) => {
+                        return Err(TypeError::ArithmeticError(format!(
+                            "Cannot apply {} to {} and {}",
+                            op, a, b
+                        )));
+                    }
+                };
+                Ok(Value::Float(match op {
+                    ArithmeticOperator::Add => a + b,
+                    ArithmeticOperator::Subtract => a - b,
+                    ArithmeticOperator::Multiply => a * b,
+                    ArithmeticOperator::Divide => a / b,
+                    ArithmeticOperator::Modulo => a % b,
+                }))
+            }
+        }
+    }
+
+    /// Negate a numeric value or interval (unary minus)
+    pub fn negate(&self) -> Result<Value, TypeError> {
+        match self {
+            Value::Interval(interval) => interval
+                .checked_neg()
+                .map(Value::Interval)
+                .ok_or_else(|| TypeError::ArithmeticError("INTERVAL out of range".to_string())),
+            _ => Value::Integer(0).arithmetic(&ArithmeticOperator::Subtract, self),
+        }
+    }
+
+    /// Match this value against a SQL LIKE pattern
+    ///
+    /// `%` matches any sequence of characters and `_` matches exactly one.
+    /// The optional escape character makes the following pattern character
+    /// match literally. Integers are matched against their text form.
+    pub fn like(
+        &self,
+        pattern: &Value,
+        escape: Option<char>,
+        case_insensitive: bool,
+    ) -> Result<bool, TypeError> {
+        let text = match self.as_type(&DataType::Text)? {
+            Value::Text(s) => s,
+            _ => return Ok(false),
+        };
+        let pattern = match pattern.as_type(&DataType::Text)? {
+            Value::Text(s) => s,
+            _ => return Ok(false),
+        };
+
+        let tokens = LikeToken::tokenize(&pattern, escape)?;
+        let text: Vec<char> = text.chars().collect();
+        Ok(LikeToken::matches(&tokens, &text, case_insensitive))
+    }
+}
+
+/// A single element of a compiled LIKE pattern
+#[derive(Debug, Clone, PartialEq)]
+enum LikeToken {
+    /// Matches this character exactly
+    Char(char),
+    /// `_`: matches any single character
+    AnyOne,
+    /// `%`: matches any sequence of characters, including none
+    AnyMany,
+}
+
+impl LikeToken {
+    /// Split a LIKE pattern into tokens, resolving escape sequences
+    fn tokenize(pattern: &str, escape: Option<char>) -> Result<Vec<LikeToken>, TypeError> {
+        let mut tokens = Vec::new();
+        let mut chars = pattern.chars();
+
+        while let Some(c) = chars.next() {
+            if Some(c) == escape {
+                match chars.next() {
+                    Some(escaped) => tokens.push(LikeToken::Char(escaped)),
+                    None => {
+                        return Err(TypeError::InvalidValue(
+                            "LIKE pattern".to_string(),
+                            format!("'{}' must not end with the escape character", pattern),
+                        ));
+                    }
+                }
+            } else if c == '%' {
+                tokens.push(LikeToken::AnyMany);
+            } else if c == '_' {
+                tokens.push(LikeToken::AnyOne);
+            } else {
+                tokens.push(LikeToken::Char(c));
+            }
+        }
+
+        Ok(tokens)
+    }
+
+    /// Match text against a tokenized pattern
+    ///
+    /// Uses the classic wildcard algorithm: on a mismatch, backtrack to the
+    /// most recent `%` and let it absorb one more character.
+    fn matches(tokens: &[LikeToken], text: &[char], case_insensitive: bool) -> bool {
+        let char_eq = |a: char, b: char| {
+            a == b || (case_insensitive && a.to_lowercase().eq(b.to_lowercase()))
+        };
+
+        let (mut t, mut p) = (0, 0);
+        let mut backtrack: Option<(usize, usize)> = None;
+
+        while t < text.len() {
+            match tokens.get(p) {
+                Some(LikeToken::AnyMany) => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                }
+                Some(LikeToken::AnyOne) => {
+                    t += 1;
+                    p += 1;
+                }
+                Some(LikeToken::Char(c)) if char_eq(*c, text[t]) => {
+                    t += 1;
+                    p += 1;
+                }
+                _ => match backtrack {
+                    Some((star_p, star_t)) => {
+                        backtrack = Some((star_p, star_t + 1));
+                        p = star_p + 1;
+                        t = star_t + 1;
+                    }
+                    None => return false,
+                },
+            }
+        }
+
+        tokens[p..].iter().all(|token| *token == LikeToken::AnyMany)
     }
 }
 
@@ -175,11 +895,230 @@ pub enum Operator {
     LtEq,
 }
 
+impl Operator {
+    /// Check whether an ordering between two values satisfies the operator
+    fn matches(&self, ordering: Ordering) -> bool {
+        match self {
+            Operator::Eq => ordering == Ordering::Equal,
+            Operator::NotEq => ordering != Ordering::Equal,
+            Operator::Gt => ordering == Ordering::Greater,
+            Operator::Lt => ordering == Ordering::Less,
+            Operator::GtEq => ordering != Ordering::Less,
+            Operator::LtEq => ordering != Ordering::Greater,
+        }
+    }
+}
+
+/// Arithmetic operators
+#[derive(Debug, Clone, PartialEq)]
+pub enum ArithmeticOperator {
+    /// Addition (+)
+    Add,
+    /// Subtraction (-)
+    Subtract,
+    /// Multiplication (*)
+    Multiply,
+    /// Division (/)
+    Divide,
+    /// Remainder (%)
+    Modulo,
+}
+
+impl ArithmeticOperator {
+    /// Type of the result of applying this operator to values of the given
+    /// types, following the promotion rules of `Value::arithmetic`
+    pub fn result_type(&self, left: &DataType, right: &DataType) -> DataType {
+        if let Some(data_type) = datetime::result_type(left, self, right) {
+            return data_type;
+        }
+        if *left == DataType::Float || *right == DataType::Float {
+            DataType::Float
+        } else if matches!(left, DataType::Decimal(_)) || matches!(right, DataType::Decimal(_)) {
+            DataType::Decimal(None)
+        } else {
+            DataType::Integer
+        }
+    }
+}
+
+impl Display for ArithmeticOperator {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let symbol = match self {
+            ArithmeticOperator::Add => "+",
+            ArithmeticOperator::Subtract => "-",
+            ArithmeticOperator::Multiply => "*",
+            ArithmeticOperator::Divide => "/",
+            ArithmeticOperator::Modulo => "%",
+        };
+        write!(f, "{}", symbol)
+    }
+}
+
+/// A decimal converted to a DECIMAL type, with its precision and scale if any
+fn fit_decimal(d: Decimal, spec: &Option<(u32, u32)>) -> Result<Value, TypeError> {
+    match spec {
+        Some((precision, scale)) => Ok(Value::Decimal(d.fit(*precision, *scale)?)),
+        None => Ok(Value::Decimal(d)),
+    }
+}
+
+/// Total order on floats: NaN equals itself and is greater than every other
+/// value, including Infinity; -0.0 equals 0.0
+fn float_cmp(a: f64, b: f64) -> Ordering {
+    a.partial_cmp(&b)
+        .unwrap_or_else(|| a.is_nan().cmp(&b.is_nan()))
+}
+
+/// Format a float for display, always distinguishable from an integer
+fn format_float(f: f64) -> String {
+    if f.is_nan() {
+        "NaN".to_string()
+    } else if f.is_infinite() {
+        if f > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else {
+        format!("{:?}", f)
+    }
+}
+
+/// Format bytes as uppercase hexadecimal digit pairs
+pub fn encode_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02X}", b)).collect()
+}
+
+/// Decode a string of hexadecimal digit pairs (case-insensitive)
+pub fn decode_hex(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(2) || !text.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&text[i..i + 2], 16).ok())
+        .collect()
+}
+
+/// Parse the accepted text spellings of a boolean, case-insensitively
+fn parse_bool(s: &str) -> Option<bool> {
+    match s.trim().to_lowercase().as_str() {
+        "true" | "t" | "yes" | "y" | "on" | "1" => Some(true),
+        "false" | "f" | "no" | "n" | "off" | "0" => Some(false),
+        _ => None,
+    }
+}
+
+/// Parse text as an INTEGER if possible, otherwise as a FLOAT
+fn parse_number(s: &str) -> Option<Value> {
+    s.parse::<i64>()
+        .map(Value::Integer)
+        .or_else(|_| s.trim().parse::<f64>().map(Value::Float))
+        .ok()
+}
+
+/// Truth value of a SQL condition under three-valued logic
+///
+/// Conditions involving NULL are neither true nor false but UNKNOWN.
+/// AND, OR and NOT follow the SQL standard truth tables, and a WHERE clause
+/// only keeps rows for which its condition is TRUE.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Truth {
+    True,
+    False,
+    Unknown,
+}
+
+impl Truth {
+    /// Logical AND: FALSE if either side is FALSE, otherwise UNKNOWN if either is UNKNOWN
+    pub fn and(self, other: Truth) -> Truth {
+        match (self, other) {
+            (Truth::False, _) | (_, Truth::False) => Truth::False,
+            (Truth::True, Truth::True) => Truth::True,
+            _ => Truth::Unknown,
+        }
+    }
+
+    /// Logical OR: TRUE if either side is TRUE, otherwise UNKNOWN if either is UNKNOWN
+    pub fn or(self, other: Truth) -> Truth {
+        match (self, other) {
+            (Truth::True, _) | (_, Truth::True) => Truth::True,
+            (Truth::False, Truth::False) => Truth::False,
+            _ => Truth::Unknown,
+        }
+    }
+
+    /// Check if the truth value is TRUE (UNKNOWN counts as not true)
+    pub fn is_true(self) -> bool {
+        self == Truth::True
+    }
+}
+
+impl std::ops::Not for Truth {
+    type Output = Truth;
+
+    /// Logical NOT: UNKNOWN stays UNKNOWN
+    fn not(self) -> Truth {
+        match self {
+            Truth::True => Truth::False,
+            Truth::False => Truth::True,
+            Truth::Unknown => Truth::Unknown,
+        }
+    }
+}
+
+impl From<bool> for Truth {
+    fn from(value: bool) -> Self {
+        if value { Truth::True } else { Truth::False }
+    }
+}
+
+impl From<Truth> for Value {
+    /// UNKNOWN is represented as NULL
+    fn from(truth: Truth) -> Self {
+        match truth {
+            Truth::True => Value::Boolean(true),
+            Truth::False => Value::Boolean(false),
+            Truth::Unknown => Value::Null,
+        }
+    }
+}
+
+impl Display for Truth {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Truth::True => write!(f, "TRUE"),
+            Truth::False => write!(f, "FALSE"),
+            Truth::Unknown => write!(f, "UNKNOWN"),
+        }
+    }
+}
+
 impl Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Value::Integer(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{}", format_float(*x)),
+            Value::Decimal(d) => write!(f, "{}", d),
             Value::Text(s) => write!(f, "'{}'", s),
+            Value::Blob(bytes) => write!(f, "X'{}'", encode_hex(bytes)),
+            Value::Boolean(true) => write!(f, "TRUE"),
+            Value::Boolean(false) => write!(f, "FALSE"),
+            Value::Date(date) => write!(f, "DATE '{}'", date),
+            Value::Time(time) => write!(f, "TIME '{}'", datetime::format_time(time)),
+            Value::Timestamp(timestamp) => {
+                write!(f, "TIMESTAMP '{}'", datetime::format_timestamp(timestamp))
+            }
+            Value::TimestampTz(timestamp) => {
+                write!(
+                    f,
+                    "TIMESTAMPTZ '{}'",
+                    datetime::format_timestamptz(timestamp)
+                )
+            }
+            Value::Interval(interval) => write!(f, "INTERVAL '{}'", interval),
+            Value::Json(json) => write!(f, "JSON '{}'", json),
+            Value::Uuid(uuid) => write!(f, "UUID '{}'", uuid),
+            Value::Array(items) => {
+                let items: Vec<String> = items.iter().map(|item| item.to_string()).collect();
+                write!(f, "ARRAY[{}]", items.join(", "))
+            }
             Value::Null => write!(f, "NULL"),
         }
     }
@@ -187,7 +1126,8 @@ impl Display for Value {
 
 /// Column definition in a table schema
 ///
-/// Represents a column with name, data type, and nullability information.
+/// Represents a column with name, data type, and nullability information,
+/// and how its value is produced when an INSERT does not give one.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Column {
     /// Column name
@@ -196,6 +1136,38 @@ pub struct Column {
     pub data_type: DataType,
     /// Whether the column can contain NULL values
     pub nullable: bool,
+    /// SQL text of the DEFAULT expression, evaluated for each inserted row
+    /// that omits the column
+    #[serde(default)]
+    pub default: Option<String>,
+    /// How a generated column computes its value from the rest of the row
+    #[serde(default)]
+    pub generated: Option<Generated>,
+    /// Sequence numbering an identity column (SERIAL, AUTOINCREMENT or
+    /// `GENERATED ... AS IDENTITY`)
+    #[serde(default)]
+    pub identity: Option<Identity>,
+}
+
+/// `GENERATED ALWAYS AS (expr) [STORED | VIRTUAL]`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Generated {
+    /// SQL text of the expression, which may refer to the table's other
+    /// non-generated columns
+    pub expr: String,
+    /// STORED columns are computed when a row is written; VIRTUAL columns
+    /// are computed again whenever the row is read
+    pub stored: bool,
+}
+
+/// `GENERATED { ALWAYS | BY DEFAULT } AS IDENTITY`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Identity {
+    /// Name of the sequence the column's values are taken from
+    pub sequence: String,
+    /// ALWAYS columns cannot be given a value; BY DEFAULT ones (including
+    /// SERIAL and AUTOINCREMENT) take the next value only when omitted
+    pub always: bool,
 }
 
 impl Column {
@@ -205,9 +1177,63 @@ impl Column {
             name,
             data_type,
             nullable,
+            default: None,
+            generated: None,
+            identity: None,
+        }
+    }
+
+    /// Give the column a DEFAULT expression (as SQL text)
+    pub fn with_default(mut self, default: Option<String>) -> Self {
+        self.default = default;
+        self
+    }
+
+    /// Make the column a generated column
+    pub fn with_generated(mut self, generated: Option<Generated>) -> Self {
+        self.generated = generated;
+        self
+    }
+
+    /// Make the column an identity column
+    pub fn with_identity(mut self, identity: Option<Identity>) -> Self {
+        self.identity = identity;
+        self
+    }
+
+    /// Convert a value to this column's type where the type mode allows it
+    ///
+    /// The conversions are those of `DataType::coerces_to`, made with
+    /// `Value::as_type`; numbers are rounded to the scale of DECIMAL columns
+    /// and array elements are coerced to the element type. Anything else,
+    /// or a value that fails to convert, is returned unchanged and left to
+    /// `validate_value`.
+    pub fn coerce_value(&self, value: Value, mode: TypeMode) -> Value {
+        match (&self.data_type, value) {
+            (DataType::Array(element), Value::Array(items)) => {
+                let column = self.element_column(element);
+                Value::Array(
+                    items
+                        .into_iter()
+                        .map(|item| column.coerce_value(item, mode))
+                        .collect(),
+                )
+            }
+            (data_type, value) => match value.data_type() {
+                Some(from) if from.coerces_to(data_type, mode) => {
+                    value.as_type(data_type).unwrap_or(value)
+                }
+                _ => value,
+            },
         }
     }
 
+    /// A nullable column of an array column's element type, used to coerce
+    /// and validate the elements
+    fn element_column(&self, element: &DataType) -> Column {
+        Column::new(self.name.clone(), element.clone(), true)
+    }
+
     /// Validate that a value matches this column's type
     pub fn validate_value(&self, value: &Value) -> Result<(), TypeError> {
         // NULL check
@@ -221,12 +1247,43 @@ impl Column {
         // Type check (skip for NULL values)
         if !value.is_null() {
             match (&self.data_type, value) {
-                (DataType::Integer, Value::Integer(_)) => Ok(()),
-                (DataType::Text, Value::Text(_)) => Ok(()),
+                // DECIMAL(p, s) values must have scale s and fit the precision
+                (DataType::Decimal(Some((precision, scale))), Value::Decimal(d))
+                    if d.scale() == *scale && d.integer_digits() <= precision - scale =>
+                {
+                    Ok(())
+                }
+                (
+                    DataType::Decimal(Some(_)),
+                    Value::Integer(_) | Value::Float(_) | Value::Decimal(_),
+                ) => Err(TypeError::InvalidValue(
+                    self.name.clone(),
+                    format!("Value {} does not fit {}", value, self.data_type),
+                )),
+                // Text is only left in a JSON column if it failed to parse
+                (DataType::Json, Value::Text(s)) => Err(TypeError::InvalidValue(
+                    self.name.clone(),
+                    match json::parse(s) {
+                        Err(TypeError::ConversionError(message)) => message,
+                        _ => format!("Value {} is not JSON", value),
+                    },
+                )),
+                // Each element of an array must have the element type
+                (DataType::Array(element), Value::Array(items)) => {
+                    let column = self.element_column(element);
+                    items
+                        .iter()
+                        .try_for_each(|item| column.validate_value(item))
+                }
+                (DataType::Uuid, Value::Text(s)) => Err(TypeError::InvalidValue(
+                    self.name.clone(),
+                    format!("'{}' is not a valid UUID", s),
+                )),
+                (data_type, value) if value.data_type().as_ref() == Some(data_type) => Ok(()),
                 _ => Err(TypeError::InvalidValue(
                     self.name.clone(),
                     format!(
-                        "Value {:?} does not match column type {:?}",
+                        "Value {} does not match column type {}",
                         value, self.data_type
                     ),
                 )),
@@ -237,29 +1294,317 @@ impl Column {
     }
 }
 
+/// PRIMARY KEY or UNIQUE constraint over one or more columns
+///
+/// No two rows may have equal values in all of the key's columns. Rows with
+/// a NULL in any of them are exempt, as NULLs are never equal; primary key
+/// columns are NOT NULL.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct KeyConstraint {
+    /// Columns of the key, in order
+    pub columns: Vec<String>,
+    /// Whether this is the table's primary key
+    pub primary: bool,
+}
+
+impl Display for KeyConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let kind = if self.primary {
+            "PRIMARY KEY"
+        } else {
+            "UNIQUE"
+        };
+        write!(f, "{} ({})", kind, self.columns.join(", "))
+    }
+}
+
+/// What happens to referencing rows when a referenced key is deleted or
+/// updated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReferentialAction {
+    /// Reject the change while rows refer to the key (the default)
+    #[default]
+    NoAction,
+    /// Reject the change while rows refer to the key
+    Restrict,
+    /// Delete or update the referencing rows along with the key
+    Cascade,
+    /// Set the referencing columns to NULL
+    SetNull,
+    /// Set the referencing columns to their default values
+    SetDefault,
+}
+
+impl Display for ReferentialAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferentialAction::NoAction => write!(f, "NO ACTION"),
+            ReferentialAction::Restrict => write!(f, "RESTRICT"),
+            ReferentialAction::Cascade => write!(f, "CASCADE"),
+            ReferentialAction::SetNull => write!(f, "SET NULL"),
+            ReferentialAction::SetDefault => write!(f, "SET DEFAULT"),
+        }
+    }
+}
+
+/// FOREIGN KEY constraint: the values of `columns` in each row must be a
+/// key of `table`'s `referenced` columns
+///
+/// Rows with a NULL in any of the columns are not checked.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ForeignKey {
+    /// Referencing columns of this table
+    pub columns: Vec<String>,
+    /// Referenced table
+    pub table: String,
+    /// Referenced columns, a PRIMARY KEY or UNIQUE key of that table
+    pub referenced: Vec<String>,
+    /// Action when a referenced row is deleted
+    pub on_delete: ReferentialAction,
+    /// Action when a referenced key is updated
+    pub on_update: ReferentialAction,
+}
+
+impl Display for ForeignKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "FOREIGN KEY ({}) REFERENCES {}({})",
+            self.columns.join(", "),
+            self.table,
+            self.referenced.join(", ")
+        )
+    }
+}
+
+/// Named CHECK constraint: a condition every row must not make FALSE
+///
+/// A condition that is UNKNOWN, because of a NULL, is satisfied.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CheckConstraint {
+    /// Constraint name, given with `CONSTRAINT name` or generated from the
+    /// table and column names
+    pub name: String,
+    /// SQL text of the condition
+    pub expr: String,
+}
+
+impl Display for CheckConstraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CONSTRAINT {} CHECK ({})", self.name, self.expr)
+    }
+}
+
+/// Sequence of integers, created with CREATE SEQUENCE or for an identity
+/// column
+///
+/// Like PostgreSQL, a sequence remembers the last value it handed out and
+/// whether that value has been used yet, so the first NEXTVAL returns the
+/// start value and SETVAL can choose whether the next call returns or skips
+/// the value it sets.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Sequence {
+    /// Value added by each NEXTVAL; negative for descending sequences
+    pub increment: i64,
+    /// Smallest value the sequence may return
+    pub min_value: i64,
+    /// Largest value the sequence may return
+    pub max_value: i64,
+    /// Whether to wrap around instead of failing when a bound is passed
+    pub cycle: bool,
+    /// Last value returned, or the start value before the first NEXTVAL
+    pub last_value: i64,
+    /// Whether `last_value` has been returned by NEXTVAL
+    pub is_called: bool,
+}
+
+impl Sequence {
+    /// Create a sequence whose first value is `start`
+    pub fn new(start: i64, increment: i64, min_value: i64, max_value: i64, cycle: bool) -> Self {
+        Self {
+            increment,
+            min_value,
+            max_value,
+            cycle,
+            last_value: start,
+            is_called: false,
+        }
+    }
+
+    /// Advance the sequence, returning None once it has passed its bound
+    /// without CYCLE
+    pub fn next_value(&mut self) -> Option<i64> {
+        if self.is_called {
+            let next = self
+                .last_value
+                .checked_add(self.increment)
+                .filter(|next| (self.min_value..=self.max_value).contains(next));
+            self.last_value = match next {
+                Some(next) => next,
+                None if !self.cycle => return None,
+                None if self.increment > 0 => self.min_value,
+                None => self.max_value,
+            };
+        }
+        self.is_called = true;
+        Some(self.last_value)
+    }
+
+    /// Value most recently returned by NEXTVAL, if any
+    pub fn current_value(&self) -> Option<i64> {
+        self.is_called.then_some(self.last_value)
+    }
+
+    /// Make `value` the last value, returned by the next NEXTVAL unless
+    /// `is_called` is set
+    pub fn set_value(&mut self, value: i64, is_called: bool) -> bool {
+        if !(self.min_value..=self.max_value).contains(&value) {
+            return false;
+        }
+        self.last_value = value;
+        self.is_called = is_called;
+        true
+    }
+
+    /// Move the sequence past a value that was used without it, so it does
+    /// not hand the value out again
+    pub fn skip_past(&mut self, value: i64) {
+        let ahead = |a: i64, b: i64| if self.increment > 0 { a > b } else { a < b };
+        let used = match self.current_value() {
+            Some(last) => ahead(value, last),
+            None => !ahead(self.last_value, value),
+        };
+        if used {
+            self.set_value(value, true);
+        }
+    }
+}
+
 /// Table schema definition
 ///
-/// Defines the structure of a table with columns and their types.
+/// Defines the structure of a table with columns and their types, and the
+/// keys that constrain its rows.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Schema {
     /// List of columns in the schema
     pub columns: Vec<Column>,
+    /// PRIMARY KEY and UNIQUE constraints
+    #[serde(default)]
+    pub keys: Vec<KeyConstraint>,
+    /// FOREIGN KEY constraints
+    #[serde(default)]
+    pub foreign_keys: Vec<ForeignKey>,
+    /// CHECK constraints
+    #[serde(default)]
+    pub checks: Vec<CheckConstraint>,
 }
 
 impl Schema {
     /// Create a new schema with the given columns
     pub fn new(columns: Vec<Column>) -> Self {
-        Self { columns }
+        Self {
+            columns,
+            keys: Vec::new(),
+            foreign_keys: Vec::new(),
+            checks: Vec::new(),
+        }
+    }
+
+    /// Add PRIMARY KEY and UNIQUE constraints to the schema
+    pub fn with_keys(mut self, keys: Vec<KeyConstraint>) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Add FOREIGN KEY constraints to the schema
+    pub fn with_foreign_keys(mut self, foreign_keys: Vec<ForeignKey>) -> Self {
+        self.foreign_keys = foreign_keys;
+        self
+    }
+
+    /// Add CHECK constraints to the schema
+    pub fn with_checks(mut self, checks: Vec<CheckConstraint>) -> Self {
+        self.checks = checks;
+        self
+    }
+
+    /// Whether the columns, in any order, are the primary key or a UNIQUE key
+    pub fn is_key(&self, columns: &[String]) -> bool {
+        self.keys.iter().any(|key| {
+            key.columns.len() == columns.len()
+                && columns.iter().all(|column| key.columns.contains(column))
+        })
+    }
+
+    /// The values of the named columns in a row, or None if a column is missing
+    pub fn values_of<'a>(&self, row: &'a Row, columns: &[String]) -> Option<Vec<&'a Value>> {
+        columns
+            .iter()
+            .map(|name| row.get_value(self.get_column_index(name)?))
+            .collect()
+    }
+
+    /// The primary key, if the table has one
+    pub fn primary_key(&self) -> Option<&KeyConstraint> {
+        self.keys.iter().find(|key| key.primary)
     }
 
     /// Get a column by name
     pub fn get_column(&self, name: &str) -> Option<&Column> {
-        self.columns.iter().find(|col| col.name == name)
+        Some(&self.columns[self.get_column_index(name)?])
     }
 
     /// Get the index of a column by name
+    ///
+    /// Columns of a FROM list with table functions are named `source.col`;
+    /// a name without a source finds such a column when only one source
+    /// has a column of that name.
     pub fn get_column_index(&self, name: &str) -> Option<usize> {
-        self.columns.iter().position(|col| col.name == name)
+        if let Some(index) = self.columns.iter().position(|col| col.name == name) {
+            return Some(index);
+        }
+        match self.qualified_indexes(name).as_slice() {
+            [index] => Some(*index),
+            _ => None,
+        }
+    }
+
+    /// Whether a name without a source matches columns of several sources
+    pub fn is_ambiguous(&self, name: &str) -> bool {
+        !self.columns.iter().any(|col| col.name == name) && self.qualified_indexes(name).len() > 1
+    }
+
+    /// Positions of the columns named `source.name` for any source
+    fn qualified_indexes(&self, name: &str) -> Vec<usize> {
+        if name.contains('.') {
+            return vec![];
+        }
+        self.columns
+            .iter()
+            .enumerate()
+            .filter(|(_, col)| {
+                col.name
+                    .split_once('.')
+                    .is_some_and(|(_, column)| column == name)
+            })
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Convert each value of a row to its column's type (see `Column::coerce_value`)
+    pub fn coerce_row(&self, row: Row, mode: TypeMode) -> Row {
+        // Leave malformed rows for `validate_row` to reject
+        if row.values.len() != self.columns.len() {
+            return row;
+        }
+
+        Row::new(
+            row.values
+                .into_iter()
+                .zip(&self.columns)
+                .map(|(value, column)| column.coerce_value(value, mode))
+                .collect(),
+        )
     }
 
     /// Validate that a row matches this schema
@@ -339,6 +1684,7 @@ impl ResultSet {
     }
 
     /// Format the result set as a string table
+    #[allow(clippy::inherent_to_string)]
     pub fn to_string(&self) -> String {
         if self.schema.columns.is_empty() {
             return "Empty result set".to_string();
@@ -371,13 +1717,13 @@ impl ResultSet {
         for (i, header) in headers.iter().enumerate() {
             result.push_str("| ");
             result.push_str(&format!("{:width$}", header, width = col_widths[i]));
-            result.push_str(" ");
+            result.push(' ');
         }
         result.push_str("|\n");
 
         // Separator row
         for width in &col_widths {
-            result.push_str("+");
+            result.push('+');
             result.push_str(&"-".repeat(width + 2));
         }
         result.push_str("+\n");
@@ -388,7 +1734,7 @@ impl ResultSet {
                 result.push_str("| ");
                 let val_str = format!("{}", val);
                 result.push_str(&format!("{:width$}", val_str, width = col_widths[i]));
-                result.push_str(" ");
+                result.push(' ');
             }
             result.push_str("|\n");
         }
@@ -423,7 +1769,75 @@ impl ResultSet {
     }
 
     /// Check if the result set is empty
+    #[allow(dead_code)]
     pub fn is_empty(&self) -> bool {
         self.rows.is_empty()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(a: Value, op: ArithmeticOperator, b: Value) -> Result<Value, TypeError> {
+        a.arithmetic(&op, &b)
+    }
+
+    #[test]
+    fn float_arithmetic_follows_ieee() {
+        use ArithmeticOperator::*;
+        let float = |result: Result<Value, TypeError>| match result {
+            Ok(Value::Float(x)) => x,
+            other => panic!("not a FLOAT: {:?}", other),
+        };
+        assert_eq!(
+            float(apply(Value::Float(1.0), Divide, Value::Integer(0))),
+            f64::INFINITY
+        );
+        assert_eq!(
+            float(apply(Value::Float(-1.0), Divide, Value::Float(0.0))),
+            f64::NEG_INFINITY
+        );
+        assert!(float(apply(Value::Float(0.0), Divide, Value::Integer(0))).is_nan());
+        assert!(float(apply(Value::Float(5.0), Modulo, Value::Float(0.0))).is_nan());
+        assert_eq!(
+            float(apply(Value::Float(1e308), Multiply, Value::Integer(10))),
+            f64::INFINITY
+        );
+    }
+
+    #[test]
+    fn exact_division_by_zero_is_an_error() {
+        use ArithmeticOperator::*;
+        let decimal = Value::Decimal("1.5".parse().unwrap());
+        for (a, op) in [
+            (Value::Integer(1), Divide),
+            (Value::Integer(1), Modulo),
+            (decimal.clone(), Divide),
+            (decimal, Modulo),
+        ] {
+            assert!(apply(a, op, Value::Integer(0)).is_err());
+        }
+    }
+
+    #[test]
+    fn nan_and_infinities_are_ordered() {
+        let mut values = [
+            Value::Float(f64::NAN),
+            Value::Float(1.0),
+            Value::Float(f64::INFINITY),
+            Value::Float(f64::NEG_INFINITY),
+        ];
+        values.sort_by(|a, b| a.sort_cmp(b));
+        let shown: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        assert_eq!(shown, ["-Infinity", "1.0", "Infinity", "NaN"]);
+        let nan = Value::Float(f64::NAN);
+        assert!(nan.compare(&Operator::Eq, &nan).unwrap().is_true());
+        assert!(
+            Value::Float(0.0)
+                .compare(&Operator::Eq, &Value::Float(-0.0))
+                .unwrap()
+                .is_true()
+        );
+    }
+}
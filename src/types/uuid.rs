@@ -0,0 +1,130 @@
+//! UUID values: parsing, formatting and generation
+//!
+//! UUIDs are stored as 16 bytes and persisted as a single 128-bit integer.
+//! Version 4 UUIDs are random; version 7 UUIDs start with a millisecond Unix
+//! timestamp so they sort by creation time.
+
+use super::TypeError;
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt::{self, Display},
+    str::FromStr,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// A 128-bit universally unique identifier
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(from = "u128", into = "u128")]
+pub struct Uuid([u8; 16]);
+
+impl Uuid {
+    /// The UUID with the given bytes
+    pub fn from_bytes(bytes: [u8; 16]) -> Self {
+        Uuid(bytes)
+    }
+
+    /// The 16 bytes of the UUID
+    pub fn as_bytes(&self) -> &[u8; 16] {
+        &self.0
+    }
+
+    /// A random (version 4) UUID
+    pub fn new_v4() -> Self {
+        Uuid(random_bytes()).with_version(4)
+    }
+
+    /// A time-ordered (version 7) UUID
+    ///
+    /// The first 48 bits are the Unix time in milliseconds and the next 12
+    /// bits count UUIDs generated within the same millisecond, so UUIDs from
+    /// this process always increase; the remaining bits are random.
+    pub fn new_v7() -> Self {
+        // Millisecond timestamp and sequence number, as `millis << 12 | seq`
+        static LAST: AtomicU64 = AtomicU64::new(0);
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        let previous = LAST
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |last| {
+                Some((millis << 12).max(last + 1))
+            })
+            .unwrap_or(0);
+        let stamp = (millis << 12).max(previous + 1);
+
+        // 48 timestamp bits, 4 version bits, 12 sequence bits, then random
+        let bits = ((stamp >> 12) as u128) << 80 | ((stamp & 0xfff) as u128) << 64;
+        let bits = bits | u64::from_be_bytes(random_bytes()) as u128;
+        Uuid::from(bits).with_version(7)
+    }
+
+    /// Set the version number and the RFC 9562 variant bits
+    fn with_version(mut self, version: u8) -> Self {
+        self.0[6] = (self.0[6] & 0x0f) | (version << 4);
+        self.0[8] = (self.0[8] & 0x3f) | 0x80;
+        self
+    }
+}
+
+impl From<u128> for Uuid {
+    fn from(bits: u128) -> Self {
+        Uuid(bits.to_be_bytes())
+    }
+}
+
+impl From<Uuid> for u128 {
+    fn from(uuid: Uuid) -> Self {
+        u128::from_be_bytes(uuid.0)
+    }
+}
+
+impl FromStr for Uuid {
+    type Err = TypeError;
+
+    /// Parse the canonical form `xxxxxxxx-xxxx-xxxx-xxxx-xxxxxxxxxxxx`, also
+    /// accepting upper case, surrounding braces and no hyphens
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || TypeError::InvalidValue("UUID".to_string(), format!("'{}'", s));
+        let text = s.trim();
+        let text = text
+            .strip_prefix('{')
+            .and_then(|inner| inner.strip_suffix('}'))
+            .unwrap_or(text);
+
+        let hyphens: Vec<usize> = text.match_indices('-').map(|(i, _)| i).collect();
+        if !hyphens.is_empty() && hyphens != [8, 13, 18, 23] {
+            return Err(error());
+        }
+        let digits: String = text.chars().filter(|c| *c != '-').collect();
+        if digits.len() != 32 || !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return Err(error());
+        }
+        u128::from_str_radix(&digits, 16)
+            .map(Uuid::from)
+            .map_err(|_| error())
+    }
+}
+
+impl Display for Uuid {
+    /// Lower-case canonical form
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, byte) in self.0.iter().enumerate() {
+            if matches!(i, 4 | 6 | 8 | 10) {
+                write!(f, "-")?;
+            }
+            write!(f, "{:02x}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+/// Random bytes from the operating system's secure random number generator
+///
+/// Panics if the generator is unavailable, as no UUID can be made safely
+/// without it.
+fn random_bytes<const N: usize>() -> [u8; N] {
+    let mut bytes = [0; N];
+    getrandom::fill(&mut bytes).expect("the operating system random number generator failed");
+    bytes
+}